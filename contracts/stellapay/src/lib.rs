@@ -2,16 +2,52 @@
 
 mod test;
 use soroban_sdk::{
-    contract, contractimpl, contracterror, contracttype, contractevent, symbol_short, 
-    Address, Env, Symbol, token, Vec,
+    contract, contractclient, contractimpl, contracterror, contracttype, contractevent, symbol_short,
+    Address, BytesN, Env, IntoVal, Map, Symbol, token, TryFromVal, Val, Vec,
 };
 
-const MIN_DURATION: u64 = 3600; // 1 hour
-const MAX_DURATION: u64 = 365 * 24 * 3600; // 1 year
-const TTL_BUFFER: u64 = 30 * 24 * 3600; // 30 days
+const MIN_DURATION: u64 = 3600; // 1 hour; lowest duration floor a config may set
+const MAX_DURATION: u64 = 365 * 24 * 3600; // 1 year; highest duration ceiling a config may set
+const MIN_TTL_BUFFER: u64 = 24 * 3600; // 1 day; shortest post-obligation retention a config may set
+const MAX_TTL_BUFFER: u64 = 365 * 24 * 3600; // 1 year; longest post-obligation retention a config may set
+const DEFAULT_TTL_BUFFER: u64 = 30 * 24 * 3600; // Retention used if the config can't be read
+const LEDGER_SECS: u64 = 5; // Average ledger close time, for converting secs to TTL ledgers
 const COUNTER_TTL_SECS: u32 = 365 * 24 * 3600;
-const DISPUTE_PERIOD: u64 = 7 * 24 * 3600; // 7 days for client to approve/dispute
+const MIN_REVIEW_PERIOD: u64 = 24 * 3600; // 1 day
+const MAX_REVIEW_PERIOD: u64 = 30 * 24 * 3600; // 30 days
+const MAX_FREEZE_PERIOD: u64 = 14 * 24 * 3600; // Arbiter freezes lapse automatically after 14 days
+const MAX_DISPUTE_HISTORY: u32 = 32; // Oldest dispute entries are dropped beyond this
+const MAX_ESCROW_HISTORY: u32 = 100; // Oldest audit log entries are dropped beyond this
+const MAX_DEADLINE_EXTENSION: u64 = 30 * 24 * 3600; // Total extra time an arbiter may grant during disputes
+const ABANDONED_DISPUTE_AGE: u64 = 60 * 24 * 3600; // Dispute age after which the admin may replace the arbiter
+const ROTATION_DELAY: u64 = 7 * 24 * 3600; // Public waiting period before a beneficiary key rotation applies
+const ACHIEVEMENT_THRESHOLDS: [u32; 3] = [5, 25, 100]; // Completed escrows that unlock an achievement
+const SELF_UNPAUSE_DELAY: u64 = 2 * 24 * 3600; // Wait before a self-paused address can take part again
+const MAX_TIMELOCK_DELAY: u64 = 30 * 24 * 3600; // Longest delay a config may put on admin actions
+const SCHEMA_VERSION: u32 = 2; // Layout of stored EscrowData; bump alongside a conversion in `migrate`
+const CONTRACT_VERSION: u32 = 10_000; // Semantic version as major * 10_000 + minor * 100 + patch
+const BUILD_TAG: &str = match option_env!("STELLAPAY_BUILD_TAG") {
+    Some(tag) => tag, // e.g. the short commit hash, set by the release build
+    None => "dev",
+};
+const MAX_FEE_BPS: u32 = 1000; // Platform fee hard cap, 10%
+const MAX_PREMIUM_BPS: u32 = 500; // Insurance premium hard cap, 5%
+const MAX_REFERRAL_BPS: u32 = 1000; // Referral cut hard cap, 10%
+const MAX_MARKETPLACE_FEE_BPS: u32 = 1000; // Marketplace fee hard cap, 10%
+const MAX_RESUBMIT_COOLDOWN: u64 = 7 * 24 * 3600; // Longest wait a depositor may impose after a revision request
+const MAX_PAYROLL_RECIPIENTS: u32 = 50; // Most recipients one payroll deposit may cover
+const MAX_BOUNTY_ENTRIES: u32 = 100; // Most entries one bounty accepts
+const MAX_TIMESHEETS: u32 = 200; // Most timesheets one hourly escrow keeps
+const MAX_PROJECT_ESCROWS: u32 = 100; // Most escrows one project groups
+const MAX_GRANTED_ROLES: u32 = 10; // Most delegates and observers one escrow may have
+const MAX_PAGE_SIZE: u32 = 100; // Most ids a listing call returns at once
+const INDEX_BUCKET_SIZE: u32 = 50; // Ids per storage entry of an address, token or status index
+const MAX_CREATE_WINDOW: u64 = 24 * 3600; // Longest creation rate-limit window a config may set
+const DEADLINE_WARNING: u64 = 48 * 3600; // How far ahead of a due date `ping` announces it
+const MAX_SUBMISSION_CUTOFF: u64 = 7 * 24 * 3600; // Longest pre-deadline window in which submissions are blocked
 
+/// Numeric codes are part of the public interface: never renumber or reuse
+/// a code, only append. Keep `error_name` in sync when adding variants.
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum EscrowError {
@@ -32,6 +68,28 @@ pub enum EscrowError {
     WorkStarted = 15,
     MilestoneAlreadySubmitted = 16,
     MilestoneNotSubmitted = 17,
+    SubmissionCooldown = 18,
+    SettlementNotFound = 19,
+    Frozen = 20,
+    AppealPending = 21,
+    SubmissionWindowClosed = 22,
+    NotInitialized = 23,
+    AlreadyInitialized = 24,
+    InvalidConfig = 25,
+    RotationNotReady = 26,
+    Paused = 27,
+    TimelockActive = 28,
+    ActionNotFound = 29,
+    AmountOutOfBounds = 30,
+    Blocked = 31,
+    MarketplaceNotFound = 32,
+    InsufficientFees = 33,
+    InsufficientPool = 34,
+    AlreadyClaimed = 35,
+    RateLimited = 36,
+    WrongMode = 37,
+    NotYetDue = 38,
+    NotFunded = 39,
 }
 
 #[contracttype]
@@ -51,6 +109,7 @@ pub enum MilestoneStatus {
     Submitted,     // Freelancer claims it's done
     Approved,      // Client approved, payment made
     Disputed,      // Client disputes quality
+    Refunded,      // Closed unpaid, amount returned to the client
 }
 
 #[contracttype]
@@ -61,6 +120,162 @@ pub struct Milestone {
     pub status: MilestoneStatus,
     pub submitted_at: Option<u64>,
     pub approved_at: Option<u64>,
+    pub revision_requested_at: Option<u64>,
+    pub rationale_hash: Option<BytesN<32>>, // Anchor of the arbiter's written ruling
+    pub appealed: bool,            // Escalated to governance; the arbiter can no longer rule
+    pub checklist: Vec<BytesN<32>>, // Acceptance criteria item hashes fixed at creation
+    pub checklist_results: Option<Vec<bool>>, // Per-item pass/fail recorded on approval
+    pub checklist_done: Vec<bool>, // Per-item progress the beneficiary marks before submitting
+    pub deadline: Option<u64>,     // Per-milestone due date; late submissions incur the penalty
+    pub penalty_bps: u32,          // Late penalty fixed at submission, refunded to the depositor
+    pub recipient: Option<Address>, // Paid on approval instead of the beneficiary, e.g. a subcontractor
+}
+
+/// Late-delivery penalty: `bps_per_day` for each started day late, capped at `max_bps`.
+/// The default (all zero) charges nothing.
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PenaltySchedule {
+    pub bps_per_day: u32,
+    pub max_bps: u32,
+}
+
+/// Vesting measured from work start: nothing unlocks before `cliff`, then `amount_per_period`
+/// unlocks at the cliff and again every `period` until the escrow is fully vested
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VestingSchedule {
+    pub cliff: u64,
+    pub period: u64,
+    pub amount_per_period: i128,
+}
+
+/// How an escrow's funds reach the beneficiary
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum PayoutMode {
+    #[default]
+    Milestones,    // Each milestone is paid on approval
+    Stream,        // The single milestone vests linearly from work start to the deadline
+    Vesting(VestingSchedule), // The single milestone unlocks in steps after a cliff
+    Subscription(u64), // Each milestone pre-funds one period of this many secs, claimable once it elapses
+    Bounty,        // Open to entries; the depositor's chosen winner becomes the beneficiary and is paid
+    Hourly(i128),  // The single milestone caps time billed at this rate per hour through timesheets
+    Retainer(u64), // Like a subscription, but anyone releases elapsed periods the depositor hasn't disputed
+}
+
+/// Who puts up an escrow's funds, and when
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum FundingMode {
+    #[default]
+    Upfront,       // The depositor deposits the whole total at creation
+    Crowdfunded,   // Anyone funds it through `contribute`; work starts once fully funded
+    PerMilestone,  // The first milestone is deposited at creation, the rest with `fund_milestone`
+}
+
+/// Terms of a new escrow. Optional policies left unset fall back to the platform defaults.
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct CreateParams {
+    pub depositor: Address,
+    pub beneficiary: Address,
+    pub arbiter: Option<Address>,  // None draws an arbiter from the registered pool
+    pub milestone_amounts: Vec<i128>,
+    pub token: Address,
+    pub duration: u64,
+    pub private_events: bool,      // Events carry ids only, no amounts or counterparties
+    pub checklists: Vec<Vec<BytesN<32>>>, // Empty, or one list of acceptance item hashes per milestone
+    pub review_period: Option<u64>,
+    pub resubmit_cooldown: Option<u64>,
+    pub submission_cutoff: Option<u64>,
+    pub governance: Option<Address>,
+    pub insured: bool,             // Pay the insurance premium on top of the deposit
+    pub milestone_deadlines: Vec<u64>, // Empty, or one due offset (secs from creation) per milestone
+    pub penalty: PenaltySchedule,
+    pub bonus: i128,               // Early-delivery bonus deposited on top of the milestones; 0 for none
+    pub kill_fee_bps: u32,         // Share owed to the beneficiary if the client cancels after work starts
+    pub referrer: Option<Address>, // Whoever sourced the work; takes `referral_bps` of each payout
+    pub referral_bps: u32,
+    pub mode: PayoutMode,          // Non-milestone modes take a single milestone amount
+    pub splits: Vec<(Address, u32)>, // Empty, or team members and their bps of each payout, summing to 10_000
+    pub milestone_recipients: Vec<Option<Address>>, // Empty, or a payout override (or None) per milestone
+    pub funding: FundingMode,      // Milestones mode only for `PerMilestone`
+    pub transferable: bool,        // Depositor consents to the beneficiary selling its right to payouts
+    pub project: Option<u32>,      // Project of the depositor's to group the escrow under
+    pub payout_tail: PayoutTail,   // Default pays approved milestones in full at once
+}
+
+/// Spreads each approved milestone's payout: `upfront_bps` is paid at once and the rest
+/// accrues per second over `duration`, claimable with `claim_accrued`
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PayoutTail {
+    pub upfront_bps: u32,
+    pub duration: u64,             // Secs the remainder accrues over; 0 for no tail
+}
+
+/// Part of an approved milestone's payout still accruing under the escrow's payout tail
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Accrual {
+    pub milestone_index: u32,
+    pub amount: i128,
+    pub claimed: i128,
+    pub start: u64,
+    pub end: u64,                  // Fully claimable from here
+    pub recipient: Option<Address>, // The milestone's payout override, if any
+}
+
+/// Platform-wide settings managed by the admin
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub fee_bps: u32,              // Platform fee in basis points, capped at MAX_FEE_BPS
+    pub fee_recipient: Address,    // Receives fees withdrawn from the treasury
+    pub min_duration: u64,
+    pub max_duration: u64,
+    pub dispute_period: u64,       // Default review period for new escrows
+    pub timelock_delay: u64,       // Wait between proposing and executing an admin action
+    pub premium_bps: u32,          // Insurance premium on the escrow total for insured escrows
+    pub ttl_buffer: u64,           // Secs escrow data outlives its last obligation
+    pub create_limit: u32,         // Escrows one depositor may create per window; 0 for no limit
+    pub create_window: u64,
+    pub payout_cosign: bool,       // Payout-address changes also need the depositor's signature
+}
+
+/// Escrows a depositor has created in the current rate-limit window
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CreateWindow {
+    pub started_at: u64,
+    pub count: u32,
+}
+
+/// Per-token limits on escrow size, inclusive
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AmountBounds {
+    pub min_milestone: i128,
+    pub max_milestone: i128,
+    pub min_total: i128,
+    pub max_total: i128,
+}
+
+/// Sensitive admin operation that only takes effect after the timelock delay
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AdminAction {
+    SetConfig(Config),
+    Upgrade(BytesN<32>),           // Hash of already-uploaded contract wasm
+    ReassignArbiter(u32, Address), // Escrow id and replacement for an arbiter who abandoned a dispute
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingAction {
+    pub action: AdminAction,
+    pub eta: u64,                  // Earliest time the action can be executed
 }
 
 #[contracttype]
@@ -76,528 +291,8257 @@ pub struct EscrowData {
     pub status: EscrowStatus,
     pub milestones: Vec<Milestone>,
     pub work_started: bool,
+    pub resubmit_cooldown: u64,    // Minimum secs between a revision request and resubmission
+    pub close_reason: Option<BytesN<32>>, // Reason/reference hash recorded when the escrow ends
+    pub frozen_until: u64,         // Arbiter freeze expiry; 0 if the one-time freeze was never used
+    pub private_events: bool,      // Events carry ids only, no amounts or counterparties
+    pub governance: Option<Address>, // Contract whose verdict settles appealed disputes
+    pub review_period: u64,        // Secs the client has to approve/dispute before the beneficiary may claim
+    pub disputed_since: Option<u64>, // Deadline countdown is paused from here until disputes close
+    pub deadline_extended: u64,    // Secs the arbiter has added to the deadline, capped in total
+    pub submission_cutoff: u64,    // Submissions are blocked this many secs before the deadline
+    pub reported: bool,            // A party has filed a fraud report against this escrow
+    pub fee_bps: u32,              // Platform fee locked in from the config at creation
+    pub insured: bool,             // Premium paid into the insurance pool; eligible for claims
+    pub penalty: PenaltySchedule,
+    pub bonus: i128,               // Early-delivery bonus pool fixed at creation
+    pub bonus_held: i128,          // Unearned part of the bonus still held; refunded at close
+    pub kill_fee_bps: u32,
+    pub referrer: Option<Address>,
+    pub referral_bps: u32,
+    pub marketplace: Option<Address>, // Marketplace the escrow was created through
+    pub marketplace_fee_bps: u32,  // Marketplace's fee locked in at creation
+    pub mode: PayoutMode,
+    pub started_at: u64,           // When work started; 0 before
+    pub claimed: i128,             // Paid out of the unapproved milestone under a stream or vesting schedule
+    pub splits: Vec<(Address, u32)>, // Payouts divided by bps among these instead of going to the beneficiary
+    pub funding: FundingMode,
+    pub contributions: Vec<(Address, i128)>, // Who has funded a non-upfront escrow, and how much
+    pub claim: ClaimState,         // Who holds the right to the beneficiary's payouts
 }
 
-#[contractevent]
-#[derive(Clone)]
-pub struct EscrowCreated {
-    pub id: u32,
-    pub depositor: Address,
-    pub beneficiary: Address,
+/// Milestone as stored under schema 1; only read by `migrate`
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct MilestoneV1 {
+    pub description: Symbol,
     pub amount: i128,
+    pub status: MilestoneStatus,
+    pub submitted_at: Option<u64>,
+    pub approved_at: Option<u64>,
+    pub revision_requested_at: Option<u64>,
+    pub rationale_hash: Option<BytesN<32>>,
+    pub appealed: bool,
+    pub checklist: Vec<BytesN<32>>,
+    pub checklist_results: Option<Vec<bool>>,
 }
 
-#[contractevent]
-#[derive(Clone)]
-pub struct MilestoneSubmitted {
-    pub id: u32,
-    pub milestone_index: u32,
+/// Escrow as stored under schema 1; only read by `migrate`
+#[contracttype]
+#[derive(Clone, Debug)]
+pub struct EscrowDataV1 {
+    pub depositor: Address,
+    pub beneficiary: Address,
+    pub arbiter: Address,
+    pub token: Address,
+    pub total_amount: i128,
+    pub paid_amount: i128,
+    pub deadline: u64,
+    pub status: EscrowStatus,
+    pub milestones: Vec<MilestoneV1>,
+    pub work_started: bool,
+    pub resubmit_cooldown: u64,
+    pub close_reason: Option<BytesN<32>>,
+    pub frozen_until: u64,
+    pub private_events: bool,
+    pub governance: Option<Address>,
+    pub review_period: u64,
+    pub disputed_since: Option<u64>,
+    pub deadline_extended: u64,
+    pub submission_cutoff: u64,
+    pub reported: bool,
+    pub fee_bps: u32,
+    pub fee_recipient: Address,    // Dropped; fees go to the recipient in the current config
 }
 
-#[contractevent]
-#[derive(Clone)]
-pub struct MilestoneApproved {
-    pub id: u32,
-    pub milestone_index: u32,
-    pub amount: i128,
+/// Whether the beneficiary's right to payouts can change hands, and who holds it
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClaimState {
+    Fixed,                         // Always paid to the beneficiary
+    Transferable,                  // Still with the beneficiary, but the depositor allowed a transfer
+    Held(Address),                 // Transferred; this address is paid in the beneficiary's place
 }
 
-#[contractevent]
-#[derive(Clone)]
-pub struct WorkStarted {
-    pub id: u32,
-    pub started_at: u64,
+/// Hours logged against an hourly escrow, paid at its rate once the depositor approves
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Timesheet {
+    pub hours: u32,
+    pub amount: i128,              // `hours` at the escrow's rate
+    pub note_hash: BytesN<32>,     // Off-chain description of the work done
+    pub status: MilestoneStatus,   // Submitted until approved or disputed; Approved once a ruling settles it
+    pub logged_at: u64,
 }
 
-fn sym_counter() -> Symbol {
-    symbol_short!("counter")
+/// Where a scheduled payment stands
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ScheduleState {
+    Locked,
+    Paid,
+    Cancelled,
 }
 
-fn sym_escrows() -> Symbol {
-    symbol_short!("escrows")
+/// Funds locked for one beneficiary until a set date, with no milestones
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduledPayment {
+    pub depositor: Address,
+    pub beneficiary: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub unlock_at: u64,            // Payable from here; cancellable by the depositor until then
+    pub fee_bps: u32,              // Platform fee locked in from the config at creation
+    pub state: ScheduleState,
 }
 
-fn sym_lock() -> Symbol {
-    symbol_short!("lock")
+/// Where an invoice stands
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RequestStatus {
+    Open,
+    Paid,
+    Escrowed(u32),                 // Funded into the escrow with this id
+    Declined,
 }
 
-fn escrow_key(id: u32) -> (Symbol, u32) {
-    (sym_escrows(), id)
+/// Invoice a beneficiary sends a depositor, settled directly or through a new escrow
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaymentRequest {
+    pub beneficiary: Address,
+    pub depositor: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub memo_hash: BytesN<32>,     // Off-chain invoice details
+    pub status: RequestStatus,
+    pub created_at: u64,
 }
 
-#[contract]
-pub struct EscrowContract;
+/// One deposit covering a pay cycle for several recipients
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Payroll {
+    pub depositor: Address,
+    pub token: Address,
+    pub recipients: Vec<(Address, i128)>,
+    pub paid: Vec<bool>,           // Per recipient, in the same order
+    pub release_at: u64,           // Recipients may claim from here; the depositor may release earlier
+    pub fee_bps: u32,              // Platform fee locked in from the config at creation
+}
 
-fn acquire_lock(e: &Env) -> Result<(), EscrowError> {
-    let key = sym_lock();
-    let locked: bool = e.storage().instance().get(&key).unwrap_or(false);
-    if locked {
-        return Err(EscrowError::Reentrancy);
-    }
-    e.storage().instance().set(&key, &true);
-    Ok(())
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DisputeAction {
+    Opened,
+    EvidenceAdded(BytesN<32>), // Hash of a document a party submitted for the arbiter
+    SettlementOffered,
+    Settled,       // Parties agreed without the arbiter
+    Resolved,      // Arbiter (or governance, for appeals) ruled
+    Appealed,      // Escalated to the governance contract
 }
 
-fn release_lock(e: &Env) {
-    let key = sym_lock();
-    e.storage().instance().set(&key, &false);
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisputeEntry {
+    pub action: DisputeAction,
+    pub actor: Address,
+    pub at: u64,
+    pub amount: i128,              // Milestone amount when opened, 0 for evidence, beneficiary's share otherwise
 }
 
-fn load_escrow(e: &Env, id: u32) -> Result<EscrowData, EscrowError> {
-    let key = escrow_key(id);
-    e.storage()
-        .persistent()
-        .get(&key)
-        .ok_or(EscrowError::EscrowNotFound)
+/// One mutating call in an escrow's audit log
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub at: u64,
+    pub actor: Address,            // Caller, or the contract itself for permissionless calls
+    pub action: Symbol,            // Name of the entrypoint
+    pub milestone_index: Option<u32>,
+    pub reason: Option<BytesN<32>>, // Reason/reference hash given when the call closed the escrow
 }
 
-fn store_escrow(e: &Env, id: u32, escrow: &EscrowData) {
-    let key = escrow_key(id);
-    e.storage().persistent().set(&key, escrow);
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FundingStatus {
+    pub funded: i128,              // Actually deposited into the contract
+    pub unfunded: i128,            // Committed but not yet deposited
+    pub paid: i128,                // Released to the beneficiary
+    pub refunded: i128,            // Returned to the depositor (refunds and dispute splits)
+    pub held: i128,                // Still locked for unpaid milestones
+}
 
-    let now = e.ledger().timestamp();
-    let ttl_u64 = if escrow.deadline > now {
-        (escrow.deadline.saturating_sub(now)).saturating_add(TTL_BUFFER)
-    } else {
-        TTL_BUFFER
-    };
+/// Whether an action would currently succeed. `reason` is the `EscrowError` code it would
+/// fail with (see `describe_error`), or 0 when allowed.
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capability {
+    pub allowed: bool,
+    pub reason: u32,
+}
 
-    let ttl_u32: u32 = ttl_u64.try_into().unwrap_or(u32::MAX);
-    let now_u32: u32 = now.try_into().unwrap_or(u32::MAX);
+/// Platform-wide escrow counts. Each counts transitions, so an escrow disputed twice adds two.
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlatformStats {
+    pub created: u32,
+    pub completed: u32,
+    pub refunded: u32,
+    pub disputed: u32,
+}
 
-    e.storage().persistent().extend_ttl(&key, now_u32, ttl_u32);
+/// Lifetime activity of one address across all escrows
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct UserStats {
+    pub as_client: u32,
+    pub as_freelancer: u32,
+    pub completed: u32,            // Escrows released in full, in either role
+    pub total_earned: i128,        // Milestone and direct payouts received, before fees
+    pub total_spent: i128,         // Milestone and direct payouts sent
+    pub completion_bps: u32,       // `completed` over all escrows, in basis points
 }
 
-fn peek_next_id(e: &Env) -> Result<u32, EscrowError> {
-    let k = sym_counter();
-    let current: u32 = e.storage().persistent().get(&k).unwrap_or(0u32);
-    let next = current.checked_add(1).ok_or(EscrowError::CounterOverflow)?;
-    Ok(next)
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Progress {
+    pub approved_milestones: u32,
+    pub total_milestones: u32,
+    pub approved_amount: i128,
+    pub total_amount: i128,
 }
 
-fn finalize_counter(e: &Env, id: u32) {
-    let k = sym_counter();
-    e.storage().persistent().set(&k, &id);
-    e.storage().persistent().extend_ttl(&k, 0u32, COUNTER_TTL_SECS);
+/// Named group of a depositor's escrows for one engagement
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Project {
+    pub owner: Address,
+    pub name_hash: BytesN<32>,     // Off-chain project name and details
+    pub created_at: u64,
 }
 
-fn safe_transfer(
-    e: &Env,
-    token_addr: &Address,
-    from: &Address,
-    to: &Address,
-    amount: &i128,
-) -> Result<(), EscrowError> {
-    let client = token::Client::new(e, token_addr);
-    client.transfer(from, to, amount);
-    Ok(())
+/// Totals across a project's escrows
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProjectSummary {
+    pub escrows: u32,
+    pub active: u32,               // Pending, in progress or disputed
+    pub completed: u32,
+    pub refunded: u32,
+    pub disputed: u32,
+    pub total_amount: i128,
+    pub paid_amount: i128,
 }
 
-#[contractimpl]
-impl EscrowContract {
-    /// Create escrow with milestones
-    pub fn create(
-        e: Env,
-        depositor: Address,
-        beneficiary: Address,
-        arbiter: Address,
-        milestone_amounts: Vec<i128>,
-        token: Address,
-        duration: u64,
-    ) -> Result<u32, EscrowError> {
-        depositor.require_auth();
+/// Headline numbers of an escrow without its milestone list
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowSummary {
+    pub depositor: Address,
+    pub beneficiary: Address,
+    pub arbiter: Address,
+    pub token: Address,
+    pub total_amount: i128,
+    pub paid_amount: i128,
+    pub status: EscrowStatus,
+    pub deadline: u64,             // Effective deadline, excluding time spent in an open dispute
+    pub milestones: u32,
+    pub not_started: u32,
+    pub submitted: u32,
+    pub approved: u32,
+    pub disputed: u32,
+    pub refunded: u32,
+}
 
-        if beneficiary == depositor {
-            return Err(EscrowError::InvalidBeneficiary);
-        }
-        if arbiter == depositor || arbiter == beneficiary {
-            return Err(EscrowError::InvalidArbiter);
-        }
-        if duration < MIN_DURATION || duration > MAX_DURATION {
-            return Err(EscrowError::InvalidDuration);
-        }
-        if milestone_amounts.is_empty() {
-            return Err(EscrowError::InvalidMilestone);
-        }
+/// Active escrows between two addresses, in either direction
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PairSummary {
+    pub count: u32,
+    pub total_locked: i128,        // Outstanding milestone funds still held by the contract
+    pub next_deadline: Option<u64>,
+}
 
-        let mut total_amount: i128 = 0;
-        for amount in milestone_amounts.iter() {
-            if amount <= 0 {
-                return Err(EscrowError::ZeroAmount);
-            }
-            total_amount = total_amount.checked_add(amount)
-                .ok_or(EscrowError::InvalidMilestone)?;
-        }
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocumentRevision {
+    pub version: u32,              // 1-based
+    pub hash: BytesN<32>,
+    pub proposed_by: Address,
+    pub proposed_at: u64,
+    pub acknowledged: bool,        // Counterparty signed off; only then are these the terms
+}
 
-        let now = e.ledger().timestamp();
-        let deadline = now.checked_add(duration)
-            .ok_or(EscrowError::InvalidDeadline)?;
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisputeStats {
+    pub opened: u32,               // Disputes raised as depositor
+    pub won: u32,                  // Rulings that gave this party the larger share
+    pub lost: u32,
+    pub arbitrated: u32,           // Rulings issued as arbiter
+}
 
-        acquire_lock(&e)?;
+/// Per-beneficiary completion history backing achievement badges
+#[contracttype]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CompletionRecord {
+    pub completed: u32,            // Escrows released as beneficiary
+    pub streak: u32,               // Consecutive completions paid in full
+    pub best_streak: u32,
+}
 
-        let id = peek_next_id(&e)?;
+/// Forward conversion rate for a submitted milestone; binding once both parties quote it
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateLock {
+    pub rate: i128,
+    pub proposed_by: Address,
+    pub locked_at: Option<u64>,    // Set when the counterparty matched the rate
+}
 
-        let mut milestones = Vec::new(&e);
-        for amount in milestone_amounts.iter() {
-            milestones.push_back(Milestone {
-                description: symbol_short!("milestone"),
-                amount,
-                status: MilestoneStatus::NotStarted,
-                submitted_at: None,
-                approved_at: None,
-            });
-        }
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SettlementOffer {
+    pub offered_by: Address,
+    pub pay_to_beneficiary: i128,
+}
 
-        let escrow = EscrowData {
-            depositor: depositor.clone(),
-            beneficiary: beneficiary.clone(),
-            arbiter: arbiter.clone(),
-            token: token.clone(),
-            total_amount,
-            paid_amount: 0,
-            deadline,
-            status: EscrowStatus::Pending,
-            milestones,
-            work_started: false,
-        };
+/// Replacement beneficiary jointly attested by the depositor and arbiter
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BeneficiaryRotation {
+    pub new_beneficiary: Address,
+    pub depositor_attested: bool,
+    pub arbiter_attested: bool,
+    pub ready_at: u64,             // Set once both have attested; 0 until then
+}
 
-        let tf_res = safe_transfer(&e, &token, &depositor, &e.current_contract_address(), &total_amount);
-        if tf_res.is_err() {
-            release_lock(&e);
-            return Err(EscrowError::TransferFailed);
-        }
+#[contracttype]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Depositor,
+    Beneficiary,
+    Arbiter,
+    Delegate,      // Reviews milestones on the depositor's behalf
+    Observer,      // Follows the escrow without acting on it
+    Stranger,      // No relationship to the escrow
+}
 
-        store_escrow(&e, id, &escrow);
-        finalize_counter(&e, id);
+#[contracttype]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RiskReport {
+    pub score: u32,                // 0 (no signals) to 100 (maximum risk)
+    pub disputed_milestones: u32,
+    pub stalled_disputes: u32,     // Disputes open longer than the review period
+    pub stale_reviews: u32,        // Submissions the client left unanswered past the review period
+    pub overdue: bool,             // Deadline passed with funds still outstanding
+    pub shortfall: bool,           // Contract balance below this escrow's outstanding amount
+}
 
-        EscrowCreated {
+#[contractevent]
+#[derive(Clone)]
+pub struct EscrowCreated {
+    pub id: u32,
+    pub depositor: Address,
+    pub beneficiary: Address,
+    pub arbiter: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub milestone_amounts: Vec<i128>, // Enough to index the escrow without reading storage
+    pub deadline: u64,
+    pub insured: bool,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+// Minimal-event counterparts published for escrows created with `private_events`
+
+#[contractevent]
+#[derive(Clone)]
+pub struct EscrowCreatedMinimal {
+    pub id: u32,
+    pub insured: bool,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct MilestoneApprovedMinimal {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct SettlementOfferedMinimal {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct SettlementAcceptedMinimal {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct MilestoneSubmitted {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ChecklistItemMarked {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub item: u32,
+    pub done: bool,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct MilestoneApproved {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub amount: i128,
+    pub tip: i128,                 // Paid by the client on top of the milestone; 0 for none
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct RevisionRequested {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct SettlementOffered {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub offered_by: Address,
+    pub pay_to_beneficiary: i128,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct SettlementAccepted {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub pay_to_beneficiary: i128,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct RateLocked {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub rate: i128,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct PayoutsFrozen {
+    pub id: u32,
+    pub until: u64,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct EscrowCancelled {
+    pub id: u32,
+    pub kill_fee: i128,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct EscrowCancelledMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct VestedClaimed {
+    pub id: u32,
+    pub amount: i128,
+    pub claimed: i128,             // Total claimed under the schedule so far
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct VestedClaimedMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AccruedClaimed {
+    pub id: u32,
+    pub amount: i128,
+    pub remaining: i128,           // Still accruing or unclaimed across the escrow's tails
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AccruedClaimedMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct PeriodsClaimed {
+    pub id: u32,
+    pub periods: u32,
+    pub amount: i128,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct PeriodsClaimedMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ContributionReceived {
+    pub id: u32,
+    pub contributor: Address,
+    pub amount: i128,
+    pub remaining: i128,           // Still needed before work can start
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ContributionReceivedMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct MilestoneFunded {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub amount: i128,
+    pub remaining: i128,           // Committed but still to be deposited
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct MilestoneFundedMinimal {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct BountyEntrySubmitted {
+    pub id: u32,
+    pub entrant: Address,
+    pub work_hash: BytesN<32>,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct BountyEntrySubmittedMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct BountyAwarded {
+    pub id: u32,
+    pub winner: Address,
+    pub amount: i128,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct BountyAwardedMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct TimeLogged {
+    pub id: u32,
+    pub sheet: u32,
+    pub hours: u32,
+    pub amount: i128,
+    pub note_hash: BytesN<32>,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct TimeLoggedMinimal {
+    pub id: u32,
+    pub sheet: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct TimeApproved {
+    pub id: u32,
+    pub sheet: u32,
+    pub amount: i128,
+    pub claimed: i128,             // Total paid for time so far
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct TimeApprovedMinimal {
+    pub id: u32,
+    pub sheet: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct PaymentScheduled {
+    pub id: u32,
+    pub depositor: Address,
+    pub beneficiary: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub unlock_at: u64,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ScheduledPaymentClosed {
+    pub id: u32,
+    pub state: ScheduleState,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct DirectPayment {
+    pub from: Address,
+    pub to: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub memo_hash: BytesN<32>,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ProjectCreated {
+    pub id: u32,
+    pub owner: Address,
+    pub name_hash: BytesN<32>,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct PaymentRequested {
+    pub id: u32,
+    pub beneficiary: Address,
+    pub depositor: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub memo_hash: BytesN<32>,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct PaymentRequestClosed {
+    pub id: u32,
+    pub status: RequestStatus,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct PayrollCreated {
+    pub id: u32,
+    pub depositor: Address,
+    pub token: Address,
+    pub total: i128,
+    pub recipients: u32,
+    pub release_at: u64,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct PayrollPaid {
+    pub id: u32,
+    pub recipient: Address,
+    pub amount: i128,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct EscrowRefunded {
+    pub id: u32,
+    pub to: Address,
+    pub amount: i128,              // Includes any unearned bonus returned with it
+    pub reason: Option<BytesN<32>>,
+    pub milestones: Vec<u32>,      // Indexes of the milestones whose funds went back
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct EscrowRefundedMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct EscrowToppedUp {
+    pub id: u32,
+    pub amount: i128,
+    pub total_amount: i128,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct EscrowToppedUpMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct DeadlineExtended {
+    pub id: u32,
+    pub deadline: u64,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct PayoutsUnfrozen {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct DisputeResolved {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub pay_to_beneficiary: i128,
+    pub rationale_hash: BytesN<32>,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct DisputeResolvedMinimal {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub rationale_hash: BytesN<32>,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct DisputeAppealed {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub governance: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AppealEnforced {
+    pub id: u32,
+    pub milestone_index: u32,
+    pub governance: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct DocumentProposed {
+    pub id: u32,
+    pub version: u32,
+    pub hash: BytesN<32>,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct DocumentAcknowledged {
+    pub id: u32,
+    pub version: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct RotationAttested {
+    pub id: u32,
+    pub attested_by: Role,
+    pub new_beneficiary: Address,
+    pub ready_at: u64,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct RotationCancelled {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct BeneficiaryRotated {
+    pub id: u32,
+    pub new_beneficiary: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct PayoutAddressChanged {
+    pub id: u32,
+    pub address: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct PayoutAddressChangedMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct RoleGranted {
+    pub id: u32,
+    pub address: Address,
+    pub role: Role,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct RoleRevoked {
+    pub id: u32,
+    pub address: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct RoleChangedMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ClaimTransferred {
+    pub id: u32,
+    pub from: Address,
+    pub to: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ClaimTransferredMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct DepositorTransferred {
+    pub id: u32,
+    pub from: Address,
+    pub to: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct DepositorTransferredMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct FraudReported {
+    pub id: u32,
+    pub reporter: Role,
+    pub report_hash: BytesN<32>,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AttestationAnchored {
+    pub id: u32,
+    pub party: Address,
+    pub hash: BytesN<32>,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AttestationAnchoredMinimal {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+/// Published on every status change alongside the specific event, so indexers can follow
+/// the lifecycle without special-casing each event type
+#[contractevent]
+#[derive(Clone)]
+pub struct EscrowStatusChanged {
+    pub id: u32,
+    pub from: EscrowStatus,
+    pub to: EscrowStatus,
+    pub actor: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct EscrowStatusChangedMinimal {
+    pub id: u32,
+    pub from: EscrowStatus,
+    pub to: EscrowStatus,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct EscrowCompleted {
+    pub id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AchievementUnlocked {
+    pub beneficiary: Address,
+    pub completed: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct DeadlineApproaching {
+    pub id: u32,
+    pub milestone_index: Option<u32>, // None for the escrow-wide deadline
+    pub due: u64,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct WorkStarted {
+    pub id: u32,
+    pub started_at: u64,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ArbiterRegistered {
+    pub arbiter: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ArbiterUnregistered {
+    pub arbiter: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ContractPaused {
+    pub admin: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ContractUnpaused {
+    pub admin: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AddressPaused {
+    pub addr: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AddressUnpauseRequested {
+    pub addr: Address,
+    pub unpause_at: u64,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AddressUnpaused {
+    pub addr: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AdminActionProposed {
+    pub action_id: u32,
+    pub action: AdminAction,
+    pub eta: u64,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AdminActionExecuted {
+    pub action_id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AdminActionCancelled {
+    pub action_id: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AddressBlocked {
+    pub addr: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AddressUnblocked {
+    pub addr: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct InsuranceClaimPaid {
+    pub id: u32,
+    pub claimant: Address,
+    pub amount: i128,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ArbiterReassigned {
+    pub id: u32,
+    pub old_arbiter: Address,
+    pub new_arbiter: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct SolvencyAlert {
+    pub token: Address,
+    pub shortfall: i128,           // How far the balance falls short of liabilities
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ExcessRescued {
+    pub token: Address,
+    pub to: Address,
+    pub amount: i128,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct FeesWithdrawn {
+    pub token: Address,
+    pub amount: i128,
+    pub to: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct MarketplaceRegistered {
+    pub marketplace: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct MarketplaceRemoved {
+    pub marketplace: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct MarketplaceFeeSet {
+    pub marketplace: Address,
+    pub fee_bps: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct FeeTierSet {
+    pub addr: Address,
+    pub fee_bps: u32,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct FeeTierRemoved {
+    pub addr: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct ConfigUpdated {
+    pub config: Config,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AmountBoundsSet {
+    pub token: Address,
+    pub bounds: AmountBounds,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AdminTransferProposed {
+    pub admin: Address,
+    pub new_admin: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+#[contractevent]
+#[derive(Clone)]
+pub struct AdminTransferred {
+    pub old_admin: Address,
+    pub new_admin: Address,
+    pub seq: u64,                  // Position in the contract-wide event stream
+}
+
+fn sym_counter() -> Symbol {
+    symbol_short!("counter")
+}
+
+fn sym_escrows() -> Symbol {
+    symbol_short!("escrows")
+}
+
+fn sym_lock() -> Symbol {
+    symbol_short!("lock")
+}
+
+fn sym_arbiters() -> Symbol {
+    symbol_short!("arbiters")
+}
+
+fn sym_offers() -> Symbol {
+    symbol_short!("offers")
+}
+
+fn sym_disputes() -> Symbol {
+    symbol_short!("disputes")
+}
+
+fn sym_rate_locks() -> Symbol {
+    symbol_short!("ratelocks")
+}
+
+fn sym_documents() -> Symbol {
+    symbol_short!("documents")
+}
+
+fn sym_dispute_stats() -> Symbol {
+    symbol_short!("dstats")
+}
+
+fn sym_admin() -> Symbol {
+    symbol_short!("admin")
+}
+
+fn sym_pending_admin() -> Symbol {
+    symbol_short!("padmin")
+}
+
+fn sym_paused() -> Symbol {
+    symbol_short!("paused")
+}
+
+fn sym_self_paused() -> Symbol {
+    symbol_short!("selfpause")
+}
+
+fn sym_actions() -> Symbol {
+    symbol_short!("actions")
+}
+
+fn sym_action_counter() -> Symbol {
+    symbol_short!("actionctr")
+}
+
+fn sym_schema() -> Symbol {
+    symbol_short!("schema")
+}
+
+fn sym_version() -> Symbol {
+    symbol_short!("version")
+}
+
+fn sym_insurance_pool() -> Symbol {
+    symbol_short!("inspool")
+}
+
+fn sym_event_seq() -> Symbol {
+    symbol_short!("eventseq")
+}
+
+fn sym_amount_bounds() -> Symbol {
+    symbol_short!("bounds")
+}
+
+fn sym_blocklist() -> Symbol {
+    symbol_short!("blocked")
+}
+
+fn sym_by_depositor() -> Symbol {
+    symbol_short!("bydep")
+}
+
+fn sym_by_beneficiary() -> Symbol {
+    symbol_short!("bybenef")
+}
+
+fn sym_by_arbiter() -> Symbol {
+    symbol_short!("byarb")
+}
+
+fn sym_user_stats() -> Symbol {
+    symbol_short!("userstats")
+}
+
+fn sym_stats() -> Symbol {
+    symbol_short!("stats")
+}
+
+fn sym_open_disputes() -> Symbol {
+    symbol_short!("opendisp")
+}
+
+fn sym_volume() -> Symbol {
+    symbol_short!("volume")
+}
+
+fn sym_history() -> Symbol {
+    symbol_short!("history")
+}
+
+fn sym_pinged() -> Symbol {
+    symbol_short!("pinged")
+}
+
+fn sym_statuses() -> Symbol {
+    symbol_short!("statuses")
+}
+
+fn sym_by_token() -> Symbol {
+    symbol_short!("bytoken")
+}
+
+fn sym_by_status() -> Symbol {
+    symbol_short!("bystatus")
+}
+
+fn sym_arbiter_disputes() -> Symbol {
+    symbol_short!("arbdisp")
+}
+
+fn sym_by_pair() -> Symbol {
+    symbol_short!("bypair")
+}
+
+fn sym_index_len() -> Symbol {
+    symbol_short!("ixlen")
+}
+
+fn sym_index_bucket() -> Symbol {
+    symbol_short!("ixbucket")
+}
+
+fn sym_index_pos() -> Symbol {
+    symbol_short!("ixpos")
+}
+
+fn sym_first_active() -> Symbol {
+    symbol_short!("firstact")
+}
+
+fn sym_create_windows() -> Symbol {
+    symbol_short!("createwin")
+}
+
+fn sym_locked() -> Symbol {
+    symbol_short!("locked")
+}
+
+fn sym_claims() -> Symbol {
+    symbol_short!("claims")
+}
+
+fn sym_treasury() -> Symbol {
+    symbol_short!("treasury")
+}
+
+fn sym_scheduled() -> Symbol {
+    symbol_short!("scheduled")
+}
+
+fn sym_schedule_counter() -> Symbol {
+    symbol_short!("schedct")
+}
+
+fn sym_requests() -> Symbol {
+    symbol_short!("requests")
+}
+
+fn sym_request_counter() -> Symbol {
+    symbol_short!("requestct")
+}
+
+fn sym_payrolls() -> Symbol {
+    symbol_short!("payrolls")
+}
+
+fn sym_payroll_counter() -> Symbol {
+    symbol_short!("payrollct")
+}
+
+fn sym_marketplaces() -> Symbol {
+    symbol_short!("markets")
+}
+
+fn sym_config() -> Symbol {
+    symbol_short!("config")
+}
+
+fn sym_completions() -> Symbol {
+    symbol_short!("complets")
+}
+
+fn sym_fee_tiers() -> Symbol {
+    symbol_short!("feetiers")
+}
+
+fn sym_entries() -> Symbol {
+    symbol_short!("entries")
+}
+
+fn sym_projects() -> Symbol {
+    symbol_short!("projects")
+}
+
+fn sym_project_counter() -> Symbol {
+    symbol_short!("projectct")
+}
+
+fn sym_by_project() -> Symbol {
+    symbol_short!("byproject")
+}
+
+fn sym_tail() -> Symbol {
+    symbol_short!("tail")
+}
+
+fn sym_accruals() -> Symbol {
+    symbol_short!("accruals")
+}
+
+fn sym_payto() -> Symbol {
+    symbol_short!("payto")
+}
+
+fn sym_roles() -> Symbol {
+    symbol_short!("roles")
+}
+
+fn sym_sheets() -> Symbol {
+    symbol_short!("sheets")
+}
+
+fn sym_rotations() -> Symbol {
+    symbol_short!("rotations")
+}
+
+fn sym_attestations() -> Symbol {
+    symbol_short!("attests")
+}
+
+fn escrow_key(id: u32) -> (Symbol, u32) {
+    (sym_escrows(), id)
+}
+
+/// Copy of an escrow's status kept beside the record so it can be polled cheaply
+fn status_key(id: u32) -> (Symbol, u32) {
+    (sym_statuses(), id)
+}
+
+fn history_key(id: u32) -> (Symbol, u32) {
+    (sym_history(), id)
+}
+
+/// Due dates already announced by `ping`, as (milestone index or None, due) pairs
+fn pinged_key(id: u32) -> (Symbol, u32) {
+    (sym_pinged(), id)
+}
+
+fn documents_key(id: u32) -> (Symbol, u32) {
+    (sym_documents(), id)
+}
+
+fn offer_key(id: u32, milestone_index: u32) -> (Symbol, u32, u32) {
+    (sym_offers(), id, milestone_index)
+}
+
+fn rate_lock_key(id: u32, milestone_index: u32) -> (Symbol, u32, u32) {
+    (sym_rate_locks(), id, milestone_index)
+}
+
+fn dispute_key(id: u32, milestone_index: u32) -> (Symbol, u32, u32) {
+    (sym_disputes(), id, milestone_index)
+}
+
+fn self_pause_key(addr: &Address) -> (Symbol, Address) {
+    (sym_self_paused(), addr.clone())
+}
+
+fn action_key(action_id: u32) -> (Symbol, u32) {
+    (sym_actions(), action_id)
+}
+
+fn fee_tier_key(addr: &Address) -> (Symbol, Address) {
+    (sym_fee_tiers(), addr.clone())
+}
+
+fn marketplace_key(marketplace: &Address) -> (Symbol, Address) {
+    (sym_marketplaces(), marketplace.clone())
+}
+
+fn rotation_key(id: u32) -> (Symbol, u32) {
+    (sym_rotations(), id)
+}
+
+fn attestation_key(id: u32, party: &Address) -> (Symbol, u32, Address) {
+    (sym_attestations(), id, party.clone())
+}
+
+/// Interface a governance contract exposes so appealed disputes can be enforced.
+/// Returns the beneficiary's share once the vote has concluded, `None` before.
+#[contractclient(name = "GovernanceClient")]
+pub trait GovernanceInterface {
+    fn get_verdict(env: Env, escrow_contract: Address, id: u32, milestone_index: u32) -> Option<i128>;
+}
+
+#[contract]
+pub struct EscrowContract;
+
+fn acquire_lock(e: &Env) -> Result<(), EscrowError> {
+    let key = sym_lock();
+    let locked: bool = e.storage().instance().get(&key).unwrap_or(false);
+    if locked {
+        return Err(EscrowError::Reentrancy);
+    }
+    e.storage().instance().set(&key, &true);
+    Ok(())
+}
+
+fn release_lock(e: &Env) {
+    let key = sym_lock();
+    e.storage().instance().set(&key, &false);
+}
+
+/// Records the running code's version and build tag
+fn store_version(e: &Env) {
+    let version = (CONTRACT_VERSION, Symbol::new(e, BUILD_TAG));
+    e.storage().instance().set(&sym_version(), &version);
+}
+
+/// Advances and returns the contract-wide event sequence. Every event carries its number so
+/// indexers can spot gaps and backfill just the missing range.
+fn next_event_seq(e: &Env) -> u64 {
+    let seq = e
+        .storage()
+        .instance()
+        .get::<_, u64>(&sym_event_seq())
+        .unwrap_or(0)
+        + 1;
+    e.storage().instance().set(&sym_event_seq(), &seq);
+    seq
+}
+
+fn load_admin(e: &Env) -> Result<Address, EscrowError> {
+    e.storage()
+        .instance()
+        .get(&sym_admin())
+        .ok_or(EscrowError::NotInitialized)
+}
+
+/// Loads the admin and requires its authorization
+fn require_admin(e: &Env) -> Result<Address, EscrowError> {
+    let admin = load_admin(e)?;
+    admin.require_auth();
+    Ok(admin)
+}
+
+fn is_paused(e: &Env) -> bool {
+    e.storage().instance().get(&sym_paused()).unwrap_or(false)
+}
+
+/// Unpause time requested by a self-paused address: 0 while paused with no request pending
+fn self_pause_state(e: &Env, addr: &Address) -> Option<u64> {
+    e.storage().persistent().get(&self_pause_key(addr))
+}
+
+fn is_self_paused(e: &Env, addr: &Address) -> bool {
+    self_pause_state(e, addr).is_some()
+}
+
+fn is_blocked(e: &Env, addr: &Address) -> bool {
+    e.storage().persistent().has(&(sym_blocklist(), addr.clone()))
+}
+
+fn ensure_not_paused(e: &Env) -> Result<(), EscrowError> {
+    if is_paused(e) {
+        return Err(EscrowError::Paused);
+    }
+    Ok(())
+}
+
+fn load_config(e: &Env) -> Result<Config, EscrowError> {
+    e.storage()
+        .instance()
+        .get(&sym_config())
+        .ok_or(EscrowError::NotInitialized)
+}
+
+fn validate_config(config: &Config) -> Result<(), EscrowError> {
+    if config.fee_bps > MAX_FEE_BPS
+        || config.min_duration < MIN_DURATION
+        || config.max_duration > MAX_DURATION
+        || config.min_duration > config.max_duration
+        || !(MIN_REVIEW_PERIOD..=MAX_REVIEW_PERIOD).contains(&config.dispute_period)
+        || config.timelock_delay > MAX_TIMELOCK_DELAY
+        || config.premium_bps > MAX_PREMIUM_BPS
+        || !(MIN_TTL_BUFFER..=MAX_TTL_BUFFER).contains(&config.ttl_buffer)
+        || (config.create_limit > 0 && !(1..=MAX_CREATE_WINDOW).contains(&config.create_window))
+    {
+        return Err(EscrowError::InvalidConfig);
+    }
+    Ok(())
+}
+
+fn store_config(e: &Env, config: &Config) -> Result<(), EscrowError> {
+    validate_config(config)?;
+    e.storage().instance().set(&sym_config(), config);
+    Ok(())
+}
+
+fn sum_milestones(milestone_amounts: &Vec<i128>) -> Result<i128, EscrowError> {
+    let mut total_amount: i128 = 0;
+    for amount in milestone_amounts.iter() {
+        if amount <= 0 {
+            return Err(EscrowError::ZeroAmount);
+        }
+        total_amount = total_amount.checked_add(amount)
+            .ok_or(EscrowError::InvalidMilestone)?;
+    }
+    Ok(total_amount)
+}
+
+/// Splits `total` into milestone amounts by basis points summing to 10_000. Rounding dust goes
+/// to the last milestone.
+fn percent_amounts(e: &Env, total: i128, shares: &Vec<u32>) -> Result<Vec<i128>, EscrowError> {
+    if total <= 0 {
+        return Err(EscrowError::ZeroAmount);
+    }
+    if shares.is_empty() || shares.iter().map(|bps| bps as u64).sum::<u64>() != 10_000 {
+        return Err(EscrowError::InvalidMilestone);
+    }
+    let mut amounts = Vec::new(e);
+    let mut remaining = total;
+    let last = shares.len() - 1;
+    for (index, bps) in shares.iter().enumerate() {
+        let amount = if index as u32 == last {
+            remaining
+        } else {
+            total.checked_mul(bps as i128).ok_or(EscrowError::InvalidMilestone)? / 10_000
+        };
+        remaining -= amount;
+        amounts.push_back(amount);
+    }
+    Ok(amounts)
+}
+
+fn load_amount_bounds(e: &Env, token: &Address) -> Option<AmountBounds> {
+    e.storage()
+        .persistent()
+        .get(&(sym_amount_bounds(), token.clone()))
+}
+
+fn check_amount_bounds(
+    bounds: &AmountBounds,
+    milestone_amounts: &Vec<i128>,
+    total_amount: i128,
+) -> Result<(), EscrowError> {
+    for amount in milestone_amounts.iter() {
+        if !(bounds.min_milestone..=bounds.max_milestone).contains(&amount) {
+            return Err(EscrowError::AmountOutOfBounds);
+        }
+    }
+    if !(bounds.min_total..=bounds.max_total).contains(&total_amount) {
+        return Err(EscrowError::AmountOutOfBounds);
+    }
+    Ok(())
+}
+
+fn premium_for(config: &Config, total_amount: i128) -> i128 {
+    total_amount * config.premium_bps as i128 / 10_000
+}
+
+fn load_insurance_pool(e: &Env, token: &Address) -> i128 {
+    e.storage()
+        .persistent()
+        .get(&(sym_insurance_pool(), token.clone()))
+        .unwrap_or(0)
+}
+
+fn add_to_insurance_pool(e: &Env, token: &Address, amount: i128) {
+    let key = (sym_insurance_pool(), token.clone());
+    let balance = load_insurance_pool(e, token) + amount;
+    e.storage().persistent().set(&key, &balance);
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+}
+
+/// Whether an arbiter (or governance, on appeal) ruled on any of the escrow's disputes
+fn has_ruling(e: &Env, id: u32, escrow: &EscrowData) -> bool {
+    (0..escrow.milestones.len()).any(|i| {
+        load_dispute_history(e, id, i)
+            .iter()
+            .any(|entry| entry.action == DisputeAction::Resolved)
+    })
+}
+
+/// Checks an arbiter replacement is allowed: the escrow's dispute has been open for at least
+/// `ABANDONED_DISPUTE_AGE` and the new arbiter is an eligible outsider
+fn validate_reassignment(e: &Env, id: u32, new_arbiter: &Address) -> Result<EscrowData, EscrowError> {
+    let escrow = load_escrow(e, id)?;
+    let now = e.ledger().timestamp();
+    let abandoned = escrow.status == EscrowStatus::Disputed
+        && escrow
+            .disputed_since
+            .is_some_and(|since| now >= since.saturating_add(ABANDONED_DISPUTE_AGE));
+    if !abandoned {
+        return Err(EscrowError::DisputePeriodActive);
+    }
+    if resolve_role(e, id, new_arbiter, &escrow) != Role::Stranger {
+        return Err(EscrowError::InvalidArbiter);
+    }
+    if is_blocked(e, new_arbiter) {
+        return Err(EscrowError::Blocked);
+    }
+    Ok(escrow)
+}
+
+fn validate_action(e: &Env, action: &AdminAction) -> Result<(), EscrowError> {
+    match action {
+        AdminAction::SetConfig(config) => validate_config(config),
+        AdminAction::Upgrade(_) => Ok(()),
+        AdminAction::ReassignArbiter(id, new_arbiter) => {
+            validate_reassignment(e, *id, new_arbiter).map(|_| ())
+        }
+    }
+}
+
+fn apply_action(e: &Env, action: AdminAction) -> Result<(), EscrowError> {
+    match action {
+        AdminAction::SetConfig(config) => {
+            store_config(e, &config)?;
+            ConfigUpdated { config, seq: next_event_seq(e) }.publish(e);
+            Ok(())
+        }
+        AdminAction::Upgrade(wasm_hash) => {
+            e.deployer().update_current_contract_wasm(wasm_hash);
+            Ok(())
+        }
+        AdminAction::ReassignArbiter(id, new_arbiter) => {
+            // Re-checked because the dispute may have been resolved during the timelock
+            let mut escrow = validate_reassignment(e, id, &new_arbiter)?;
+            let old_arbiter = escrow.arbiter.clone();
+            index_remove(e, &(sym_by_arbiter(), old_arbiter.clone()), id);
+            index_add(e, &(sym_by_arbiter(), new_arbiter.clone()), id);
+            escrow.arbiter = new_arbiter.clone();
+            let admin = load_admin(e)?;
+            store_escrow(e, id, &escrow, &admin);
+            log_action(e, id, &admin, "reassign_arbiter", None);
+
+            ArbiterReassigned {
+                id,
+                old_arbiter,
+                new_arbiter,
+                seq: next_event_seq(e),
+            }
+            .publish(e);
+            Ok(())
+        }
+    }
+}
+
+fn load_escrow(e: &Env, id: u32) -> Result<EscrowData, EscrowError> {
+    let key = escrow_key(id);
+    e.storage()
+        .persistent()
+        .get(&key)
+        .ok_or(EscrowError::EscrowNotFound)
+}
+
+/// Loads an escrow for `migrate`, converting it if it is still in the schema 1 layout. Judged
+/// per record rather than by the stored version, so a migration can span several batches.
+/// Also returns whether the record was converted.
+fn load_any_schema(e: &Env, id: u32) -> Result<(EscrowData, bool), EscrowError> {
+    let raw: Val = e.storage()
+        .persistent()
+        .get(&escrow_key(id))
+        .ok_or(EscrowError::EscrowNotFound)?;
+    let fields = Map::<Symbol, Val>::try_from_val(e, &raw).map_err(|_| EscrowError::InvalidConfig)?;
+    if !fields.contains_key(Symbol::new(e, "fee_recipient")) {
+        return Ok((load_escrow(e, id)?, false));
+    }
+    let old = EscrowDataV1::try_from_val(e, &raw).map_err(|_| EscrowError::InvalidConfig)?;
+    Ok((convert_v1(e, old), true))
+}
+
+/// Schema 1 to 2: every field added since starts out as the default a new escrow gets
+fn convert_v1(e: &Env, old: EscrowDataV1) -> EscrowData {
+    let milestones = Vec::from_iter(e, old.milestones.iter().map(|m| Milestone {
+        description: m.description,
+        amount: m.amount,
+        status: m.status,
+        submitted_at: m.submitted_at,
+        approved_at: m.approved_at,
+        revision_requested_at: m.revision_requested_at,
+        rationale_hash: m.rationale_hash,
+        appealed: m.appealed,
+        checklist_done: Vec::from_iter(e, m.checklist.iter().map(|_| false)),
+        checklist: m.checklist,
+        checklist_results: m.checklist_results,
+        deadline: None,
+        penalty_bps: 0,
+        recipient: None,
+    }));
+    EscrowData {
+        depositor: old.depositor,
+        beneficiary: old.beneficiary,
+        arbiter: old.arbiter,
+        token: old.token,
+        total_amount: old.total_amount,
+        paid_amount: old.paid_amount,
+        deadline: old.deadline,
+        status: old.status,
+        milestones,
+        work_started: old.work_started,
+        resubmit_cooldown: old.resubmit_cooldown,
+        close_reason: old.close_reason,
+        frozen_until: old.frozen_until,
+        private_events: old.private_events,
+        governance: old.governance,
+        review_period: old.review_period,
+        disputed_since: old.disputed_since,
+        deadline_extended: old.deadline_extended,
+        submission_cutoff: old.submission_cutoff,
+        reported: old.reported,
+        fee_bps: old.fee_bps,
+        insured: false,
+        penalty: PenaltySchedule::default(),
+        bonus: 0,
+        bonus_held: 0,
+        kill_fee_bps: 0,
+        referrer: None,
+        referral_bps: 0,
+        marketplace: None,
+        marketplace_fee_bps: 0,
+        mode: PayoutMode::Milestones,
+        started_at: 0,
+        claimed: 0,
+        splits: Vec::new(e),
+        funding: FundingMode::Upfront,
+        contributions: Vec::new(e),
+        claim: ClaimState::Fixed,
+    }
+}
+
+/// Latest moment any party may still act on the escrow: the deadline, an
+/// open review/dispute window, or a running freeze
+fn furthest_obligation(escrow: &EscrowData) -> u64 {
+    let mut furthest = escrow.deadline.max(escrow.frozen_until);
+    for milestone in escrow.milestones.iter() {
+        if let Some(submitted_at) = milestone.submitted_at {
+            furthest = furthest.max(submitted_at.saturating_add(escrow.review_period));
+        }
+    }
+    furthest
+}
+
+fn escrow_ttl_ledgers(e: &Env, escrow: &EscrowData) -> u32 {
+    let now = e.ledger().timestamp();
+    let buffer = load_config(e).map_or(DEFAULT_TTL_BUFFER, |config| config.ttl_buffer);
+    let ttl_secs = furthest_obligation(escrow)
+        .saturating_sub(now)
+        .saturating_add(buffer);
+    (ttl_secs / LEDGER_SECS).try_into().unwrap_or(u32::MAX)
+}
+
+/// Consistency checks run on every escrow write in tests and `invariants` builds, so state
+/// corruption fails loudly at the call that caused it
+#[cfg(any(test, feature = "invariants"))]
+fn assert_invariants(e: &Env, id: u32, escrow: &EscrowData) {
+    let mut committed: i128 = 0;
+    let mut approved: i128 = 0;
+    let mut disputed = false;
+    for milestone in escrow.milestones.iter() {
+        committed += milestone.amount;
+        match milestone.status {
+            MilestoneStatus::Approved => approved += milestone.amount,
+            MilestoneStatus::Disputed => disputed = true,
+            _ => {}
+        }
+    }
+
+    assert!(committed == escrow.total_amount, "escrow {}: milestones do not sum to total", id);
+    if approved < committed {
+        approved += escrow.claimed;
+    }
+    assert!(
+        escrow.paid_amount >= 0 && escrow.paid_amount <= approved,
+        "escrow {}: paid amount exceeds approved milestones",
+        id
+    );
+    match escrow.status {
+        EscrowStatus::Pending => assert!(!escrow.work_started, "escrow {}: pending after start", id),
+        EscrowStatus::InProgress => assert!(
+            escrow.work_started && !disputed,
+            "escrow {}: in progress with open dispute or before start",
+            id
+        ),
+        EscrowStatus::Disputed => assert!(disputed, "escrow {}: disputed without dispute", id),
+        EscrowStatus::Released => assert!(
+            approved == escrow.total_amount,
+            "escrow {}: released with unapproved milestones",
+            id
+        ),
+        EscrowStatus::Refunded => {}
+    }
+
+    if is_active(escrow) {
+        let balance = token::Client::new(e, &escrow.token).balance(&e.current_contract_address());
+        assert!(
+            balance >= outstanding_amount(escrow) - unfunded_amount(escrow),
+            "escrow {}: contract balance below outstanding obligations",
+            id
+        );
+    }
+}
+
+fn store_escrow(e: &Env, id: u32, escrow: &EscrowData, actor: &Address) {
+    #[cfg(any(test, feature = "invariants"))]
+    assert_invariants(e, id, escrow);
+
+    let key = escrow_key(id);
+    let previous: Option<EscrowData> = e.storage().persistent().get(&key);
+    let delta = locked_amount(escrow) - previous.as_ref().map_or(0, locked_amount);
+    adjust_locked(e, &escrow.token, delta);
+    let volume = escrow.total_amount - previous.as_ref().map_or(0, |old| old.total_amount);
+    if volume != 0 {
+        let volume_key = (sym_volume(), escrow.token.clone());
+        let total = load_volume(e, &escrow.token) + volume;
+        e.storage().persistent().set(&volume_key, &total);
+        e.storage().persistent().extend_ttl(&volume_key, 0u32, COUNTER_TTL_SECS);
+    }
+    record_user_activity(e, previous.as_ref(), escrow);
+    let opened = disputed_milestones(e, escrow).len() as i64
+        - previous.as_ref().map_or(0, |old| disputed_milestones(e, old).len() as i64);
+    if opened != 0 {
+        let open = (count_open_disputes(e) as i64 + opened).max(0) as u32;
+        e.storage().instance().set(&sym_open_disputes(), &open);
+    }
+    // Open escrows between each depositor and beneficiary, for `get_open_between`
+    let old_pair = previous
+        .as_ref()
+        .filter(|old| is_active(old))
+        .map(|old| (sym_by_pair(), old.depositor.clone(), old.beneficiary.clone()));
+    let new_pair = is_active(escrow)
+        .then(|| (sym_by_pair(), escrow.depositor.clone(), escrow.beneficiary.clone()));
+    move_in_index(e, old_pair, new_pair, id);
+    // Each arbiter's queue of escrows in dispute, for `get_disputes_by_arbiter`
+    let old_queue = previous
+        .as_ref()
+        .filter(|old| old.status == EscrowStatus::Disputed)
+        .map(|old| (sym_arbiter_disputes(), old.arbiter.clone()));
+    let new_queue = (escrow.status == EscrowStatus::Disputed)
+        .then(|| (sym_arbiter_disputes(), escrow.arbiter.clone()));
+    move_in_index(e, old_queue, new_queue, id);
+    let old_status = previous.map(|old| old.status);
+    if old_status.as_ref() != Some(&escrow.status) {
+        record_transition(e, old_status.is_none(), &escrow.status);
+        if let Some(old_status) = old_status {
+            if is_open_status(&old_status) {
+                index_remove(e, &(sym_by_status(), old_status.clone()), id);
+            }
+            publish_status_change(e, id, escrow, old_status, actor);
+        }
+        e.storage().persistent().set(&status_key(id), &escrow.status);
+        if is_open_status(&escrow.status) {
+            index_add(e, &(sym_by_status(), escrow.status.clone()), id);
+        } else if id == e.storage().instance().get(&sym_first_active()).unwrap_or(1) {
+            let next = first_active_id(e).unwrap_or(escrow_count(e) + 1);
+            e.storage().instance().set(&sym_first_active(), &next);
+        }
+    }
+    e.storage().persistent().set(&key, escrow);
+
+    // Persistent TTLs are clamped to the network maximum by the host
+    let ttl = escrow_ttl_ledgers(e, escrow);
+    e.storage().persistent().extend_ttl(&key, ttl, ttl);
+    e.storage().persistent().extend_ttl(&status_key(id), ttl, ttl);
+}
+
+fn publish_status_change(e: &Env, id: u32, escrow: &EscrowData, from: EscrowStatus, actor: &Address) {
+    let to = escrow.status.clone();
+    if escrow.private_events {
+        EscrowStatusChangedMinimal { id, from, to, seq: next_event_seq(e) }.publish(e);
+    } else {
+        EscrowStatusChanged {
+            id,
+            from,
+            to,
+            actor: actor.clone(),
+            seq: next_event_seq(e),
+        }
+        .publish(e);
+    }
+}
+
+fn load_user_stats(e: &Env, addr: &Address) -> UserStats {
+    e.storage()
+        .persistent()
+        .get(&(sym_user_stats(), addr.clone()))
+        .unwrap_or_default()
+}
+
+fn update_user_stats(e: &Env, addr: &Address, update: impl FnOnce(&mut UserStats)) {
+    let key = (sym_user_stats(), addr.clone());
+    let mut stats = load_user_stats(e, addr);
+    update(&mut stats);
+    let escrows = stats.as_client + stats.as_freelancer;
+    stats.completion_bps = (stats.completed as u64 * 10_000 / escrows.max(1) as u64) as u32;
+    e.storage().persistent().set(&key, &stats);
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+}
+
+/// Credits creations, payouts and completions to both parties' lifetime stats
+fn record_user_activity(e: &Env, previous: Option<&EscrowData>, escrow: &EscrowData) {
+    let paid = escrow.paid_amount - previous.map_or(0, |old| old.paid_amount);
+    let created = previous.is_none();
+    let completed = escrow.status == EscrowStatus::Released
+        && previous.is_some_and(|old| old.status != EscrowStatus::Released);
+    if !created && !completed && paid == 0 {
+        return;
+    }
+    update_user_stats(e, &escrow.depositor, |stats| {
+        stats.as_client += created as u32;
+        stats.completed += completed as u32;
+        stats.total_spent += paid;
+    });
+    update_user_stats(e, &escrow.beneficiary, |stats| {
+        stats.as_freelancer += created as u32;
+        stats.completed += completed as u32;
+        stats.total_earned += paid;
+    });
+}
+
+fn load_stats(e: &Env) -> PlatformStats {
+    e.storage().instance().get(&sym_stats()).unwrap_or_default()
+}
+
+fn load_volume(e: &Env, token: &Address) -> i128 {
+    e.storage()
+        .persistent()
+        .get(&(sym_volume(), token.clone()))
+        .unwrap_or(0)
+}
+
+/// Counts a status change towards the platform stats
+fn record_transition(e: &Env, created: bool, status: &EscrowStatus) {
+    let mut stats = load_stats(e);
+    if created {
+        stats.created += 1;
+    }
+    match status {
+        EscrowStatus::Released => stats.completed += 1,
+        EscrowStatus::Refunded => stats.refunded += 1,
+        EscrowStatus::Disputed => stats.disputed += 1,
+        EscrowStatus::Pending | EscrowStatus::InProgress => {}
+    }
+    e.storage().instance().set(&sym_stats(), &stats);
+}
+
+/// Indexes of the escrow's milestones currently in dispute
+fn disputed_milestones(e: &Env, escrow: &EscrowData) -> Vec<u32> {
+    let mut disputed = Vec::new(e);
+    for (index, milestone) in escrow.milestones.iter().enumerate() {
+        if milestone.status == MilestoneStatus::Disputed {
+            disputed.push_back(index as u32);
+        }
+    }
+    disputed
+}
+
+fn count_open_disputes(e: &Env) -> u32 {
+    e.storage().instance().get(&sym_open_disputes()).unwrap_or(0)
+}
+
+/// Escrows created so far; ids run from 1 to this value
+fn escrow_count(e: &Env) -> u32 {
+    e.storage().persistent().get(&sym_counter()).unwrap_or(0u32)
+}
+
+fn peek_next_id(e: &Env) -> Result<u32, EscrowError> {
+    let next = escrow_count(e).checked_add(1).ok_or(EscrowError::CounterOverflow)?;
+    Ok(next)
+}
+
+/// Released and refunded escrows drop out of the status index, so it only ever holds live ids
+fn is_open_status(status: &EscrowStatus) -> bool {
+    matches!(
+        status,
+        EscrowStatus::Pending | EscrowStatus::InProgress | EscrowStatus::Disputed
+    )
+}
+
+/// Lowest id that may still be open. Scans forward from the cursor `store_escrow` keeps at
+/// most `MAX_PAGE_SIZE` ids, so after a long run of closures it can trail the true value,
+/// but every escrow below it is always closed
+fn first_active_id(e: &Env) -> Option<u32> {
+    let count = escrow_count(e);
+    let mut id: u32 = e.storage().instance().get(&sym_first_active()).unwrap_or(1);
+    let stop = id.saturating_add(MAX_PAGE_SIZE);
+    while id <= count && id < stop {
+        let status: Option<EscrowStatus> = e.storage().persistent().get(&status_key(id));
+        if status.as_ref().is_some_and(is_open_status) {
+            return Some(id);
+        }
+        id += 1;
+    }
+    (id <= count).then_some(id)
+}
+
+fn index_len<K: IntoVal<Env, Val>>(e: &Env, key: &K) -> u32 {
+    let key: Val = key.into_val(e);
+    e.storage().persistent().get(&(sym_index_len(), key)).unwrap_or(0)
+}
+
+fn load_bucket(e: &Env, key: Val, bucket: u32) -> Vec<u32> {
+    let bucket_key = (sym_index_bucket(), key, bucket);
+    e.storage().persistent().get(&bucket_key).unwrap_or(Vec::new(e))
+}
+
+fn set_index_entry<K: IntoVal<Env, Val>, V: IntoVal<Env, Val>>(e: &Env, key: &K, value: &V) {
+    e.storage().persistent().set(key, value);
+    e.storage().persistent().extend_ttl(key, 0u32, COUNTER_TTL_SECS);
+}
+
+/// Appends `id` to an index and returns its position. Ids are stored in buckets of
+/// `INDEX_BUCKET_SIZE`, so neither writes nor reads grow with the length of the index
+fn index_push<K: IntoVal<Env, Val>>(e: &Env, key: &K, id: u32) -> u32 {
+    let key: Val = key.into_val(e);
+    let len = index_len(e, &key);
+    let mut bucket = load_bucket(e, key, len / INDEX_BUCKET_SIZE);
+    bucket.push_back(id);
+    set_index_entry(e, &(sym_index_bucket(), key, len / INDEX_BUCKET_SIZE), &bucket);
+    set_index_entry(e, &(sym_index_len(), key), &(len + 1));
+    len
+}
+
+/// Appends `id` to an address or status index, remembering its position so it can be removed
+fn index_add<K: IntoVal<Env, Val>>(e: &Env, key: &K, id: u32) {
+    let key: Val = key.into_val(e);
+    let position = index_push(e, &key, id);
+    set_index_entry(e, &(sym_index_pos(), key, id), &position);
+}
+
+/// Drops `id` from an index when the escrow leaves that role or status, moving the last id
+/// into its place. Returns whether `id` was listed
+fn index_remove<K: IntoVal<Env, Val>>(e: &Env, key: &K, id: u32) -> bool {
+    let key: Val = key.into_val(e);
+    let position_key = (sym_index_pos(), key, id);
+    let Some(position) = e.storage().persistent().get::<_, u32>(&position_key) else {
+        return false;
+    };
+    e.storage().persistent().remove(&position_key);
+
+    let last = index_len(e, &key) - 1;
+    let mut tail = load_bucket(e, key, last / INDEX_BUCKET_SIZE);
+    let moved = tail.pop_back().unwrap_or(id);
+    if position != last {
+        if position / INDEX_BUCKET_SIZE == last / INDEX_BUCKET_SIZE {
+            tail.set(position % INDEX_BUCKET_SIZE, moved);
+        } else {
+            let mut bucket = load_bucket(e, key, position / INDEX_BUCKET_SIZE);
+            bucket.set(position % INDEX_BUCKET_SIZE, moved);
+            set_index_entry(e, &(sym_index_bucket(), key, position / INDEX_BUCKET_SIZE), &bucket);
+        }
+        set_index_entry(e, &(sym_index_pos(), key, moved), &position);
+    }
+    let tail_key = (sym_index_bucket(), key, last / INDEX_BUCKET_SIZE);
+    if tail.is_empty() {
+        e.storage().persistent().remove(&tail_key);
+    } else {
+        set_index_entry(e, &tail_key, &tail);
+    }
+    set_index_entry(e, &(sym_index_len(), key), &last);
+    true
+}
+
+/// Moves `id` between the index it belonged to and the one it belongs to now, if they differ
+fn move_in_index<K: IntoVal<Env, Val> + PartialEq>(e: &Env, from: Option<K>, to: Option<K>, id: u32) {
+    if from == to {
+        return;
+    }
+    if let Some(key) = from {
+        index_remove(e, &key, id);
+    }
+    if let Some(key) = to {
+        index_add(e, &key, id);
+    }
+}
+
+/// Ids at positions `offset..offset + limit` of an index (`limit` capped at `MAX_PAGE_SIZE`),
+/// reading only the buckets that hold them
+fn index_page<K: IntoVal<Env, Val>>(e: &Env, key: &K, offset: u32, limit: u32) -> Vec<u32> {
+    let key: Val = key.into_val(e);
+    let len = index_len(e, &key);
+    let start = offset.min(len);
+    let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(len);
+    let mut ids = Vec::new(e);
+    let mut position = start;
+    while position < end {
+        let bucket = load_bucket(e, key, position / INDEX_BUCKET_SIZE);
+        let from = position % INDEX_BUCKET_SIZE;
+        let to = (end - position + from).min(bucket.len());
+        if to <= from {
+            break;
+        }
+        ids.append(&bucket.slice(from..to));
+        position += to - from;
+    }
+    ids
+}
+
+/// Slice of `items` starting at `offset`, at most `limit` (capped at `MAX_PAGE_SIZE`) long
+fn page<T: IntoVal<Env, Val> + TryFromVal<Env, Val>>(items: &Vec<T>, offset: u32, limit: u32) -> Vec<T> {
+    let start = offset.min(items.len());
+    let end = start.saturating_add(limit.min(MAX_PAGE_SIZE)).min(items.len());
+    items.slice(start..end)
+}
+
+fn finalize_counter(e: &Env, id: u32) {
+    let k = sym_counter();
+    e.storage().persistent().set(&k, &id);
+    e.storage().persistent().extend_ttl(&k, 0u32, COUNTER_TTL_SECS);
+}
+
+fn safe_transfer(
+    e: &Env,
+    token_addr: &Address,
+    from: &Address,
+    to: &Address,
+    amount: &i128,
+) -> Result<(), EscrowError> {
+    let client = token::Client::new(e, token_addr);
+    client.transfer(from, to, amount);
+    Ok(())
+}
+
+/// Fee rate for a payout: the escrow's locked-in rate, lowered by either party's fee tier
+fn payout_fee_bps(e: &Env, escrow: &EscrowData) -> u32 {
+    let mut fee_bps = escrow.fee_bps;
+    for party in [&escrow.depositor, &escrow.beneficiary] {
+        if let Some(tier) = e.storage().persistent().get::<_, u32>(&fee_tier_key(party)) {
+            fee_bps = fee_bps.min(tier);
+        }
+    }
+    fee_bps
+}
+
+fn load_accrued_fees(e: &Env, token: &Address) -> i128 {
+    e.storage()
+        .persistent()
+        .get(&(sym_treasury(), token.clone()))
+        .unwrap_or(0)
+}
+
+/// Platform fees stay in the contract until the admin withdraws them
+fn accrue_fees(e: &Env, token: &Address, amount: i128) {
+    let key = (sym_treasury(), token.clone());
+    e.storage().persistent().set(&key, &(load_accrued_fees(e, token) + amount));
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+}
+
+/// Team shares must be positive, distinct, sum to 10_000 bps and exclude the other parties
+fn check_splits(
+    e: &Env,
+    splits: &Vec<(Address, u32)>,
+    depositor: &Address,
+    arbiter: &Address,
+) -> Result<(), EscrowError> {
+    if splits.is_empty() {
+        return Ok(());
+    }
+    let mut total_bps: u32 = 0;
+    for (index, (member, bps)) in splits.iter().enumerate() {
+        if bps == 0 || bps > 10_000 {
+            return Err(EscrowError::InvalidConfig);
+        }
+        if member == *depositor
+            || member == *arbiter
+            || splits.iter().take(index).any(|(other, _)| other == member)
+        {
+            return Err(EscrowError::InvalidBeneficiary);
+        }
+        if is_blocked(e, &member) {
+            return Err(EscrowError::Blocked);
+        }
+        total_bps += bps;
+    }
+    if total_bps != 10_000 {
+        return Err(EscrowError::InvalidConfig);
+    }
+    Ok(())
+}
+
+/// Single address paid instead of the beneficiary's wallet or team: the claim holder if the
+/// claim was sold, else `recipient` (a milestone's payout override) if given
+fn payout_override<'a>(escrow: &'a EscrowData, recipient: Option<&'a Address>) -> Option<&'a Address> {
+    // Whoever bought the claim takes everything that would have gone the beneficiary's way
+    match &escrow.claim {
+        ClaimState::Held(holder) => Some(holder),
+        ClaimState::Fixed | ClaimState::Transferable => recipient,
+    }
+}
+
+/// Wallet the beneficiary's own payouts go to
+fn payout_wallet(e: &Env, id: u32, escrow: &EscrowData) -> Address {
+    load_payout_address(e, id).unwrap_or(escrow.beneficiary.clone())
+}
+
+/// Pays the beneficiary, withholding the platform and marketplace fees and any referral cut.
+/// The rest goes to the claim holder if the claim was sold, else to `recipient` if given, else
+/// to the beneficiary's payout address (or its team splits).
+fn pay_beneficiary(
+    e: &Env,
+    id: u32,
+    escrow: &EscrowData,
+    amount: i128,
+    recipient: Option<&Address>,
+) -> Result<(), EscrowError> {
+    let recipient = payout_override(escrow, recipient);
+    let wallet = payout_wallet(e, id, escrow);
+    if is_blocked(e, recipient.unwrap_or(&wallet)) {
+        return Err(EscrowError::Blocked);
+    }
+    if recipient.is_none() && escrow.splits.iter().any(|(member, _)| is_blocked(e, &member)) {
+        return Err(EscrowError::Blocked);
+    }
+    let fee = amount * payout_fee_bps(e, escrow) as i128 / 10_000;
+    if fee > 0 {
+        accrue_fees(e, &escrow.token, fee);
+    }
+    let mut marketplace_fee = 0;
+    if let Some(marketplace) = &escrow.marketplace {
+        marketplace_fee = amount * escrow.marketplace_fee_bps as i128 / 10_000;
+        if marketplace_fee > 0 {
+            safe_transfer(
+                e,
+                &escrow.token,
+                &e.current_contract_address(),
+                marketplace,
+                &marketplace_fee,
+            )?;
+        }
+    }
+    let mut referral = 0;
+    if let Some(referrer) = &escrow.referrer {
+        referral = amount * escrow.referral_bps as i128 / 10_000;
+        if referral > 0 {
+            safe_transfer(e, &escrow.token, &e.current_contract_address(), referrer, &referral)?;
+        }
+    }
+    let net = amount - fee - marketplace_fee - referral;
+    if let Some(recipient) = recipient {
+        return safe_transfer(e, &escrow.token, &e.current_contract_address(), recipient, &net);
+    }
+    if escrow.splits.is_empty() {
+        return safe_transfer(e, &escrow.token, &e.current_contract_address(), &wallet, &net);
+    }
+    // The last member takes the rounding remainder
+    let mut remaining = net;
+    let last = escrow.splits.len() - 1;
+    for (index, (member, bps)) in escrow.splits.iter().enumerate() {
+        let share = if index as u32 == last { remaining } else { net * bps as i128 / 10_000 };
+        remaining -= share;
+        let member = if member == escrow.beneficiary { wallet.clone() } else { member };
+        if share > 0 {
+            safe_transfer(e, &escrow.token, &e.current_contract_address(), &member, &share)?;
+        }
+    }
+    Ok(())
+}
+
+fn load_arbiter_pool(e: &Env) -> Vec<Address> {
+    e.storage()
+        .persistent()
+        .get(&sym_arbiters())
+        .unwrap_or(Vec::new(e))
+}
+
+fn store_arbiter_pool(e: &Env, pool: &Vec<Address>) {
+    let k = sym_arbiters();
+    e.storage().persistent().set(&k, pool);
+    e.storage().persistent().extend_ttl(&k, 0u32, COUNTER_TTL_SECS);
+}
+
+fn pick_pool_arbiter(
+    e: &Env,
+    depositor: &Address,
+    beneficiary: &Address,
+) -> Result<Address, EscrowError> {
+    let mut candidates = Vec::new(e);
+    for arbiter in load_arbiter_pool(e).iter() {
+        if arbiter != *depositor && arbiter != *beneficiary && !is_self_paused(e, &arbiter) {
+            candidates.push_back(arbiter);
+        }
+    }
+    if candidates.is_empty() {
+        return Err(EscrowError::InvalidArbiter);
+    }
+
+    let index = e.prng().gen_range::<u64>(0..candidates.len() as u64) as u32;
+    Ok(candidates.get(index).unwrap())
+}
+
+/// The parties come first; anyone else may hold a role the depositor granted
+fn resolve_role(e: &Env, id: u32, caller: &Address, escrow: &EscrowData) -> Role {
+    if *caller == escrow.depositor {
+        Role::Depositor
+    } else if *caller == escrow.beneficiary {
+        Role::Beneficiary
+    } else if *caller == escrow.arbiter {
+        Role::Arbiter
+    } else {
+        load_granted_roles(e, id).get(caller.clone()).unwrap_or(Role::Stranger)
+    }
+}
+
+/// Role of `caller` in a payment request, scheduled payment or payroll, which have a payer
+/// and, except for payroll, a single payee
+fn resolve_party(caller: &Address, depositor: &Address, beneficiary: Option<&Address>) -> Role {
+    if caller == depositor {
+        Role::Depositor
+    } else if Some(caller) == beneficiary {
+        Role::Beneficiary
+    } else {
+        Role::Stranger
+    }
+}
+
+fn error_name(code: u32) -> &'static str {
+    match code {
+        1 => "AlreadyCompleted",
+        2 => "NotAuthorized",
+        3 => "InvalidDeadline",
+        4 => "ZeroAmount",
+        5 => "EscrowNotFound",
+        6 => "TransferFailed",
+        7 => "InvalidBeneficiary",
+        8 => "InvalidArbiter",
+        9 => "CounterOverflow",
+        10 => "InvalidDuration",
+        11 => "Reentrancy",
+        12 => "InvalidMilestone",
+        13 => "MilestoneNotCompleted",
+        14 => "DisputePeriodActive",
+        15 => "WorkStarted",
+        16 => "MilestoneAlreadySubmitted",
+        17 => "MilestoneNotSubmitted",
+        18 => "SubmissionCooldown",
+        19 => "SettlementNotFound",
+        20 => "Frozen",
+        21 => "AppealPending",
+        22 => "SubmissionWindowClosed",
+        23 => "NotInitialized",
+        24 => "AlreadyInitialized",
+        25 => "InvalidConfig",
+        26 => "RotationNotReady",
+        27 => "Paused",
+        28 => "TimelockActive",
+        29 => "ActionNotFound",
+        30 => "AmountOutOfBounds",
+        31 => "Blocked",
+        32 => "MarketplaceNotFound",
+        33 => "InsufficientFees",
+        34 => "InsufficientPool",
+        35 => "AlreadyClaimed",
+        36 => "RateLimited",
+        37 => "WrongMode",
+        38 => "NotYetDue",
+        39 => "NotFunded",
+        _ => "Unknown",
+    }
+}
+
+fn load_dispute_history(e: &Env, id: u32, milestone_index: u32) -> Vec<DisputeEntry> {
+    e.storage()
+        .persistent()
+        .get(&dispute_key(id, milestone_index))
+        .unwrap_or(Vec::new(e))
+}
+
+fn record_dispute(
+    e: &Env,
+    id: u32,
+    milestone_index: u32,
+    action: DisputeAction,
+    actor: &Address,
+    amount: i128,
+) {
+    let key = dispute_key(id, milestone_index);
+    let mut history = load_dispute_history(e, id, milestone_index);
+    if history.len() >= MAX_DISPUTE_HISTORY {
+        history.pop_front();
+    }
+    history.push_back(DisputeEntry {
+        action,
+        actor: actor.clone(),
+        at: e.ledger().timestamp(),
+        amount,
+    });
+    e.storage().persistent().set(&key, &history);
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+}
+
+fn load_escrow_history(e: &Env, id: u32) -> Vec<HistoryEntry> {
+    e.storage()
+        .persistent()
+        .get(&history_key(id))
+        .unwrap_or(Vec::new(e))
+}
+
+/// Appends to the escrow's audit log, dropping the oldest entry once it is full
+fn log_action(e: &Env, id: u32, actor: &Address, action: &str, milestone_index: Option<u32>) {
+    log_action_with_reason(e, id, actor, action, milestone_index, None);
+}
+
+/// Like `log_action`, for the calls that close an escrow and say why
+fn log_action_with_reason(
+    e: &Env,
+    id: u32,
+    actor: &Address,
+    action: &str,
+    milestone_index: Option<u32>,
+    reason: Option<BytesN<32>>,
+) {
+    let key = history_key(id);
+    let mut history = load_escrow_history(e, id);
+    if history.len() >= MAX_ESCROW_HISTORY {
+        history.pop_front();
+    }
+    history.push_back(HistoryEntry {
+        at: e.ledger().timestamp(),
+        actor: actor.clone(),
+        action: Symbol::new(e, action),
+        milestone_index,
+        reason,
+    });
+    e.storage().persistent().set(&key, &history);
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+}
+
+fn load_documents(e: &Env, id: u32) -> Vec<DocumentRevision> {
+    e.storage()
+        .persistent()
+        .get(&documents_key(id))
+        .unwrap_or(Vec::new(e))
+}
+
+fn store_documents(e: &Env, id: u32, documents: &Vec<DocumentRevision>) {
+    let key = documents_key(id);
+    e.storage().persistent().set(&key, documents);
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+}
+
+fn load_dispute_stats(e: &Env, addr: &Address) -> DisputeStats {
+    e.storage()
+        .persistent()
+        .get(&(sym_dispute_stats(), addr.clone()))
+        .unwrap_or_default()
+}
+
+fn update_dispute_stats(e: &Env, addr: &Address, f: impl FnOnce(&mut DisputeStats)) {
+    let key = (sym_dispute_stats(), addr.clone());
+    let mut stats = load_dispute_stats(e, addr);
+    f(&mut stats);
+    e.storage().persistent().set(&key, &stats);
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+}
+
+/// The side receiving the larger share wins; an even split counts for neither
+fn record_ruling_stats(
+    e: &Env,
+    escrow: &EscrowData,
+    pay_to_beneficiary: i128,
+    refund: i128,
+    arbiter: Option<&Address>,
+) {
+    if pay_to_beneficiary > refund {
+        update_dispute_stats(e, &escrow.beneficiary, |s| s.won += 1);
+        update_dispute_stats(e, &escrow.depositor, |s| s.lost += 1);
+    } else if refund > pay_to_beneficiary {
+        update_dispute_stats(e, &escrow.depositor, |s| s.won += 1);
+        update_dispute_stats(e, &escrow.beneficiary, |s| s.lost += 1);
+    }
+    if let Some(arbiter) = arbiter {
+        update_dispute_stats(e, arbiter, |s| s.arbitrated += 1);
+    }
+}
+
+fn load_completion_record(e: &Env, addr: &Address) -> CompletionRecord {
+    e.storage()
+        .persistent()
+        .get(&(sym_completions(), addr.clone()))
+        .unwrap_or_default()
+}
+
+/// Publishes completion and advances the beneficiary's record. Only escrows paid in full
+/// extend the streak; any split or partial ruling resets it.
+fn complete_escrow(e: &Env, id: u32, escrow: &EscrowData, actor: &Address) -> Result<(), EscrowError> {
+    EscrowCompleted { id, seq: next_event_seq(e) }.publish(e);
+    settle_bonus(e, id, escrow, actor)?;
+
+    let key = (sym_completions(), escrow.beneficiary.clone());
+    let mut record = load_completion_record(e, &escrow.beneficiary);
+    record.completed += 1;
+    if escrow.paid_amount == escrow.total_amount {
+        record.streak += 1;
+        record.best_streak = record.best_streak.max(record.streak);
+    } else {
+        record.streak = 0;
+    }
+    e.storage().persistent().set(&key, &record);
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+    if ACHIEVEMENT_THRESHOLDS.contains(&record.completed) {
+        AchievementUnlocked {
+            beneficiary: escrow.beneficiary.clone(),
+            completed: record.completed,
+            seq: next_event_seq(e),
+        }
+        .publish(e);
+    }
+    Ok(())
+}
+
+fn unapproved_milestones(e: &Env, escrow: &EscrowData) -> Vec<u32> {
+    let mut milestones = Vec::new(e);
+    for (index, milestone) in escrow.milestones.iter().enumerate() {
+        if !is_settled(&milestone.status) {
+            milestones.push_back(index as u32);
+        }
+    }
+    milestones
+}
+
+/// Publishes a refund of `amount` to the depositor along with the milestones it covers
+fn publish_refund(e: &Env, id: u32, escrow: &EscrowData, amount: i128, milestones: Vec<u32>) {
+    if escrow.private_events {
+        EscrowRefundedMinimal { id, seq: next_event_seq(e) }.publish(e);
+        return;
+    }
+    EscrowRefunded {
+        id,
+        to: escrow.depositor.clone(),
+        amount,
+        reason: escrow.close_reason.clone(),
+        milestones,
+        seq: next_event_seq(e),
+    }
+    .publish(e);
+}
+
+/// Returns the unearned part of the early-delivery bonus to the depositor
+fn settle_bonus(e: &Env, id: u32, escrow: &EscrowData, actor: &Address) -> Result<(), EscrowError> {
+    if escrow.bonus_held == 0 {
+        return Ok(());
+    }
+    safe_transfer(
+        e,
+        &escrow.token,
+        &e.current_contract_address(),
+        &escrow.depositor,
+        &escrow.bonus_held,
+    )?;
+    let mut settled = escrow.clone();
+    settled.bonus_held = 0;
+    store_escrow(e, id, &settled, actor);
+    Ok(())
+}
+
+fn is_frozen(e: &Env, escrow: &EscrowData) -> bool {
+    e.ledger().timestamp() < escrow.frozen_until
+}
+
+fn is_appealed(escrow: &EscrowData, milestone_index: u32) -> bool {
+    escrow
+        .milestones
+        .get(milestone_index)
+        .map(|m| m.appealed)
+        .unwrap_or(false)
+}
+
+fn has_open_dispute(escrow: &EscrowData) -> bool {
+    escrow
+        .milestones
+        .iter()
+        .any(|m| m.status == MilestoneStatus::Disputed)
+}
+
+/// Penalty owed for submitting `milestone` at `now` under the escrow's schedule
+fn late_penalty_bps(escrow: &EscrowData, milestone: &Milestone, now: u64) -> u32 {
+    let Some(due) = milestone.deadline else {
+        return 0;
+    };
+    if now <= due {
+        return 0;
+    }
+    let days_late = (now - due).div_ceil(24 * 3600);
+    let bps = (escrow.penalty.bps_per_day as u64).saturating_mul(days_late);
+    bps.min(escrow.penalty.max_bps as u64) as u32
+}
+
+/// Share of the bonus earned by `milestone`, in proportion to its amount. Only milestones
+/// both submitted and approved by their own deadline qualify.
+fn early_bonus(escrow: &EscrowData, milestone: &Milestone, now: u64) -> i128 {
+    let (Some(due), Some(submitted_at)) = (milestone.deadline, milestone.submitted_at) else {
+        return 0;
+    };
+    if escrow.bonus == 0 || submitted_at > due || now > due {
+        return 0;
+    }
+    escrow.bonus * milestone.amount / escrow.total_amount
+}
+
+/// Approves every period that has fully elapsed by `now` and is neither paid nor disputed.
+/// Returns how many periods that was and their total.
+fn approve_elapsed_periods(escrow: &mut EscrowData, period: u64, now: u64) -> (u32, i128) {
+    let mut periods = 0u32;
+    let mut amount = 0i128;
+    for index in 0..escrow.milestones.len() {
+        let mut milestone = escrow.milestones.get(index).unwrap();
+        let ends_at = escrow.started_at.saturating_add(period.saturating_mul(index as u64 + 1));
+        if milestone.status == MilestoneStatus::NotStarted && ends_at <= now {
+            milestone.status = MilestoneStatus::Approved;
+            milestone.approved_at = Some(now);
+            amount += milestone.amount;
+            periods += 1;
+            escrow.milestones.set(index, milestone);
+        }
+    }
+    escrow.paid_amount += amount;
+    (periods, amount)
+}
+
+/// Marks every unpaid period ending after `cutoff` refunded. Returns their indexes and total,
+/// owed back to the depositor.
+fn close_periods_after(
+    e: &Env,
+    escrow: &mut EscrowData,
+    period: u64,
+    cutoff: u64,
+) -> (Vec<u32>, i128) {
+    let mut closed = Vec::new(e);
+    let mut amount = 0i128;
+    for index in 0..escrow.milestones.len() {
+        let mut milestone = escrow.milestones.get(index).unwrap();
+        let ends_at = escrow.started_at.saturating_add(period.saturating_mul(index as u64 + 1));
+        if milestone.status == MilestoneStatus::NotStarted && ends_at > cutoff {
+            milestone.status = MilestoneStatus::Refunded;
+            amount += milestone.amount;
+            closed.push_back(index);
+            escrow.milestones.set(index, milestone);
+        }
+    }
+    (closed, amount)
+}
+
+/// Pays a bounty's finalists their awards, refunds the rest and closes the escrow
+fn award_bounty(
+    e: &Env,
+    caller: &Address,
+    id: u32,
+    winners: Vec<(Address, i128)>,
+    action: &str,
+) -> Result<(), EscrowError> {
+    acquire_lock(e)?;
+
+    let mut escrow = load_escrow(e, id)?;
+
+    if resolve_role(e, id, caller, &escrow) != Role::Depositor {
+        release_lock(e);
+        return Err(EscrowError::NotAuthorized);
+    }
+    if escrow.mode != PayoutMode::Bounty {
+        release_lock(e);
+        return Err(EscrowError::WrongMode);
+    }
+    if escrow.status != EscrowStatus::Pending {
+        release_lock(e);
+        return Err(EscrowError::AlreadyCompleted);
+    }
+    if is_frozen(e, &escrow) {
+        release_lock(e);
+        return Err(EscrowError::Frozen);
+    }
+    let Some((first, _)) = winners.first() else {
+        release_lock(e);
+        return Err(EscrowError::InvalidBeneficiary);
+    };
+    let entries = load_entries(e, id);
+    let mut awarded: i128 = 0;
+    for (index, (winner, amount)) in winners.iter().enumerate() {
+        if amount <= 0 {
+            release_lock(e);
+            return Err(EscrowError::ZeroAmount);
+        }
+        if !entries.iter().any(|(entrant, _)| entrant == winner)
+            || winners.iter().take(index).any(|(other, _)| other == winner)
+        {
+            release_lock(e);
+            return Err(EscrowError::InvalidBeneficiary);
+        }
+        if is_blocked(e, &winner) {
+            release_lock(e);
+            return Err(EscrowError::Blocked);
+        }
+        awarded = awarded.saturating_add(amount);
+    }
+    if awarded > escrow.total_amount {
+        release_lock(e);
+        return Err(EscrowError::AmountOutOfBounds);
+    }
+
+    let now = e.ledger().timestamp();
+    // The winner entered the bounty, so it has agreed to be listed
+    index_add(e, &(sym_by_beneficiary(), first.clone()), id);
+    escrow.beneficiary = first;
+    escrow.work_started = true;
+    escrow.started_at = now;
+    for index in 0..escrow.milestones.len() {
+        let mut milestone = escrow.milestones.get(index).unwrap();
+        milestone.status = MilestoneStatus::Approved;
+        escrow.milestones.set(index, milestone);
+    }
+    escrow.paid_amount = awarded;
+    escrow.status = EscrowStatus::Released;
+    store_escrow(e, id, &escrow, caller);
+
+    for (winner, amount) in winners.iter() {
+        pay_beneficiary(e, id, &escrow, amount, Some(&winner))?;
+        if escrow.private_events {
+            BountyAwardedMinimal { id, seq: next_event_seq(e) }.publish(e);
+        } else {
+            BountyAwarded {
+                id,
+                winner,
+                amount,
+                seq: next_event_seq(e),
+            }
+            .publish(e);
+        }
+    }
+    let remainder = escrow.total_amount - awarded;
+    if remainder > 0 {
+        refund_depositor(e, &escrow, remainder)?;
+        publish_refund(e, id, &escrow, remainder, Vec::new(e));
+    }
+    complete_escrow(e, id, &escrow, caller)?;
+
+    release_lock(e);
+    log_action(e, id, caller, action, None);
+    Ok(())
+}
+
+/// Pays the beneficiary whatever the escrow's schedule has unlocked beyond earlier claims,
+/// completing the escrow once everything is claimed
+fn claim_vested_funds(
+    e: &Env,
+    caller: Address,
+    id: u32,
+    accepts: fn(&PayoutMode) -> bool,
+    action: &str,
+) -> Result<i128, EscrowError> {
+    ensure_not_paused(e)?;
+    caller.require_auth();
+    acquire_lock(e)?;
+
+    let mut escrow = load_escrow(e, id)?;
+
+    if resolve_role(e, id, &caller, &escrow) != Role::Beneficiary {
+        release_lock(e);
+        return Err(EscrowError::NotAuthorized);
+    }
+    if !accepts(&escrow.mode) {
+        release_lock(e);
+        return Err(EscrowError::WrongMode);
+    }
+    if escrow.status != EscrowStatus::InProgress {
+        release_lock(e);
+        return Err(EscrowError::NotAuthorized);
+    }
+    if is_frozen(e, &escrow) {
+        release_lock(e);
+        return Err(EscrowError::Frozen);
+    }
+
+    let now = e.ledger().timestamp();
+    let amount = vested_amount(&escrow, now) - escrow.claimed;
+    if amount <= 0 {
+        release_lock(e);
+        return Err(EscrowError::ZeroAmount);
+    }
+
+    escrow.claimed += amount;
+    escrow.paid_amount += amount;
+    if escrow.claimed == escrow.total_amount {
+        let mut milestone = escrow.milestones.get(0).unwrap();
+        milestone.status = MilestoneStatus::Approved;
+        milestone.approved_at = Some(now);
+        escrow.milestones.set(0, milestone);
+        escrow.status = EscrowStatus::Released;
+    }
+    store_escrow(e, id, &escrow, &caller);
+    pay_beneficiary(e, id, &escrow, amount, None)?;
+
+    if escrow.private_events {
+        VestedClaimedMinimal { id, seq: next_event_seq(e) }.publish(e);
+    } else {
+        VestedClaimed {
+            id,
+            amount,
+            claimed: escrow.claimed,
+            seq: next_event_seq(e),
+        }
+        .publish(e);
+    }
+    if escrow.status == EscrowStatus::Released {
+        complete_escrow(e, id, &escrow, &caller)?;
+    }
+
+    release_lock(e);
+    log_action(e, id, &caller, action, None);
+    Ok(amount)
+}
+
+/// Marks a submitted milestone approved, pays it out and completes the escrow if it was the last
+fn release_milestone(
+    e: &Env,
+    id: u32,
+    mut escrow: EscrowData,
+    milestone_index: u32,
+    mut milestone: Milestone,
+    tip: i128,
+    actor: &Address,
+) -> Result<(), EscrowError> {
+    let now = e.ledger().timestamp();
+    milestone.status = MilestoneStatus::Approved;
+    milestone.approved_at = Some(now);
+
+    // Late penalties go back to the depositor
+    let penalty = milestone.amount * milestone.penalty_bps as i128 / 10_000;
+    let amount = milestone.amount - penalty;
+    let bonus = early_bonus(&escrow, &milestone, now);
+    let recipient = milestone.recipient.clone();
+    escrow.bonus_held -= bonus;
+    escrow.paid_amount += amount;
+    if escrow.milestones.len() == 1 {
+        // Single-deliverable gigs dominate volume. Their one approval settles the escrow, so
+        // the list is replaced outright rather than patched and rescanned for the new status.
+        escrow.milestones = Vec::from_array(e, [milestone]);
+        escrow.status = EscrowStatus::Released;
+    } else {
+        escrow.milestones.set(milestone_index, milestone);
+        refresh_status(&mut escrow, now);
+    }
+
+    store_escrow(e, id, &escrow, actor);
+
+    // Transfer payment, less whatever the payout tail holds back
+    let deferred = defer_payout(e, id, &escrow, milestone_index, amount + bonus, recipient.clone());
+    if amount + bonus > deferred {
+        pay_beneficiary(e, id, &escrow, amount + bonus - deferred, recipient.as_ref())?;
+    }
+    refund_depositor(e, &escrow, penalty)?;
+    if tip > 0 {
+        // A tip follows the milestone payout, but is never split across the team
+        let to = payout_override(&escrow, recipient.as_ref())
+            .cloned()
+            .unwrap_or_else(|| payout_wallet(e, id, &escrow));
+        safe_transfer(e, &escrow.token, &escrow.depositor, &to, &tip)?;
+    }
+
+    if escrow.private_events {
+        MilestoneApprovedMinimal { id, milestone_index, seq: next_event_seq(e) }.publish(e);
+    } else {
+        MilestoneApproved {
+            id,
+            milestone_index,
+            amount,
+            tip,
+            seq: next_event_seq(e),
+        }
+        .publish(e);
+    }
+    if escrow.status == EscrowStatus::Released {
+        complete_escrow(e, id, &escrow, actor)?;
+    }
+    Ok(())
+}
+
+/// Releases the escrow once every milestone is paid, or resumes work once no dispute is open.
+/// Leaving the disputed state pushes the deadline back by the time spent in arbitration.
+fn refresh_status(escrow: &mut EscrowData, now: u64) {
+    if !has_open_dispute(escrow) {
+        if let Some(since) = escrow.disputed_since.take() {
+            escrow.deadline = escrow.deadline.saturating_add(now.saturating_sub(since));
+        }
+    }
+
+    if escrow.milestones.iter().all(|m| is_settled(&m.status)) {
+        // Once any period was handed back the engagement ended early rather than completing
+        escrow.status = if escrow.milestones.iter().any(|m| m.status == MilestoneStatus::Refunded) {
+            EscrowStatus::Refunded
+        } else {
+            EscrowStatus::Released
+        };
+    } else if !has_open_dispute(escrow) {
+        escrow.status = EscrowStatus::InProgress;
+    }
+}
+
+/// Closes a disputed milestone with the arbiter's split and returns the depositor's refund
+fn apply_ruling(
+    e: &Env,
+    id: u32,
+    escrow: &mut EscrowData,
+    milestone_index: u32,
+    pay_to_beneficiary: i128,
+    rationale_hash: Option<BytesN<32>>,
+) -> Result<i128, EscrowError> {
+    if milestone_index >= escrow.milestones.len() {
+        return Err(EscrowError::InvalidMilestone);
+    }
+
+    let mut milestone = escrow.milestones.get(milestone_index).unwrap();
+
+    if milestone.status != MilestoneStatus::Disputed {
+        return Err(EscrowError::NotAuthorized);
+    }
+
+    if matches!(escrow.mode, PayoutMode::Hourly(_)) {
+        return apply_sheet_ruling(e, id, escrow, milestone, pay_to_beneficiary, rationale_hash);
+    }
+
+    // A stopped stream only splits what has not been claimed yet
+    let milestone_amount = milestone.amount - escrow.claimed;
+
+    if pay_to_beneficiary < 0 || pay_to_beneficiary > milestone_amount {
+        return Err(EscrowError::InvalidMilestone);
+    }
+
+    milestone.status = MilestoneStatus::Approved;
+    milestone.rationale_hash = rationale_hash;
+    escrow.milestones.set(milestone_index, milestone);
+
+    Ok(milestone_amount - pay_to_beneficiary)
+}
+
+/// An hourly ruling splits only the disputed timesheet. The rest of the cap stays open and
+/// other sheets remain reviewable, unless the ruled sheet used up the cap.
+fn apply_sheet_ruling(
+    e: &Env,
+    id: u32,
+    escrow: &mut EscrowData,
+    mut milestone: Milestone,
+    pay_to_beneficiary: i128,
+    rationale_hash: Option<BytesN<32>>,
+) -> Result<i128, EscrowError> {
+    let mut sheets = load_sheets(e, id);
+    let Some(index) = sheets.iter().position(|sheet| sheet.status == MilestoneStatus::Disputed) else {
+        return Err(EscrowError::InvalidMilestone);
+    };
+    let mut sheet = sheets.get(index as u32).unwrap();
+    if pay_to_beneficiary < 0 || pay_to_beneficiary > sheet.amount {
+        return Err(EscrowError::InvalidMilestone);
+    }
+
+    let amount = sheet.amount;
+    sheet.status = MilestoneStatus::Approved;
+    sheets.set(index as u32, sheet);
+    store_sheets(e, id, escrow, &sheets);
+    escrow.claimed += amount;
+    milestone.status = if escrow.claimed == milestone.amount {
+        milestone.approved_at = Some(e.ledger().timestamp());
+        MilestoneStatus::Approved
+    } else {
+        MilestoneStatus::NotStarted
+    };
+    milestone.rationale_hash = rationale_hash;
+    escrow.milestones.set(0, milestone);
+
+    Ok(amount - pay_to_beneficiary)
+}
+
+fn publish_resolution(
+    e: &Env,
+    escrow: &EscrowData,
+    id: u32,
+    milestone_index: u32,
+    pay_to_beneficiary: i128,
+    rationale_hash: &BytesN<32>,
+) {
+    if escrow.private_events {
+        DisputeResolvedMinimal {
+            id,
+            milestone_index,
+            rationale_hash: rationale_hash.clone(),
+            seq: next_event_seq(e),
+        }
+        .publish(e);
+    } else {
+        DisputeResolved {
+            id,
+            milestone_index,
+            pay_to_beneficiary,
+            rationale_hash: rationale_hash.clone(),
+            seq: next_event_seq(e),
+        }
+        .publish(e);
+    }
+}
+
+fn pay_ruling(
+    e: &Env,
+    id: u32,
+    escrow: &EscrowData,
+    pay_to_beneficiary: i128,
+    refund: i128,
+) -> Result<(), EscrowError> {
+    // Pay beneficiary their portion
+    if pay_to_beneficiary > 0 {
+        pay_beneficiary(e, id, escrow, pay_to_beneficiary, None)?;
+    }
+
+    // Refund depositor the rest
+    refund_depositor(e, escrow, refund)
+}
+
+/// Deadline as it stands once time spent in an ongoing dispute is excluded
+fn effective_deadline(escrow: &EscrowData, now: u64) -> u64 {
+    match escrow.disputed_since {
+        Some(since) => escrow.deadline.saturating_add(now.saturating_sub(since)),
+        None => escrow.deadline,
+    }
+}
+
+fn summarize(escrow: &EscrowData, now: u64) -> EscrowSummary {
+    let mut summary = EscrowSummary {
+        depositor: escrow.depositor.clone(),
+        beneficiary: escrow.beneficiary.clone(),
+        arbiter: escrow.arbiter.clone(),
+        token: escrow.token.clone(),
+        total_amount: escrow.total_amount,
+        paid_amount: escrow.paid_amount,
+        status: escrow.status.clone(),
+        deadline: effective_deadline(escrow, now),
+        milestones: escrow.milestones.len(),
+        not_started: 0,
+        submitted: 0,
+        approved: 0,
+        disputed: 0,
+        refunded: 0,
+    };
+    for milestone in escrow.milestones.iter() {
+        match milestone.status {
+            MilestoneStatus::NotStarted => summary.not_started += 1,
+            MilestoneStatus::Submitted => summary.submitted += 1,
+            MilestoneStatus::Approved => summary.approved += 1,
+            MilestoneStatus::Disputed => summary.disputed += 1,
+            MilestoneStatus::Refunded => summary.refunded += 1,
+        }
+    }
+    summary
+}
+
+/// Paid out or handed back; nothing more is owed on the milestone either way
+fn is_settled(status: &MilestoneStatus) -> bool {
+    matches!(status, MilestoneStatus::Approved | MilestoneStatus::Refunded)
+}
+
+fn is_active(escrow: &EscrowData) -> bool {
+    escrow.status != EscrowStatus::Released && escrow.status != EscrowStatus::Refunded
+}
+
+/// Amount still owed on unpaid milestones (dispute refunds are not double counted)
+fn outstanding_amount(escrow: &EscrowData) -> i128 {
+    let mut outstanding: i128 = 0;
+    for milestone in escrow.milestones.iter() {
+        if !is_settled(&milestone.status) {
+            outstanding += milestone.amount;
+        }
+    }
+    // Streamed claims come out of the single milestone until it is closed
+    if outstanding > 0 {
+        outstanding -= escrow.claimed;
+    }
+    outstanding
+}
+
+/// Part of the total committed but not deposited yet, by contributors or milestone by milestone
+fn unfunded_amount(escrow: &EscrowData) -> i128 {
+    if escrow.funding == FundingMode::Upfront {
+        return 0;
+    }
+    escrow.total_amount - contributed_amount(escrow)
+}
+
+/// Deposits needed to cover milestones up to and including `milestone_index`. Pay-as-you-go
+/// escrows are funded in milestone order, so this against `contributed_amount` says which are.
+fn funded_through(escrow: &EscrowData, milestone_index: u32) -> i128 {
+    escrow.milestones.iter().take(milestone_index as usize + 1).map(|m| m.amount).sum()
+}
+
+fn contributed_amount(escrow: &EscrowData) -> i128 {
+    escrow.contributions.iter().map(|(_, amount)| amount).sum()
+}
+
+fn add_contribution(escrow: &mut EscrowData, contributor: &Address, amount: i128) {
+    let existing = escrow.contributions.iter().position(|(addr, _)| addr == *contributor);
+    match existing {
+        Some(index) => {
+            let (addr, previous) = escrow.contributions.get(index as u32).unwrap();
+            escrow.contributions.set(index as u32, (addr, previous + amount));
+        }
+        None => escrow.contributions.push_back((contributor.clone(), amount)),
+    }
+}
+
+/// Returns escrowed principal to whoever put it up: the depositor, or the contributors of a
+/// crowdfunded escrow in proportion to what each gave (the last takes the rounding remainder)
+fn refund_depositor(e: &Env, escrow: &EscrowData, amount: i128) -> Result<(), EscrowError> {
+    if amount <= 0 {
+        return Ok(());
+    }
+    if escrow.funding == FundingMode::Upfront {
+        return safe_transfer(e, &escrow.token, &e.current_contract_address(), &escrow.depositor, &amount);
+    }
+    let contributed = contributed_amount(escrow);
+    let mut remaining = amount;
+    let last = escrow.contributions.len().saturating_sub(1);
+    for (index, (contributor, contribution)) in escrow.contributions.iter().enumerate() {
+        let share = if index as u32 == last { remaining } else { amount * contribution / contributed };
+        remaining -= share;
+        if share > 0 {
+            safe_transfer(e, &escrow.token, &e.current_contract_address(), &contributor, &share)?;
+        }
+    }
+    Ok(())
+}
+
+/// Amount a stream or vesting escrow has unlocked by `now`, out of its total
+fn vested_amount(escrow: &EscrowData, now: u64) -> i128 {
+    if !escrow.work_started {
+        return 0;
+    }
+    let elapsed = now.saturating_sub(escrow.started_at);
+    match &escrow.mode {
+        PayoutMode::Milestones
+        | PayoutMode::Subscription(_)
+        | PayoutMode::Bounty
+        | PayoutMode::Hourly(_)
+        | PayoutMode::Retainer(_) => 0,
+        PayoutMode::Stream => {
+            if now >= escrow.deadline || escrow.deadline <= escrow.started_at {
+                return escrow.total_amount;
+            }
+            let span = (escrow.deadline - escrow.started_at) as i128;
+            escrow.total_amount * elapsed as i128 / span
+        }
+        PayoutMode::Vesting(schedule) => {
+            if elapsed < schedule.cliff {
+                return 0;
+            }
+            let periods = ((elapsed - schedule.cliff) / schedule.period) as i128 + 1;
+            periods
+                .saturating_mul(schedule.amount_per_period)
+                .min(escrow.total_amount)
+        }
+    }
+}
+
+/// Rejects schedules that cannot unlock the whole escrow within its duration
+fn check_vesting(schedule: &VestingSchedule, total: i128, duration: u64) -> Result<(), EscrowError> {
+    if schedule.period == 0 || schedule.amount_per_period <= 0 {
+        return Err(EscrowError::InvalidConfig);
+    }
+    let steps = (total + schedule.amount_per_period - 1) / schedule.amount_per_period;
+    let fully_vested = (steps as u64).saturating_sub(1)
+        .checked_mul(schedule.period)
+        .and_then(|secs| secs.checked_add(schedule.cliff))
+        .ok_or(EscrowError::InvalidDuration)?;
+    if fully_vested > duration {
+        return Err(EscrowError::InvalidDuration);
+    }
+    Ok(())
+}
+
+/// Funds an escrow still holds for its parties: unpaid milestones and any unsettled bonus
+fn locked_amount(escrow: &EscrowData) -> i128 {
+    if is_active(escrow) {
+        outstanding_amount(escrow) + escrow.bonus_held - unfunded_amount(escrow)
+    } else {
+        0
+    }
+}
+
+/// Running total of `locked_amount` across all escrows in `token`, kept by `store_escrow`
+fn load_total_locked(e: &Env, token: &Address) -> i128 {
+    e.storage()
+        .persistent()
+        .get(&(sym_locked(), token.clone()))
+        .unwrap_or(0)
+}
+
+fn adjust_locked(e: &Env, token: &Address, delta: i128) {
+    if delta == 0 {
+        return;
+    }
+    let key = (sym_locked(), token.clone());
+    let total = load_total_locked(e, token) + delta;
+    e.storage().persistent().set(&key, &total);
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+}
+
+fn entries_key(id: u32) -> (Symbol, u32) {
+    (sym_entries(), id)
+}
+
+fn load_entries(e: &Env, id: u32) -> Vec<(Address, BytesN<32>)> {
+    e.storage().persistent().get(&entries_key(id)).unwrap_or(Vec::new(e))
+}
+
+fn project_key(id: u32) -> (Symbol, u32) {
+    (sym_projects(), id)
+}
+
+fn load_project(e: &Env, id: u32) -> Result<Project, EscrowError> {
+    e.storage()
+        .persistent()
+        .get(&project_key(id))
+        .ok_or(EscrowError::EscrowNotFound)
+}
+
+fn tail_key(id: u32) -> (Symbol, u32) {
+    (sym_tail(), id)
+}
+
+fn accruals_key(id: u32) -> (Symbol, u32) {
+    (sym_accruals(), id)
+}
+
+fn load_accruals(e: &Env, id: u32) -> Vec<Accrual> {
+    e.storage().persistent().get(&accruals_key(id)).unwrap_or(Vec::new(e))
+}
+
+fn store_accruals(e: &Env, id: u32, accruals: &Vec<Accrual>) {
+    let key = accruals_key(id);
+    if accruals.is_empty() {
+        e.storage().persistent().remove(&key);
+        return;
+    }
+    e.storage().persistent().set(&key, accruals);
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+}
+
+/// Part of an accrual unlocked by `now`, out of its amount
+fn accrued_amount(accrual: &Accrual, now: u64) -> i128 {
+    if now >= accrual.end {
+        return accrual.amount;
+    }
+    let elapsed = now.saturating_sub(accrual.start) as i128;
+    accrual.amount * elapsed / (accrual.end - accrual.start) as i128
+}
+
+/// Holds back the part of a milestone payout the escrow's tail defers, keeping the escrow
+/// around until it has all accrued. Returns the amount held back.
+fn defer_payout(
+    e: &Env,
+    id: u32,
+    escrow: &EscrowData,
+    milestone_index: u32,
+    payout: i128,
+    recipient: Option<Address>,
+) -> i128 {
+    let Some(tail) = e.storage().persistent().get::<_, PayoutTail>(&tail_key(id)) else {
+        return 0;
+    };
+    let deferred = payout - payout * tail.upfront_bps as i128 / 10_000;
+    if deferred <= 0 {
+        return 0;
+    }
+    let now = e.ledger().timestamp();
+    let mut accruals = load_accruals(e, id);
+    accruals.push_back(Accrual {
+        milestone_index,
+        amount: deferred,
+        claimed: 0,
+        start: now,
+        end: now.saturating_add(tail.duration),
+        recipient,
+    });
+    store_accruals(e, id, &accruals);
+    adjust_locked(e, &escrow.token, deferred);
+
+    let tail_ledgers = (tail.duration / LEDGER_SECS).try_into().unwrap_or(u32::MAX);
+    let ttl = escrow_ttl_ledgers(e, escrow).saturating_add(tail_ledgers);
+    e.storage().persistent().extend_ttl(&escrow_key(id), ttl, ttl);
+    deferred
+}
+
+fn payto_key(id: u32) -> (Symbol, u32) {
+    (sym_payto(), id)
+}
+
+/// Wallet the beneficiary asked to be paid at, if it changed it
+fn load_payout_address(e: &Env, id: u32) -> Option<Address> {
+    e.storage().persistent().get(&payto_key(id))
+}
+
+fn roles_key(id: u32) -> (Symbol, u32) {
+    (sym_roles(), id)
+}
+
+/// Delegates and observers the depositor has named
+fn load_granted_roles(e: &Env, id: u32) -> Map<Address, Role> {
+    e.storage().persistent().get(&roles_key(id)).unwrap_or(Map::new(e))
+}
+
+fn sheets_key(id: u32) -> (Symbol, u32) {
+    (sym_sheets(), id)
+}
+
+fn load_sheets(e: &Env, id: u32) -> Vec<Timesheet> {
+    e.storage().persistent().get(&sheets_key(id)).unwrap_or(Vec::new(e))
+}
+
+fn store_sheets(e: &Env, id: u32, escrow: &EscrowData, sheets: &Vec<Timesheet>) {
+    let key = sheets_key(id);
+    let ttl = escrow_ttl_ledgers(e, escrow);
+    e.storage().persistent().set(&key, sheets);
+    e.storage().persistent().extend_ttl(&key, ttl, ttl);
+}
+
+fn payroll_key(id: u32) -> (Symbol, u32) {
+    (sym_payrolls(), id)
+}
+
+fn scheduled_key(id: u32) -> (Symbol, u32) {
+    (sym_scheduled(), id)
+}
+
+fn load_scheduled(e: &Env, id: u32) -> Result<ScheduledPayment, EscrowError> {
+    e.storage()
+        .persistent()
+        .get(&scheduled_key(id))
+        .ok_or(EscrowError::EscrowNotFound)
+}
+
+fn store_scheduled(e: &Env, id: u32, payment: &ScheduledPayment) {
+    let key = scheduled_key(id);
+    e.storage().persistent().set(&key, payment);
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+}
+
+fn request_key(id: u32) -> (Symbol, u32) {
+    (sym_requests(), id)
+}
+
+fn load_request(e: &Env, id: u32) -> Result<PaymentRequest, EscrowError> {
+    e.storage()
+        .persistent()
+        .get(&request_key(id))
+        .ok_or(EscrowError::EscrowNotFound)
+}
+
+fn store_request(e: &Env, id: u32, request: &PaymentRequest) {
+    let key = request_key(id);
+    e.storage().persistent().set(&key, request);
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+}
+
+/// Moves a payment outside any escrow straight through the contract, less the platform fee,
+/// and credits it to both parties' stats and the token's volume
+fn pay_now(
+    e: &Env,
+    token: &Address,
+    from: &Address,
+    to: &Address,
+    amount: i128,
+) -> Result<(), EscrowError> {
+    let config = load_config(e)?;
+    safe_transfer(e, token, from, &e.current_contract_address(), &amount)?;
+    pay_out(e, token, from, to, amount, config.fee_bps)
+}
+
+/// Pays funds the contract already holds for `from` on to `to`, less `fee_bps`, crediting the
+/// payment like `pay_now`
+fn pay_out(
+    e: &Env,
+    token: &Address,
+    from: &Address,
+    to: &Address,
+    amount: i128,
+    fee_bps: u32,
+) -> Result<(), EscrowError> {
+    let fee = amount * fee_bps as i128 / 10_000;
+    if fee > 0 {
+        accrue_fees(e, token, fee);
+    }
+    safe_transfer(e, token, &e.current_contract_address(), to, &(amount - fee))?;
+
+    update_user_stats(e, from, |stats| stats.total_spent += amount);
+    update_user_stats(e, to, |stats| stats.total_earned += amount);
+    let volume_key = (sym_volume(), token.clone());
+    let total = load_volume(e, token) + amount;
+    e.storage().persistent().set(&volume_key, &total);
+    e.storage().persistent().extend_ttl(&volume_key, 0u32, COUNTER_TTL_SECS);
+    Ok(())
+}
+
+/// Marks an open request settled or declined and announces it
+fn close_request(e: &Env, id: u32, request: &mut PaymentRequest, status: RequestStatus) {
+    request.status = status.clone();
+    store_request(e, id, request);
+    PaymentRequestClosed {
+        id,
+        status,
+        seq: next_event_seq(e),
+    }
+    .publish(e);
+}
+
+fn load_payroll(e: &Env, id: u32) -> Result<Payroll, EscrowError> {
+    e.storage()
+        .persistent()
+        .get(&payroll_key(id))
+        .ok_or(EscrowError::EscrowNotFound)
+}
+
+fn store_payroll(e: &Env, id: u32, payroll: &Payroll) {
+    let key = payroll_key(id);
+    e.storage().persistent().set(&key, payroll);
+    e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+}
+
+/// Pays recipient `index` its share less the platform fee and marks it paid
+fn pay_payroll_recipient(
+    e: &Env,
+    id: u32,
+    payroll: &mut Payroll,
+    index: u32,
+) -> Result<i128, EscrowError> {
+    let (recipient, amount) = payroll.recipients.get(index).unwrap();
+    let fee = amount * payroll.fee_bps as i128 / 10_000;
+    if fee > 0 {
+        accrue_fees(e, &payroll.token, fee);
+    }
+    payroll.paid.set(index, true);
+    adjust_locked(e, &payroll.token, -amount);
+    safe_transfer(
+        e,
+        &payroll.token,
+        &e.current_contract_address(),
+        &recipient,
+        &(amount - fee),
+    )?;
+    PayrollPaid {
+        id,
+        recipient,
+        amount,
+        seq: next_event_seq(e),
+    }
+    .publish(e);
+    Ok(amount)
+}
+
+/// Everything the contract owes in `token`: escrowed funds, accrued platform fees and the
+/// insurance pool
+fn token_liabilities(e: &Env, token: &Address) -> i128 {
+    load_total_locked(e, token) + load_accrued_fees(e, token) + load_insurance_pool(e, token)
+}
+
+/// Kill fee owed when the client cancels a started escrow. Only reachable before any
+/// submission, so nothing has been paid out yet; capped at what was actually deposited.
+fn kill_fee(escrow: &EscrowData) -> i128 {
+    let fee = escrow.total_amount * escrow.kill_fee_bps as i128 / 10_000;
+    fee.min(escrow.total_amount - unfunded_amount(escrow))
+}
+
+fn funding_status(escrow: &EscrowData) -> FundingStatus {
+    // A closed escrow owes nothing further, whatever was left unfunded
+    let funded = escrow.total_amount - unfunded_amount(escrow);
+    let (unfunded, held) = if is_active(escrow) {
+        let unfunded = unfunded_amount(escrow);
+        (unfunded, outstanding_amount(escrow) - unfunded)
+    } else {
+        (0, 0)
+    };
+    // Refund is only open before work starts, so a started refunded escrow was cancelled
+    let paid = if escrow.status == EscrowStatus::Refunded && escrow.work_started {
+        kill_fee(escrow)
+    } else {
+        escrow.paid_amount
+    };
+    FundingStatus {
+        funded,
+        unfunded,
+        paid,
+        refunded: funded - paid - held,
+        held,
+    }
+}
+
+fn compute_risk(e: &Env, escrow: &EscrowData) -> RiskReport {
+    let now = e.ledger().timestamp();
+    let mut report = RiskReport {
+        score: 0,
+        disputed_milestones: 0,
+        stalled_disputes: 0,
+        stale_reviews: 0,
+        overdue: false,
+        shortfall: false,
+    };
+
+    for milestone in escrow.milestones.iter() {
+        let waited_past_period = milestone
+            .submitted_at
+            .map(|at| now.saturating_sub(at) > escrow.review_period)
+            .unwrap_or(false);
+        match milestone.status {
+            MilestoneStatus::Disputed => {
+                report.disputed_milestones += 1;
+                if waited_past_period {
+                    report.stalled_disputes += 1;
+                }
+            }
+            MilestoneStatus::Submitted if waited_past_period => {
+                report.stale_reviews += 1;
+            }
+            _ => {}
+        }
+    }
+
+    let outstanding = outstanding_amount(escrow);
+    if is_active(escrow) && outstanding > 0 {
+        report.overdue = now >= effective_deadline(escrow, now);
+        let balance = token::Client::new(e, &escrow.token).balance(&e.current_contract_address());
+        report.shortfall = balance < outstanding;
+    }
+
+    let mut score: u32 = report.disputed_milestones * 15
+        + report.stalled_disputes * 10
+        + report.stale_reviews * 10;
+    if report.overdue {
+        score += 25;
+    }
+    if report.shortfall {
+        score += 50;
+    }
+    report.score = score.min(100);
+    report
+}
+
+/// Everything that must hold for `caller` to refund the escrow
+fn check_refund(
+    e: &Env,
+    id: u32,
+    escrow: &EscrowData,
+    caller: &Address,
+) -> Result<(), EscrowError> {
+    if resolve_role(e, id, caller, escrow) != Role::Depositor {
+        return Err(EscrowError::NotAuthorized);
+    }
+    if is_frozen(e, escrow) {
+        return Err(EscrowError::Frozen);
+    }
+    if escrow.work_started {
+        return Err(EscrowError::WorkStarted);
+    }
+    if escrow.status != EscrowStatus::Pending {
+        return Err(EscrowError::AlreadyCompleted);
+    }
+    let now = e.ledger().timestamp();
+    if escrow.mode == PayoutMode::Bounty {
+        // Entrants are owed a decision until the deadline; after it the funds can go back
+        if now < escrow.deadline && !load_entries(e, id).is_empty() {
+            return Err(EscrowError::NotYetDue);
+        }
+        return Ok(());
+    }
+    if now >= escrow.deadline {
+        return Err(EscrowError::NotAuthorized);
+    }
+    Ok(())
+}
+
+/// Everything that must hold for `caller` to submit the milestone
+fn check_submit(
+    e: &Env,
+    id: u32,
+    escrow: &EscrowData,
+    caller: &Address,
+    milestone_index: u32,
+) -> Result<(), EscrowError> {
+    if resolve_role(e, id, caller, escrow) != Role::Beneficiary {
+        return Err(EscrowError::NotAuthorized);
+    }
+    if escrow.mode != PayoutMode::Milestones {
+        return Err(EscrowError::WrongMode);
+    }
+    if escrow.status != EscrowStatus::InProgress {
+        return Err(EscrowError::NotAuthorized);
+    }
+    let milestone = escrow
+        .milestones
+        .get(milestone_index)
+        .ok_or(EscrowError::InvalidMilestone)?;
+    if milestone.status != MilestoneStatus::NotStarted {
+        return Err(EscrowError::MilestoneAlreadySubmitted);
+    }
+    if escrow.funding == FundingMode::PerMilestone
+        && contributed_amount(escrow) < funded_through(escrow, milestone_index)
+    {
+        return Err(EscrowError::NotFunded);
+    }
+
+    let now = e.ledger().timestamp();
+    if let Some(requested_at) = milestone.revision_requested_at {
+        if now < requested_at.saturating_add(escrow.resubmit_cooldown) {
+            return Err(EscrowError::SubmissionCooldown);
+        }
+    }
+    if escrow.submission_cutoff > 0
+        && now >= escrow.deadline.saturating_sub(escrow.submission_cutoff)
+    {
+        return Err(EscrowError::SubmissionWindowClosed);
+    }
+    Ok(())
+}
+
+/// Everything that must hold for `caller` to approve the milestone
+fn check_approve(
+    e: &Env,
+    id: u32,
+    escrow: &EscrowData,
+    caller: &Address,
+    milestone_index: u32,
+) -> Result<(), EscrowError> {
+    if !matches!(resolve_role(e, id, caller, escrow), Role::Depositor | Role::Delegate) {
+        return Err(EscrowError::NotAuthorized);
+    }
+    if is_frozen(e, escrow) {
+        return Err(EscrowError::Frozen);
+    }
+    let milestone = escrow
+        .milestones
+        .get(milestone_index)
+        .ok_or(EscrowError::InvalidMilestone)?;
+    if milestone.status != MilestoneStatus::Submitted {
+        return Err(EscrowError::MilestoneNotSubmitted);
+    }
+    Ok(())
+}
+
+/// Outcome of an action check for frontends
+fn capability(
+    e: &Env,
+    id: u32,
+    check: impl FnOnce(&EscrowData) -> Result<(), EscrowError>,
+) -> Capability {
+    match load_escrow(e, id).and_then(|escrow| check(&escrow)) {
+        Ok(()) => Capability { allowed: true, reason: 0 },
+        Err(err) => Capability { allowed: false, reason: err as u32 },
+    }
+}
+
+/// Shared approval path; `results` records the checklist outcome when supplied
+fn approve(
+    e: &Env,
+    caller: Address,
+    id: u32,
+    milestone_index: u32,
+    results: Option<Vec<bool>>,
+    tip: i128,
+) -> Result<(), EscrowError> {
+    caller.require_auth();
+    acquire_lock(e)?;
+
+    let escrow = load_escrow(e, id)?;
+
+    if let Err(err) = check_approve(e, id, &escrow, &caller, milestone_index) {
+        release_lock(e);
+        return Err(err);
+    }
+    // Tips come out of the depositor's own wallet
+    if tip > 0 && resolve_role(e, id, &caller, &escrow) != Role::Depositor {
+        release_lock(e);
+        return Err(EscrowError::NotAuthorized);
+    }
+
+    let mut milestone = escrow.milestones.get(milestone_index).unwrap();
+
+    if let Some(results) = results {
+        if results.len() != milestone.checklist.len() {
+            release_lock(e);
+            return Err(EscrowError::InvalidMilestone);
+        }
+        milestone.checklist_results = Some(results);
+    }
+
+    let res = release_milestone(e, id, escrow, milestone_index, milestone, tip, &caller);
+    release_lock(e);
+    log_action(e, id, &caller, "approve_milestone", Some(milestone_index));
+    res
+}
+
+/// Counts a creation against the depositor's window, failing once the limit is reached
+fn check_create_rate(e: &Env, config: &Config, depositor: &Address) -> Result<(), EscrowError> {
+    if config.create_limit == 0 {
+        return Ok(());
+    }
+    let now = e.ledger().timestamp();
+    let key = (sym_create_windows(), depositor.clone());
+    let mut window: CreateWindow = e
+        .storage()
+        .temporary()
+        .get(&key)
+        .filter(|w: &CreateWindow| now < w.started_at.saturating_add(config.create_window))
+        .unwrap_or(CreateWindow { started_at: now, count: 0 });
+    if window.count >= config.create_limit {
+        return Err(EscrowError::RateLimited);
+    }
+    window.count += 1;
+    e.storage().temporary().set(&key, &window);
+    let ttl = (config.create_window / LEDGER_SECS + 1) as u32;
+    e.storage().temporary().extend_ttl(&key, ttl, ttl);
+    Ok(())
+}
+
+fn load_marketplace_fee(e: &Env, marketplace: &Address) -> Option<u32> {
+    e.storage().persistent().get(&marketplace_key(marketplace))
+}
+
+fn create_escrow(
+    e: &Env,
+    params: CreateParams,
+    marketplace: Option<Address>,
+) -> Result<u32, EscrowError> {
+    let CreateParams {
+        depositor,
+        beneficiary,
+        arbiter,
+        milestone_amounts,
+        token,
+        duration,
+        private_events,
+        checklists,
+        review_period,
+        resubmit_cooldown,
+        submission_cutoff,
+        governance,
+        insured,
+        milestone_deadlines,
+        penalty,
+        bonus,
+        kill_fee_bps,
+        referrer,
+        referral_bps,
+        mode,
+        splits,
+        milestone_recipients,
+        funding,
+        transferable,
+        project,
+        payout_tail,
+    } = params;
+    depositor.require_auth();
+    // The contract stands in as beneficiary until a bounty winner is picked
+    let beneficiary = if mode == PayoutMode::Bounty {
+        e.current_contract_address()
+    } else {
+        beneficiary
+    };
+    let marketplace_fee_bps = match &marketplace {
+        Some(marketplace) => {
+            marketplace.require_auth();
+            load_marketplace_fee(e, marketplace).ok_or(EscrowError::MarketplaceNotFound)?
+        }
+        None => 0,
+    };
+
+    if beneficiary == depositor {
+        return Err(EscrowError::InvalidBeneficiary);
+    }
+    let arbiter = match arbiter {
+        Some(arbiter) => arbiter,
+        None => pick_pool_arbiter(e, &depositor, &beneficiary)?,
+    };
+    if arbiter == depositor || arbiter == beneficiary {
+        return Err(EscrowError::InvalidArbiter);
+    }
+    for party in [&depositor, &beneficiary, &arbiter] {
+        if is_blocked(e, party) {
+            return Err(EscrowError::Blocked);
+        }
+        if is_self_paused(e, party) {
+            return Err(EscrowError::Paused);
+        }
+    }
+    let config = load_config(e)?;
+    check_create_rate(e, &config, &depositor)?;
+    if !(config.min_duration..=config.max_duration).contains(&duration) {
+        return Err(EscrowError::InvalidDuration);
+    }
+    let review_period = review_period.unwrap_or(config.dispute_period);
+    if !(MIN_REVIEW_PERIOD..=MAX_REVIEW_PERIOD).contains(&review_period) {
+        return Err(EscrowError::InvalidDuration);
+    }
+    let resubmit_cooldown = resubmit_cooldown.unwrap_or(0);
+    let submission_cutoff = submission_cutoff.unwrap_or(0);
+    if resubmit_cooldown > MAX_RESUBMIT_COOLDOWN || submission_cutoff > MAX_SUBMISSION_CUTOFF {
+        return Err(EscrowError::InvalidDuration);
+    }
+    if milestone_amounts.is_empty() {
+        return Err(EscrowError::InvalidMilestone);
+    }
+    if matches!(mode, PayoutMode::Stream | PayoutMode::Vesting(_) | PayoutMode::Hourly(_))
+        && milestone_amounts.len() != 1
+    {
+        return Err(EscrowError::InvalidMilestone);
+    }
+    if let PayoutMode::Hourly(rate) = mode {
+        if rate <= 0 {
+            return Err(EscrowError::InvalidConfig);
+        }
+    }
+    if let PayoutMode::Subscription(period) | PayoutMode::Retainer(period) = mode {
+        let span = period.checked_mul(milestone_amounts.len() as u64);
+        if period == 0 || span.is_none_or(|span| span > duration) {
+            return Err(EscrowError::InvalidDuration);
+        }
+    }
+    if let PayoutMode::Vesting(schedule) = &mode {
+        check_vesting(schedule, sum_milestones(&milestone_amounts)?, duration)?;
+    }
+    if !checklists.is_empty() && checklists.len() != milestone_amounts.len() {
+        return Err(EscrowError::InvalidMilestone);
+    }
+    if !milestone_deadlines.is_empty() && milestone_deadlines.len() != milestone_amounts.len() {
+        return Err(EscrowError::InvalidMilestone);
+    }
+    if milestone_deadlines.iter().any(|offset| offset > duration) {
+        return Err(EscrowError::InvalidDeadline);
+    }
+    if penalty.max_bps > 10_000 || kill_fee_bps > 10_000 {
+        return Err(EscrowError::InvalidConfig);
+    }
+    if referral_bps > MAX_REFERRAL_BPS || (referral_bps > 0 && referrer.is_none()) {
+        return Err(EscrowError::InvalidConfig);
+    }
+    if let Some(referrer) = &referrer {
+        if referrer == &beneficiary {
+            return Err(EscrowError::InvalidConfig);
+        }
+        if is_blocked(e, referrer) {
+            return Err(EscrowError::Blocked);
+        }
+    }
+    if bonus < 0 {
+        return Err(EscrowError::ZeroAmount);
+    }
+    check_splits(e, &splits, &depositor, &arbiter)?;
+    if !milestone_recipients.is_empty() && milestone_recipients.len() != milestone_amounts.len() {
+        return Err(EscrowError::InvalidMilestone);
+    }
+    for recipient in milestone_recipients.iter().flatten() {
+        if recipient == depositor || recipient == arbiter {
+            return Err(EscrowError::InvalidBeneficiary);
+        }
+        if is_blocked(e, &recipient) {
+            return Err(EscrowError::Blocked);
+        }
+    }
+    if payout_tail.upfront_bps > 10_000
+        || (payout_tail.duration > 0 && mode != PayoutMode::Milestones)
+    {
+        return Err(EscrowError::InvalidConfig);
+    }
+    if funding == FundingMode::PerMilestone && mode != PayoutMode::Milestones {
+        return Err(EscrowError::WrongMode);
+    }
+    if let Some(project) = project {
+        if load_project(e, project)?.owner != depositor {
+            return Err(EscrowError::NotAuthorized);
+        }
+        if index_len(e, &(sym_by_project(), project)) >= MAX_PROJECT_ESCROWS {
+            return Err(EscrowError::InvalidConfig);
+        }
+    }
+
+    let total_amount = sum_milestones(&milestone_amounts)?;
+    if let Some(bounds) = load_amount_bounds(e, &token) {
+        check_amount_bounds(&bounds, &milestone_amounts, total_amount)?;
+    }
+    let premium = if insured { premium_for(&config, total_amount) } else { 0 };
+
+    let now = e.ledger().timestamp();
+    let deadline = now.checked_add(duration)
+        .ok_or(EscrowError::InvalidDeadline)?;
+
+    acquire_lock(e)?;
+
+    let id = peek_next_id(e)?;
+
+    let mut milestones = Vec::new(e);
+    for (i, amount) in milestone_amounts.iter().enumerate() {
+        let checklist = checklists.get(i as u32).unwrap_or(Vec::new(e));
+        let deadline = milestone_deadlines.get(i as u32).map(|offset| now + offset);
+        milestones.push_back(Milestone {
+            description: symbol_short!("milestone"),
+            amount,
+            status: MilestoneStatus::NotStarted,
+            submitted_at: None,
+            approved_at: None,
+            revision_requested_at: None,
+            rationale_hash: None,
+            appealed: false,
+            checklist_done: Vec::from_iter(e, checklist.iter().map(|_| false)),
+            checklist,
+            checklist_results: None,
+            deadline,
+            penalty_bps: 0,
+            recipient: milestone_recipients.get(i as u32).flatten(),
+        });
+    }
+
+    // Crowdfunded escrows are paid for later through `contribute`, pay-as-you-go ones
+    // through `fund_milestone`
+    let deposit = match funding {
+        FundingMode::Upfront => total_amount,
+        FundingMode::Crowdfunded => 0,
+        FundingMode::PerMilestone => milestone_amounts.get(0).unwrap(),
+    };
+    let mut contributions = Vec::new(e);
+    if funding == FundingMode::PerMilestone {
+        contributions.push_back((depositor.clone(), deposit));
+    }
+
+    let escrow = EscrowData {
+        depositor: depositor.clone(),
+        beneficiary: beneficiary.clone(),
+        arbiter: arbiter.clone(),
+        token: token.clone(),
+        total_amount,
+        paid_amount: 0,
+        deadline,
+        status: EscrowStatus::Pending,
+        milestones,
+        work_started: false,
+        resubmit_cooldown,
+        close_reason: None,
+        frozen_until: 0,
+        private_events,
+        governance,
+        review_period,
+        disputed_since: None,
+        deadline_extended: 0,
+        submission_cutoff,
+        reported: false,
+        fee_bps: config.fee_bps,
+        insured,
+        penalty,
+        bonus,
+        bonus_held: bonus,
+        kill_fee_bps,
+        referrer,
+        referral_bps,
+        marketplace,
+        marketplace_fee_bps,
+        mode,
+        started_at: 0,
+        claimed: 0,
+        splits,
+        funding,
+        contributions,
+        claim: if transferable { ClaimState::Transferable } else { ClaimState::Fixed },
+    };
+
+    if deposit > 0 {
+        let tf_res = safe_transfer(e, &token, &depositor, &e.current_contract_address(), &deposit);
+        if tf_res.is_err() {
+            release_lock(e);
+            return Err(EscrowError::TransferFailed);
+        }
+    }
+    if premium > 0 {
+        safe_transfer(e, &token, &depositor, &e.current_contract_address(), &premium)?;
+        add_to_insurance_pool(e, &token, premium);
+    }
+    if bonus > 0 {
+        safe_transfer(e, &token, &depositor, &e.current_contract_address(), &bonus)?;
+    }
+
+    store_escrow(e, id, &escrow, &depositor);
+    finalize_counter(e, id);
+    index_add(e, &(sym_by_depositor(), depositor.clone()), id);
+    if let Some(project) = project {
+        index_push(e, &(sym_by_project(), project), id);
+    }
+    if payout_tail.duration > 0 {
+        let key = tail_key(id);
+        e.storage().persistent().set(&key, &payout_tail);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+    }
+    index_add(e, &(sym_by_arbiter(), arbiter.clone()), id);
+    // A token's escrows never leave its index, so it is append-only and keeps no positions
+    index_push(e, &(sym_by_token(), token.clone()), id);
+
+    if private_events {
+        EscrowCreatedMinimal { id, insured, seq: next_event_seq(e) }.publish(e);
+    } else {
+        EscrowCreated {
+            id,
+            depositor: depositor.clone(),
+            beneficiary: beneficiary.clone(),
+            arbiter,
+            token,
+            amount: total_amount,
+            milestone_amounts,
+            deadline,
+            insured,
+            seq: next_event_seq(e),
+        }
+        .publish(e);
+    }
+
+    release_lock(e);
+    log_action(e, id, &depositor, "create", None);
+    Ok(id)
+}
+
+#[contractimpl]
+impl EscrowContract {
+    /// Create escrow with milestones, pulling the full amount from the depositor
+    pub fn create(e: Env, params: CreateParams) -> Result<u32, EscrowError> {
+        ensure_not_paused(&e)?;
+        create_escrow(&e, params, None)
+    }
+
+    /// Opens a project to group the owner's escrows under. Projects have their own id sequence.
+    pub fn create_project(
+        e: Env,
+        owner: Address,
+        name_hash: BytesN<32>,
+    ) -> Result<u32, EscrowError> {
+        ensure_not_paused(&e)?;
+        owner.require_auth();
+
+        if is_blocked(&e, &owner) {
+            return Err(EscrowError::Blocked);
+        }
+        let id = e
+            .storage()
+            .instance()
+            .get::<_, u32>(&sym_project_counter())
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or(EscrowError::CounterOverflow)?;
+        e.storage().instance().set(&sym_project_counter(), &id);
+
+        let key = project_key(id);
+        let project = Project {
+            owner: owner.clone(),
+            name_hash: name_hash.clone(),
+            created_at: e.ledger().timestamp(),
+        };
+        e.storage().persistent().set(&key, &project);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        ProjectCreated {
+            id,
+            owner,
+            name_hash,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        Ok(id)
+    }
+
+    pub fn get_project(e: Env, id: u32) -> Option<Project> {
+        e.storage().persistent().get(&project_key(id))
+    }
+
+    /// Ids of the escrows grouped under a project, in creation order
+    pub fn get_project_escrows(e: Env, id: u32, offset: u32, limit: u32) -> Vec<u32> {
+        index_page(&e, &(sym_by_project(), id), offset, limit)
+    }
+
+    /// Status counts and amounts across every escrow in a project
+    pub fn get_project_summary(e: Env, id: u32) -> Result<ProjectSummary, EscrowError> {
+        load_project(&e, id)?;
+        let mut summary = ProjectSummary::default();
+        for escrow_id in index_page(&e, &(sym_by_project(), id), 0, MAX_PROJECT_ESCROWS).iter() {
+            let Ok(escrow) = load_escrow(&e, escrow_id) else {
+                continue;
+            };
+            summary.escrows += 1;
+            summary.total_amount += escrow.total_amount;
+            summary.paid_amount += escrow.paid_amount;
+            match escrow.status {
+                EscrowStatus::Released => summary.completed += 1,
+                EscrowStatus::Refunded => summary.refunded += 1,
+                EscrowStatus::Disputed => {
+                    summary.active += 1;
+                    summary.disputed += 1;
+                }
+                EscrowStatus::Pending | EscrowStatus::InProgress => summary.active += 1,
+            }
+        }
+        Ok(summary)
+    }
+
+    /// Create escrow with milestones sized in basis points of `total`, summing to 10_000.
+    /// `params.milestone_amounts` is replaced by the computed amounts.
+    pub fn create_percent(
+        e: Env,
+        params: CreateParams,
+        total: i128,
+        shares: Vec<u32>,
+    ) -> Result<u32, EscrowError> {
+        ensure_not_paused(&e)?;
+        let milestone_amounts = percent_amounts(&e, total, &shares)?;
+        create_escrow(&e, CreateParams { milestone_amounts, ..params }, None)
+    }
+
+    /// Create escrow on behalf of a registered marketplace, which takes its fee from each payout
+    pub fn create_for_marketplace(
+        e: Env,
+        marketplace: Address,
+        params: CreateParams,
+    ) -> Result<u32, EscrowError> {
+        ensure_not_paused(&e)?;
+        create_escrow(&e, params, Some(marketplace))
+    }
+
+    /// Insurance premium `create` would charge on top of the deposit if `params.insured` were set
+    pub fn quote_premium(e: Env, params: CreateParams) -> Result<i128, EscrowError> {
+        let config = load_config(&e)?;
+        let total_amount = sum_milestones(&params.milestone_amounts)?;
+        Ok(premium_for(&config, total_amount))
+    }
+
+    /// Premiums collected per token from insured escrows
+    pub fn get_insurance_pool(e: Env, token: Address) -> i128 {
+        load_insurance_pool(&e, &token)
+    }
+
+    /// Admin compensates a party of an insured escrow who lost funds to a bad ruling, paid from
+    /// the escrow token's insurance pool. Each escrow can be claimed against once.
+    pub fn pay_insurance_claim(
+        e: Env,
+        id: u32,
+        claimant: Address,
+        amount: i128,
+    ) -> Result<(), EscrowError> {
+        require_admin(&e)?;
+
+        let escrow = load_escrow(&e, id)?;
+        let role = resolve_role(&e, id, &claimant, &escrow);
+        if !escrow.insured || (role != Role::Depositor && role != Role::Beneficiary) {
+            return Err(EscrowError::NotAuthorized);
+        }
+        // Only rulings are covered, so there must have been one
+        if !has_ruling(&e, id, &escrow) {
+            return Err(EscrowError::NotAuthorized);
+        }
+        if amount <= 0 || amount > escrow.total_amount {
+            return Err(EscrowError::ZeroAmount);
+        }
+        if amount > load_insurance_pool(&e, &escrow.token) {
+            return Err(EscrowError::InsufficientPool);
+        }
+
+        let key = (sym_claims(), id);
+        if e.storage().persistent().has(&key) {
+            return Err(EscrowError::AlreadyClaimed);
+        }
+        e.storage().persistent().set(&key, &amount);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        add_to_insurance_pool(&e, &escrow.token, -amount);
+        safe_transfer(&e, &escrow.token, &e.current_contract_address(), &claimant, &amount)?;
+
+        InsuranceClaimPaid {
+            id,
+            claimant,
+            amount,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    /// Sets the platform admin and configuration as part of deployment, so no one can claim
+    /// the admin between deploying and a separate setup call
+    pub fn __constructor(e: Env, admin: Address, config: Config) -> Result<(), EscrowError> {
+        store_config(&e, &config)?;
+        e.storage().instance().set(&sym_admin(), &admin);
+        e.storage().instance().set(&sym_schema(), &SCHEMA_VERSION);
+        store_version(&e);
+
+        ConfigUpdated { config, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    pub fn get_config(e: Env) -> Result<Config, EscrowError> {
+        load_config(&e)
+    }
+
+    /// Admin queues a sensitive action; it can be executed once the configured delay has passed,
+    /// giving users time to exit. Config changes never alter terms of existing escrows.
+    pub fn propose_action(e: Env, action: AdminAction) -> Result<u32, EscrowError> {
+        require_admin(&e)?;
+        validate_action(&e, &action)?;
+
+        let config = load_config(&e)?;
+        let eta = e.ledger().timestamp().saturating_add(config.timelock_delay);
+
+        let action_id = e
+            .storage()
+            .instance()
+            .get::<_, u32>(&sym_action_counter())
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or(EscrowError::CounterOverflow)?;
+        e.storage().instance().set(&sym_action_counter(), &action_id);
+
+        let key = action_key(action_id);
+        let pending = PendingAction {
+            action: action.clone(),
+            eta,
+        };
+        e.storage().persistent().set(&key, &pending);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        AdminActionProposed {
+            action_id,
+            action,
+            eta,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        Ok(action_id)
+    }
+
+    pub fn execute_action(e: Env, action_id: u32) -> Result<(), EscrowError> {
+        require_admin(&e)?;
+
+        let key = action_key(action_id);
+        let pending: PendingAction = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(EscrowError::ActionNotFound)?;
+
+        if e.ledger().timestamp() < pending.eta {
+            return Err(EscrowError::TimelockActive);
+        }
+
+        e.storage().persistent().remove(&key);
+        apply_action(&e, pending.action)?;
+
+        AdminActionExecuted { action_id, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    pub fn cancel_action(e: Env, action_id: u32) -> Result<(), EscrowError> {
+        require_admin(&e)?;
+
+        let key = action_key(action_id);
+        if !e.storage().persistent().has(&key) {
+            return Err(EscrowError::ActionNotFound);
+        }
+        e.storage().persistent().remove(&key);
+
+        AdminActionCancelled { action_id, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    /// Admin queues a wasm upgrade behind the timelock; shorthand for proposing `AdminAction::Upgrade`
+    pub fn upgrade(e: Env, new_wasm_hash: BytesN<32>) -> Result<u32, EscrowError> {
+        Self::propose_action(e, AdminAction::Upgrade(new_wasm_hash))
+    }
+
+    /// Admin queues replacing the arbiter of a dispute left unresolved for 60 days; shorthand
+    /// for proposing `AdminAction::ReassignArbiter`
+    pub fn reassign_arbiter(e: Env, id: u32, new_arbiter: Address) -> Result<u32, EscrowError> {
+        Self::propose_action(e, AdminAction::ReassignArbiter(id, new_arbiter))
+    }
+
+    /// Admin rewrites the given escrows in the current storage layout after an upgrade.
+    /// A schema change adds its conversion to `load_any_schema`.
+    pub fn migrate(e: Env, ids: Vec<u32>) -> Result<u32, EscrowError> {
+        let admin = require_admin(&e)?;
+
+        let stored: u32 = e.storage().instance().get(&sym_schema()).unwrap_or(1);
+        if stored > SCHEMA_VERSION {
+            return Err(EscrowError::InvalidConfig);
+        }
+
+        for id in ids.iter() {
+            let (escrow, converted) = load_any_schema(&e, id)?;
+            if !converted {
+                store_escrow(&e, id, &escrow, &admin);
+                continue;
+            }
+            // Schema 1 escrows predate the locked totals, stats, status copy and indexes, so
+            // the old record is dropped and `store_escrow` accounts for it like a new one
+            e.storage().persistent().remove(&escrow_key(id));
+            store_escrow(&e, id, &escrow, &admin);
+            index_add(&e, &(sym_by_depositor(), escrow.depositor.clone()), id);
+            if escrow.work_started {
+                index_add(&e, &(sym_by_beneficiary(), escrow.beneficiary.clone()), id);
+            }
+            index_add(&e, &(sym_by_arbiter(), escrow.arbiter.clone()), id);
+            index_push(&e, &(sym_by_token(), escrow.token.clone()), id);
+        }
+
+        e.storage().instance().set(&sym_schema(), &SCHEMA_VERSION);
+        store_version(&e);
+        Ok(SCHEMA_VERSION)
+    }
+
+    /// Version and build tag recorded at deployment, refreshed by `migrate` after an upgrade.
+    /// The version packs major * 10_000 + minor * 100 + patch.
+    pub fn version(e: Env) -> (u32, Symbol) {
+        e.storage()
+            .instance()
+            .get(&sym_version())
+            .unwrap_or_else(|| (CONTRACT_VERSION, Symbol::new(&e, BUILD_TAG)))
+    }
+
+    pub fn get_schema_version(e: Env) -> u32 {
+        e.storage().instance().get(&sym_schema()).unwrap_or(1)
+    }
+
+    pub fn get_pending_action(e: Env, action_id: u32) -> Option<PendingAction> {
+        e.storage().persistent().get(&action_key(action_id))
+    }
+
+    pub fn get_admin(e: Env) -> Result<Address, EscrowError> {
+        load_admin(&e)
+    }
+
+    /// Admin grants `addr` a reduced (or zero) fee rate, applied to payouts on escrows it is party to
+    pub fn set_fee_tier(e: Env, addr: Address, fee_bps: u32) -> Result<(), EscrowError> {
+        require_admin(&e)?;
+
+        if fee_bps > MAX_FEE_BPS {
+            return Err(EscrowError::InvalidConfig);
+        }
+
+        let key = fee_tier_key(&addr);
+        e.storage().persistent().set(&key, &fee_bps);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        FeeTierSet { addr, fee_bps, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    /// Admin sends accrued platform fees in `token` to the configured fee recipient
+    pub fn withdraw_fees(e: Env, token: Address, amount: i128) -> Result<(), EscrowError> {
+        require_admin(&e)?;
+
+        if amount <= 0 {
+            return Err(EscrowError::ZeroAmount);
+        }
+        let accrued = load_accrued_fees(&e, &token);
+        if amount > accrued {
+            return Err(EscrowError::InsufficientFees);
+        }
+
+        let key = (sym_treasury(), token.clone());
+        e.storage().persistent().set(&key, &(accrued - amount));
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        let to = load_config(&e)?.fee_recipient;
+        safe_transfer(&e, &token, &e.current_contract_address(), &to, &amount)?;
+
+        FeesWithdrawn {
+            token,
+            amount,
+            to,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    /// Admin sweeps `token` sent to the contract by mistake. Only the balance above what the
+    /// contract owes can be moved, so escrowed funds are never touched.
+    pub fn rescue_excess(e: Env, token: Address, to: Address) -> Result<i128, EscrowError> {
+        require_admin(&e)?;
+
+        let excess = Self::verify_solvency(e.clone(), token.clone());
+        if excess <= 0 {
+            return Err(EscrowError::ZeroAmount);
+        }
+        safe_transfer(&e, &token, &e.current_contract_address(), &to, &excess)?;
+
+        ExcessRescued {
+            token,
+            to,
+            amount: excess,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        Ok(excess)
+    }
+
+    /// Contract balance of `token` minus everything it owes in that token. A negative result
+    /// means the books don't reconcile and also raises a `SolvencyAlert`.
+    pub fn verify_solvency(e: Env, token: Address) -> i128 {
+        let balance = token::Client::new(&e, &token).balance(&e.current_contract_address());
+        let surplus = balance - token_liabilities(&e, &token);
+        if surplus < 0 {
+            SolvencyAlert {
+                token,
+                shortfall: -surplus,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+        surplus
+    }
+
+    pub fn get_user_stats(e: Env, addr: Address) -> UserStats {
+        load_user_stats(&e, &addr)
+    }
+
+    pub fn get_stats(e: Env) -> PlatformStats {
+        load_stats(&e)
+    }
+
+    /// Total value ever escrowed in `token`, including top-ups
+    pub fn get_volume(e: Env, token: Address) -> i128 {
+        load_volume(&e, &token)
+    }
+
+    /// Funds in `token` still held for the parties of all escrows
+    pub fn get_total_locked(e: Env, token: Address) -> i128 {
+        load_total_locked(&e, &token)
+    }
+
+    /// Platform fees accrued in `token` and not yet withdrawn
+    pub fn get_accrued_fees(e: Env, token: Address) -> i128 {
+        load_accrued_fees(&e, &token)
+    }
+
+    /// Admin lets `marketplace` create escrows through `create_for_marketplace`
+    pub fn register_marketplace(e: Env, marketplace: Address) -> Result<(), EscrowError> {
+        require_admin(&e)?;
+
+        let key = marketplace_key(&marketplace);
+        if !e.storage().persistent().has(&key) {
+            e.storage().persistent().set(&key, &0u32);
+        }
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        MarketplaceRegistered { marketplace, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    /// Admin delists `marketplace`; escrows it already created keep their fee
+    pub fn remove_marketplace(e: Env, marketplace: Address) -> Result<(), EscrowError> {
+        require_admin(&e)?;
+
+        let key = marketplace_key(&marketplace);
+        if !e.storage().persistent().has(&key) {
+            return Err(EscrowError::MarketplaceNotFound);
+        }
+        e.storage().persistent().remove(&key);
+
+        MarketplaceRemoved { marketplace, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    /// Registered marketplace sets the fee it charges on escrows created from now on
+    pub fn set_marketplace_fee(e: Env, marketplace: Address, fee_bps: u32) -> Result<(), EscrowError> {
+        marketplace.require_auth();
+
+        let key = marketplace_key(&marketplace);
+        if !e.storage().persistent().has(&key) {
+            return Err(EscrowError::MarketplaceNotFound);
+        }
+        if fee_bps > MAX_MARKETPLACE_FEE_BPS {
+            return Err(EscrowError::InvalidConfig);
+        }
+        e.storage().persistent().set(&key, &fee_bps);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        MarketplaceFeeSet { marketplace, fee_bps, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    /// Fee `marketplace` currently charges, or `None` if it is not registered
+    pub fn get_marketplace_fee(e: Env, marketplace: Address) -> Option<u32> {
+        load_marketplace_fee(&e, &marketplace)
+    }
+
+    /// Admin limits milestone and escrow sizes for `token`, keeping out dust and oversized escrows
+    pub fn set_amount_bounds(e: Env, token: Address, bounds: AmountBounds) -> Result<(), EscrowError> {
+        require_admin(&e)?;
+
+        if bounds.min_milestone <= 0
+            || bounds.min_milestone > bounds.max_milestone
+            || bounds.min_total <= 0
+            || bounds.min_total > bounds.max_total
+        {
+            return Err(EscrowError::InvalidConfig);
+        }
+
+        let key = (sym_amount_bounds(), token.clone());
+        e.storage().persistent().set(&key, &bounds);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        AmountBoundsSet { token, bounds, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    pub fn get_amount_bounds(e: Env, token: Address) -> Option<AmountBounds> {
+        load_amount_bounds(&e, &token)
+    }
+
+    /// Admin bars `addr` from new escrows, starting work and receiving payouts
+    pub fn block_address(e: Env, addr: Address) -> Result<(), EscrowError> {
+        require_admin(&e)?;
+
+        let key = (sym_blocklist(), addr.clone());
+        e.storage().persistent().set(&key, &true);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        AddressBlocked {
+            addr,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    pub fn unblock_address(e: Env, addr: Address) -> Result<(), EscrowError> {
+        require_admin(&e)?;
+
+        e.storage().persistent().remove(&(sym_blocklist(), addr.clone()));
+
+        AddressUnblocked {
+            addr,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    pub fn is_blocked(e: Env, addr: Address) -> bool {
+        is_blocked(&e, &addr)
+    }
+
+    /// Admin returns `addr` to the standard fee rate
+    pub fn remove_fee_tier(e: Env, addr: Address) -> Result<(), EscrowError> {
+        require_admin(&e)?;
+
+        e.storage().persistent().remove(&fee_tier_key(&addr));
+
+        FeeTierRemoved { addr, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    pub fn get_fee_tier(e: Env, addr: Address) -> Option<u32> {
+        e.storage().persistent().get(&fee_tier_key(&addr))
+    }
+
+    /// Admin halts every state-changing entrypoint except refunds of pending escrows
+    pub fn pause(e: Env) -> Result<(), EscrowError> {
+        let admin = require_admin(&e)?;
+        e.storage().instance().set(&sym_paused(), &true);
+
+        ContractPaused { admin, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    pub fn unpause(e: Env) -> Result<(), EscrowError> {
+        let admin = require_admin(&e)?;
+        e.storage().instance().set(&sym_paused(), &false);
+
+        ContractUnpaused { admin, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    pub fn is_paused(e: Env) -> bool {
+        is_paused(&e)
+    }
+
+    /// Admin nominates a successor, who must call `accept_admin` to take over
+    pub fn transfer_admin(e: Env, new_admin: Address) -> Result<(), EscrowError> {
+        let admin = require_admin(&e)?;
+        e.storage().instance().set(&sym_pending_admin(), &new_admin);
+
+        AdminTransferProposed { admin, new_admin, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    /// Nominated admin completes the handover started by `transfer_admin`
+    pub fn accept_admin(e: Env) -> Result<(), EscrowError> {
+        let pending: Address = e
+            .storage()
+            .instance()
+            .get(&sym_pending_admin())
+            .ok_or(EscrowError::NotAuthorized)?;
+        pending.require_auth();
+
+        let old_admin = load_admin(&e)?;
+        e.storage().instance().set(&sym_admin(), &pending);
+        e.storage().instance().remove(&sym_pending_admin());
+
+        AdminTransferred {
+            old_admin,
+            new_admin: pending,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        Ok(())
+    }
+
+    /// Address suspends its own participation, e.g. on suspected key compromise: no new escrow
+    /// can name it as a party. Allowed while the contract is paused since it only restricts.
+    pub fn self_pause(e: Env, caller: Address) -> Result<(), EscrowError> {
+        caller.require_auth();
+
+        let key = self_pause_key(&caller);
+        e.storage().persistent().set(&key, &0u64);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        AddressPaused { addr: caller, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    /// Starts the timelock after which `self_unpause` lifts the address's pause
+    pub fn request_self_unpause(e: Env, caller: Address) -> Result<u64, EscrowError> {
+        caller.require_auth();
+
+        if !is_self_paused(&e, &caller) {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        let unpause_at = e.ledger().timestamp().saturating_add(SELF_UNPAUSE_DELAY);
+        let key = self_pause_key(&caller);
+        e.storage().persistent().set(&key, &unpause_at);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        AddressUnpauseRequested {
+            addr: caller,
+            unpause_at,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        Ok(unpause_at)
+    }
+
+    pub fn self_unpause(e: Env, caller: Address) -> Result<(), EscrowError> {
+        caller.require_auth();
+
+        let unpause_at = self_pause_state(&e, &caller).ok_or(EscrowError::NotAuthorized)?;
+        if unpause_at == 0 || e.ledger().timestamp() < unpause_at {
+            return Err(EscrowError::TimelockActive);
+        }
+
+        e.storage().persistent().remove(&self_pause_key(&caller));
+
+        AddressUnpaused { addr: caller, seq: next_event_seq(&e) }.publish(&e);
+        Ok(())
+    }
+
+    pub fn is_self_paused(e: Env, addr: Address) -> bool {
+        is_self_paused(&e, &addr)
+    }
+
+    /// Arbiter opts into the pool used when `create` is called without an arbiter
+    pub fn register_arbiter(e: Env, arbiter: Address) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        arbiter.require_auth();
+
+        let mut pool = load_arbiter_pool(&e);
+        if !pool.contains(&arbiter) {
+            pool.push_back(arbiter.clone());
+            store_arbiter_pool(&e, &pool);
+            ArbiterRegistered { arbiter, seq: next_event_seq(&e) }.publish(&e);
+        }
+        Ok(())
+    }
+
+    /// Arbiter leaves the pool (existing assignments are unaffected)
+    pub fn unregister_arbiter(e: Env, arbiter: Address) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        arbiter.require_auth();
+
+        let mut pool = load_arbiter_pool(&e);
+        if let Some(index) = pool.first_index_of(&arbiter) {
+            pool.remove(index);
+            store_arbiter_pool(&e, &pool);
+            ArbiterUnregistered { arbiter, seq: next_event_seq(&e) }.publish(&e);
+        }
+        Ok(())
+    }
+
+    /// Depositor blocks submissions in the final `cutoff` secs before the deadline; 0 disables
+    pub fn set_submission_cutoff(
+        e: Env,
+        caller: Address,
+        id: u32,
+        cutoff: u64,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if escrow.work_started {
+            return Err(EscrowError::WorkStarted);
+        }
+
+        if escrow.status != EscrowStatus::Pending {
+            return Err(EscrowError::AlreadyCompleted);
+        }
+
+        if cutoff > MAX_SUBMISSION_CUTOFF {
+            return Err(EscrowError::InvalidDuration);
+        }
+
+        escrow.submission_cutoff = cutoff;
+        store_escrow(&e, id, &escrow, &caller);
+        log_action(&e, id, &caller, "set_submission_cutoff", None);
+        Ok(())
+    }
+
+    /// Depositor sets the resubmission cooldown; terms are fixed once work starts
+    pub fn set_resubmit_cooldown(
+        e: Env,
+        caller: Address,
+        id: u32,
+        cooldown: u64,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if escrow.work_started {
+            return Err(EscrowError::WorkStarted);
+        }
+
+        if escrow.status != EscrowStatus::Pending {
+            return Err(EscrowError::AlreadyCompleted);
+        }
+
+        if cooldown > MAX_RESUBMIT_COOLDOWN {
+            return Err(EscrowError::InvalidDuration);
+        }
+
+        escrow.resubmit_cooldown = cooldown;
+        store_escrow(&e, id, &escrow, &caller);
+        log_action(&e, id, &caller, "set_resubmit_cooldown", None);
+        Ok(())
+    }
+
+    /// Depositor sets the client review window; fixed once work starts
+    pub fn set_review_period(
+        e: Env,
+        caller: Address,
+        id: u32,
+        review_period: u64,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if escrow.work_started {
+            return Err(EscrowError::WorkStarted);
+        }
+
+        if escrow.status != EscrowStatus::Pending {
+            return Err(EscrowError::AlreadyCompleted);
+        }
+
+        if !(MIN_REVIEW_PERIOD..=MAX_REVIEW_PERIOD).contains(&review_period) {
+            return Err(EscrowError::InvalidDuration);
+        }
+
+        escrow.review_period = review_period;
+        store_escrow(&e, id, &escrow, &caller);
+        log_action(&e, id, &caller, "set_review_period", None);
+        Ok(())
+    }
+
+    /// Depositor names the governance contract appeals escalate to; fixed once work starts
+    pub fn set_governance(
+        e: Env,
+        caller: Address,
+        id: u32,
+        governance: Address,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if escrow.work_started {
+            return Err(EscrowError::WorkStarted);
+        }
+
+        if escrow.status != EscrowStatus::Pending {
+            return Err(EscrowError::AlreadyCompleted);
+        }
+
+        escrow.governance = Some(governance);
+        store_escrow(&e, id, &escrow, &caller);
+        log_action(&e, id, &caller, "set_governance", None);
+        Ok(())
+    }
+
+    /// Beneficiary marks work as started (blocks refunds)
+    pub fn start_work(e: Env, caller: Address, id: u32) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Beneficiary {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if escrow.work_started {
+            release_lock(&e);
+            return Err(EscrowError::WorkStarted);
+        }
+
+        if escrow.status != EscrowStatus::Pending {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+
+        if is_blocked(&e, &escrow.depositor) || is_blocked(&e, &escrow.beneficiary) {
+            release_lock(&e);
+            return Err(EscrowError::Blocked);
+        }
+
+        if escrow.funding == FundingMode::Crowdfunded && unfunded_amount(&escrow) > 0 {
+            release_lock(&e);
+            return Err(EscrowError::NotFunded);
+        }
+
+        let now = e.ledger().timestamp();
+        escrow.work_started = true;
+        escrow.started_at = now;
+        escrow.status = EscrowStatus::InProgress;
+        store_escrow(&e, id, &escrow, &caller);
+        // Listed only once the beneficiary takes the job, so no one can fill another
+        // address's list with escrows it never agreed to
+        index_add(&e, &(sym_by_beneficiary(), caller.clone()), id);
+
+        WorkStarted {
+            id,
+            started_at: now,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "start_work", None);
+        Ok(())
+    }
+
+    /// Beneficiary marks a checklist item of an unsubmitted milestone done or not done, so the
+    /// client can follow progress between submissions
+    pub fn mark_checklist_item(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+        item: u32,
+        done: bool,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Beneficiary {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if escrow.status != EscrowStatus::InProgress {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        let Some(mut milestone) = escrow.milestones.get(milestone_index) else {
+            release_lock(&e);
+            return Err(EscrowError::InvalidMilestone);
+        };
+        if milestone.status != MilestoneStatus::NotStarted {
+            release_lock(&e);
+            return Err(EscrowError::MilestoneAlreadySubmitted);
+        }
+        if item >= milestone.checklist_done.len() {
+            release_lock(&e);
+            return Err(EscrowError::InvalidMilestone);
+        }
+
+        milestone.checklist_done.set(item, done);
+        escrow.milestones.set(milestone_index, milestone);
+        store_escrow(&e, id, &escrow, &caller);
+
+        ChecklistItemMarked {
+            id,
+            milestone_index,
+            item,
+            done,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "mark_checklist_item", Some(milestone_index));
+        Ok(())
+    }
+
+    /// Beneficiary submits milestone for review (no payment yet)
+    pub fn submit_milestone(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if let Err(err) = check_submit(&e, id, &escrow, &caller, milestone_index) {
+            release_lock(&e);
+            return Err(err);
+        }
+
+        let now = e.ledger().timestamp();
+        let mut milestone = escrow.milestones.get(milestone_index).unwrap();
+        milestone.status = MilestoneStatus::Submitted;
+        milestone.submitted_at = Some(now);
+        milestone.penalty_bps = late_penalty_bps(&escrow, &milestone, now);
+        escrow.milestones.set(milestone_index, milestone);
+
+        store_escrow(&e, id, &escrow, &caller);
+
+        MilestoneSubmitted {
+            id,
+            milestone_index,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "submit_milestone", Some(milestone_index));
+        Ok(())
+    }
+
+    /// Client or its delegate sends a submitted milestone back for rework (no payment)
+    pub fn request_revision(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if !matches!(resolve_role(&e, id, &caller, &escrow), Role::Depositor | Role::Delegate) {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if milestone_index >= escrow.milestones.len() {
+            release_lock(&e);
+            return Err(EscrowError::InvalidMilestone);
+        }
+
+        let mut milestone = escrow.milestones.get(milestone_index).unwrap();
+
+        if milestone.status != MilestoneStatus::Submitted {
+            release_lock(&e);
+            return Err(EscrowError::MilestoneNotSubmitted);
+        }
+
+        milestone.status = MilestoneStatus::NotStarted;
+        milestone.submitted_at = None;
+        milestone.revision_requested_at = Some(e.ledger().timestamp());
+        escrow.milestones.set(milestone_index, milestone);
+
+        store_escrow(&e, id, &escrow, &caller);
+
+        RevisionRequested {
+            id,
+            milestone_index,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "request_revision", Some(milestone_index));
+        Ok(())
+    }
+
+    /// Client or its delegate approves milestone (triggers payment). The client alone may add
+    /// a tip, paid straight from their own balance.
+    pub fn approve_milestone(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+        tip: Option<i128>,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        if tip.is_some_and(|tip| tip <= 0) {
+            return Err(EscrowError::ZeroAmount);
+        }
+        approve(&e, caller, id, milestone_index, None, tip.unwrap_or(0))
+    }
+
+    /// Client or its delegate approves milestone and records which checklist items passed, one
+    /// flag per item
+    pub fn approve_milestone_with_results(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+        results: Vec<bool>,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        approve(&e, caller, id, milestone_index, Some(results), 0)
+    }
+
+    /// Client releases every milestone still open in one call, submitted or not, closing the
+    /// escrow; `reason` anchors why. Returns the amount released.
+    pub fn release_all(
+        e: Env,
+        caller: Address,
+        id: u32,
+        reason: Option<BytesN<32>>,
+    ) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if escrow.mode != PayoutMode::Milestones {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
+        }
+        // An open dispute is settled through arbitration, not released around
+        if escrow.status != EscrowStatus::InProgress {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if is_frozen(&e, &escrow) {
+            release_lock(&e);
+            return Err(EscrowError::Frozen);
+        }
+        if unfunded_amount(&escrow) > 0 {
+            release_lock(&e);
+            return Err(EscrowError::NotFunded);
+        }
+
+        let paid_before = escrow.paid_amount;
+        escrow.close_reason = reason.clone();
+        for index in unapproved_milestones(&e, &escrow).iter() {
+            let milestone = escrow.milestones.get(index).unwrap();
+            let released = release_milestone(&e, id, escrow, index, milestone, 0, &caller);
+            escrow = match released.and_then(|_| load_escrow(&e, id)) {
+                Ok(escrow) => escrow,
+                Err(err) => {
+                    release_lock(&e);
+                    return Err(err);
+                }
+            };
+        }
+
+        release_lock(&e);
+        log_action_with_reason(&e, id, &caller, "release_all", None, reason);
+        Ok(escrow.paid_amount - paid_before)
+    }
+
+    /// Beneficiary claims a submitted milestone the client left unreviewed past the review period
+    pub fn claim_milestone(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Beneficiary {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if is_frozen(&e, &escrow) {
+            release_lock(&e);
+            return Err(EscrowError::Frozen);
+        }
+
+        if milestone_index >= escrow.milestones.len() {
+            release_lock(&e);
+            return Err(EscrowError::InvalidMilestone);
+        }
+
+        let milestone = escrow.milestones.get(milestone_index).unwrap();
+
+        if milestone.status != MilestoneStatus::Submitted {
+            release_lock(&e);
+            return Err(EscrowError::MilestoneNotSubmitted);
+        }
+
+        let submitted_at = milestone.submitted_at.unwrap_or(0);
+        if e.ledger().timestamp() < submitted_at.saturating_add(escrow.review_period) {
+            release_lock(&e);
+            return Err(EscrowError::DisputePeriodActive);
+        }
+
+        let res = release_milestone(&e, id, escrow, milestone_index, milestone, 0, &caller);
+        release_lock(&e);
+        log_action(&e, id, &caller, "claim_milestone", Some(milestone_index));
+        res
+    }
+
+    /// Client disputes milestone quality
+    pub fn dispute_milestone(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if milestone_index >= escrow.milestones.len() {
+            release_lock(&e);
+            return Err(EscrowError::InvalidMilestone);
+        }
+
+        let mut milestone = escrow.milestones.get(milestone_index).unwrap();
+        
+        // A running stream or vesting schedule can be stopped at any time, but only through
+        // arbitration. A retainer period is disputed before it is released.
+        let scheduled = matches!(
+            escrow.mode,
+            PayoutMode::Stream | PayoutMode::Vesting(_) | PayoutMode::Retainer(_)
+        )
+            && escrow.status == EscrowStatus::InProgress
+            && milestone.status == MilestoneStatus::NotStarted;
+        if milestone.status != MilestoneStatus::Submitted && !scheduled {
+            release_lock(&e);
+            return Err(EscrowError::MilestoneNotSubmitted);
+        }
+
+        let amount = milestone.amount;
+        milestone.status = MilestoneStatus::Disputed;
+        escrow.milestones.set(milestone_index, milestone);
+        escrow.status = EscrowStatus::Disputed;
+        if escrow.disputed_since.is_none() {
+            escrow.disputed_since = Some(e.ledger().timestamp());
+        }
+
+        store_escrow(&e, id, &escrow, &caller);
+        record_dispute(&e, id, milestone_index, DisputeAction::Opened, &caller, amount);
+        update_dispute_stats(&e, &caller, |s| s.opened += 1);
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "dispute_milestone", Some(milestone_index));
+        Ok(())
+    }
+
+    /// Arbiter resolves disputed milestone, anchoring the written ruling by hash
+    pub fn resolve_milestone_dispute(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+        pay_to_beneficiary: i128,
+        rationale_hash: BytesN<32>,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Arbiter {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if is_appealed(&escrow, milestone_index) {
+            release_lock(&e);
+            return Err(EscrowError::AppealPending);
+        }
+
+        let refund = match apply_ruling(
+            &e,
+            id,
+            &mut escrow,
+            milestone_index,
+            pay_to_beneficiary,
+            Some(rationale_hash.clone()),
+        ) {
+            Ok(refund) => refund,
+            Err(err) => {
+                release_lock(&e);
+                return Err(err);
+            }
+        };
+
+        pay_ruling(&e, id, &escrow, pay_to_beneficiary, refund)?;
+        escrow.paid_amount += pay_to_beneficiary;
+        refresh_status(&mut escrow, e.ledger().timestamp());
+
+        store_escrow(&e, id, &escrow, &caller);
+        record_dispute(
+            &e,
+            id,
+            milestone_index,
+            DisputeAction::Resolved,
+            &caller,
+            pay_to_beneficiary,
+        );
+        record_ruling_stats(&e, &escrow, pay_to_beneficiary, refund, Some(&caller));
+        publish_resolution(&e, &escrow, id, milestone_index, pay_to_beneficiary, &rationale_hash);
+        if escrow.status == EscrowStatus::Released {
+            complete_escrow(&e, id, &escrow, &caller)?;
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "resolve_milestone_dispute", Some(milestone_index));
+        Ok(())
+    }
+
+    /// Arbiter settles several disputed milestones atomically with aggregated transfers;
+    /// one rationale document covers the whole batch
+    pub fn resolve_multiple(
+        e: Env,
+        caller: Address,
+        id: u32,
+        rulings: Vec<(u32, i128)>,
+        rationale_hash: BytesN<32>,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Arbiter {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if rulings.is_empty() {
+            release_lock(&e);
+            return Err(EscrowError::InvalidMilestone);
+        }
+
+        let mut total_pay: i128 = 0;
+        let mut total_refund: i128 = 0;
+        for (milestone_index, pay_to_beneficiary) in rulings.iter() {
+            if is_appealed(&escrow, milestone_index) {
+                release_lock(&e);
+                return Err(EscrowError::AppealPending);
+            }
+
+            // A repeated index fails here because the first ruling already closed it
+            match apply_ruling(
+                &e,
+                id,
+                &mut escrow,
+                milestone_index,
+                pay_to_beneficiary,
+                Some(rationale_hash.clone()),
+            ) {
+                Ok(refund) => {
+                    total_pay += pay_to_beneficiary;
+                    total_refund += refund;
+                    record_ruling_stats(&e, &escrow, pay_to_beneficiary, refund, Some(&caller));
+                    record_dispute(
+                        &e,
+                        id,
+                        milestone_index,
+                        DisputeAction::Resolved,
+                        &caller,
+                        pay_to_beneficiary,
+                    );
+                    publish_resolution(
+                        &e,
+                        &escrow,
+                        id,
+                        milestone_index,
+                        pay_to_beneficiary,
+                        &rationale_hash,
+                    );
+                }
+                Err(err) => {
+                    release_lock(&e);
+                    return Err(err);
+                }
+            }
+        }
+
+        pay_ruling(&e, id, &escrow, total_pay, total_refund)?;
+        escrow.paid_amount += total_pay;
+        refresh_status(&mut escrow, e.ledger().timestamp());
+
+        store_escrow(&e, id, &escrow, &caller);
+        if escrow.status == EscrowStatus::Released {
+            complete_escrow(&e, id, &escrow, &caller)?;
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "resolve_multiple", None);
+        Ok(())
+    }
+
+    /// Either party escalates a disputed milestone to the escrow's governance contract
+    pub fn appeal(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        let role = resolve_role(&e, id, &caller, &escrow);
+        if role != Role::Depositor && role != Role::Beneficiary {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        let governance = match escrow.governance.clone() {
+            Some(governance) => governance,
+            None => return Err(EscrowError::NotAuthorized),
+        };
+
+        if milestone_index >= escrow.milestones.len() {
+            return Err(EscrowError::InvalidMilestone);
+        }
+
+        let mut milestone = escrow.milestones.get(milestone_index).unwrap();
+
+        if milestone.status != MilestoneStatus::Disputed {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if milestone.appealed {
+            return Err(EscrowError::AppealPending);
+        }
+
+        milestone.appealed = true;
+        escrow.milestones.set(milestone_index, milestone);
+        store_escrow(&e, id, &escrow, &caller);
+        record_dispute(&e, id, milestone_index, DisputeAction::Appealed, &caller, 0);
+
+        DisputeAppealed {
+            id,
+            milestone_index,
+            governance,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+
+        log_action(&e, id, &caller, "appeal", Some(milestone_index));
+        Ok(())
+    }
+
+    /// Anyone can enforce the governance verdict for an appealed milestone once it exists
+    pub fn enforce_verdict(e: Env, id: u32, milestone_index: u32) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if !is_appealed(&escrow, milestone_index) {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        let governance = escrow.governance.clone().unwrap();
+        let verdict = GovernanceClient::new(&e, &governance).get_verdict(
+            &e.current_contract_address(),
+            &id,
+            &milestone_index,
+        );
+        let pay_to_beneficiary = match verdict {
+            Some(pay_to_beneficiary) => pay_to_beneficiary,
+            None => {
+                release_lock(&e);
+                return Err(EscrowError::AppealPending);
+            }
+        };
+
+        let refund = match apply_ruling(&e, id, &mut escrow, milestone_index, pay_to_beneficiary, None) {
+            Ok(refund) => refund,
+            Err(err) => {
+                release_lock(&e);
+                return Err(err);
+            }
+        };
+
+        pay_ruling(&e, id, &escrow, pay_to_beneficiary, refund)?;
+        escrow.paid_amount += pay_to_beneficiary;
+        refresh_status(&mut escrow, e.ledger().timestamp());
+
+        store_escrow(&e, id, &escrow, &e.current_contract_address());
+        record_dispute(
+            &e,
+            id,
+            milestone_index,
+            DisputeAction::Resolved,
+            &governance,
+            pay_to_beneficiary,
+        );
+        record_ruling_stats(&e, &escrow, pay_to_beneficiary, refund, None);
+        AppealEnforced {
+            id,
+            milestone_index,
+            governance,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        if escrow.status == EscrowStatus::Released {
+            complete_escrow(&e, id, &escrow, &e.current_contract_address())?;
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &e.current_contract_address(), "enforce_verdict", Some(milestone_index));
+        Ok(())
+    }
+
+    /// Depositor and arbiter each attest to a replacement for a beneficiary who lost their key.
+    /// Once both agree a public waiting period starts, during which the current key can cancel.
+    pub fn attest_rotation(
+        e: Env,
+        caller: Address,
+        id: u32,
+        new_beneficiary: Address,
+    ) -> Result<u64, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let escrow = load_escrow(&e, id)?;
+
+        let role = resolve_role(&e, id, &caller, &escrow);
+        if role != Role::Depositor && role != Role::Arbiter {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if !is_active(&escrow) {
+            return Err(EscrowError::AlreadyCompleted);
+        }
+
+        if resolve_role(&e, id, &new_beneficiary, &escrow) != Role::Stranger {
+            return Err(EscrowError::InvalidBeneficiary);
+        }
+
+        let key = rotation_key(id);
+        let mut rotation = match e.storage().persistent().get::<_, BeneficiaryRotation>(&key) {
+            Some(r) if r.new_beneficiary == new_beneficiary => r,
+            // Attesting to a different address starts over
+            _ => BeneficiaryRotation {
+                new_beneficiary: new_beneficiary.clone(),
+                depositor_attested: false,
+                arbiter_attested: false,
+                ready_at: 0,
+            },
+        };
+
+        if role == Role::Depositor {
+            rotation.depositor_attested = true;
+        } else {
+            rotation.arbiter_attested = true;
+        }
+        if rotation.depositor_attested && rotation.arbiter_attested && rotation.ready_at == 0 {
+            rotation.ready_at = e.ledger().timestamp().saturating_add(ROTATION_DELAY);
+        }
+
+        e.storage().persistent().set(&key, &rotation);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        RotationAttested {
+            id,
+            attested_by: role,
+            new_beneficiary,
+            ready_at: rotation.ready_at,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        log_action(&e, id, &caller, "attest_rotation", None);
+        Ok(rotation.ready_at)
+    }
+
+    /// Current beneficiary vetoes a pending rotation, proving the key is not lost
+    pub fn cancel_rotation(e: Env, caller: Address, id: u32) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Beneficiary {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        let key = rotation_key(id);
+        if !e.storage().persistent().has(&key) {
+            return Err(EscrowError::RotationNotReady);
+        }
+        e.storage().persistent().remove(&key);
+
+        RotationCancelled { id, seq: next_event_seq(&e) }.publish(&e);
+        log_action(&e, id, &caller, "cancel_rotation", None);
+        Ok(())
+    }
+
+    /// Beneficiary of a streaming escrow collects everything vested since the last claim.
+    /// The final claim at the deadline completes the escrow. Returns the amount paid.
+    pub fn claim_streamed(e: Env, caller: Address, id: u32) -> Result<i128, EscrowError> {
+        claim_vested_funds(&e, caller, id, |mode| *mode == PayoutMode::Stream, "claim_streamed")
+    }
+
+    /// Beneficiary of a vesting escrow collects every period unlocked since the last claim.
+    /// The claim that drains the schedule completes the escrow. Returns the amount paid.
+    pub fn claim_vested(e: Env, caller: Address, id: u32) -> Result<i128, EscrowError> {
+        claim_vested_funds(
+            &e,
+            caller,
+            id,
+            |mode| matches!(mode, PayoutMode::Vesting(_)),
+            "claim_vested",
+        )
+    }
+
+    /// Beneficiary collects what has accrued so far of approved milestones paid out under the
+    /// escrow's payout tail. Returns the amount paid.
+    pub fn claim_accrued(e: Env, caller: Address, id: u32) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Beneficiary {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if is_frozen(&e, &escrow) {
+            release_lock(&e);
+            return Err(EscrowError::Frozen);
+        }
+
+        let now = e.ledger().timestamp();
+        let mut payouts = Vec::new(&e);
+        let mut unfinished = Vec::new(&e);
+        let mut total = 0i128;
+        let mut remaining = 0i128;
+        for mut accrual in load_accruals(&e, id).iter() {
+            let due = accrued_amount(&accrual, now) - accrual.claimed;
+            if due > 0 {
+                accrual.claimed += due;
+                total += due;
+                payouts.push_back((accrual.recipient.clone(), due));
+            }
+            if accrual.claimed < accrual.amount {
+                remaining += accrual.amount - accrual.claimed;
+                unfinished.push_back(accrual);
+            }
+        }
+        if total == 0 {
+            release_lock(&e);
+            return Err(EscrowError::ZeroAmount);
+        }
+
+        store_accruals(&e, id, &unfinished);
+        adjust_locked(&e, &escrow.token, -total);
+        for (recipient, due) in payouts.iter() {
+            pay_beneficiary(&e, id, &escrow, due, recipient.as_ref())?;
+        }
+
+        if escrow.private_events {
+            AccruedClaimedMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            AccruedClaimed {
+                id,
+                amount: total,
+                remaining,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "claim_accrued", None);
+        Ok(total)
+    }
+
+    /// Approved milestone payouts still accruing under the escrow's payout tail
+    pub fn get_accruals(e: Env, id: u32) -> Vec<Accrual> {
+        load_accruals(&e, id)
+    }
+
+    /// Beneficiary of a subscription collects every fully elapsed period not yet paid.
+    /// Returns the amount paid.
+    pub fn claim_period(e: Env, caller: Address, id: u32) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Beneficiary {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        let PayoutMode::Subscription(period) = escrow.mode else {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
+        };
+        if escrow.status != EscrowStatus::InProgress {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if is_frozen(&e, &escrow) {
+            release_lock(&e);
+            return Err(EscrowError::Frozen);
+        }
+
+        let now = e.ledger().timestamp();
+        let (periods, amount) = approve_elapsed_periods(&mut escrow, period, now);
+        if periods == 0 {
+            release_lock(&e);
+            return Err(EscrowError::ZeroAmount);
+        }
+
+        refresh_status(&mut escrow, now);
+        store_escrow(&e, id, &escrow, &caller);
+        pay_beneficiary(&e, id, &escrow, amount, None)?;
+
+        if escrow.private_events {
+            PeriodsClaimedMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            PeriodsClaimed {
+                id,
+                periods,
+                amount,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+        if escrow.status == EscrowStatus::Released {
+            complete_escrow(&e, id, &escrow, &caller)?;
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "claim_period", None);
+        Ok(amount)
+    }
+
+    /// Depositor ends a subscription: periods that have not finished are refunded, those
+    /// already elapsed stay claimable by the beneficiary. Returns the amount refunded.
+    pub fn cancel_subscription(e: Env, caller: Address, id: u32) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        let PayoutMode::Subscription(period) = escrow.mode else {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
+        };
+        if escrow.status != EscrowStatus::InProgress {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if is_frozen(&e, &escrow) {
+            release_lock(&e);
+            return Err(EscrowError::Frozen);
+        }
+
+        let now = e.ledger().timestamp();
+        let (refunded, refund_amount) = close_periods_after(&e, &mut escrow, period, now);
+        if refunded.is_empty() {
+            release_lock(&e);
+            return Err(EscrowError::ZeroAmount);
+        }
+
+        refresh_status(&mut escrow, now);
+        store_escrow(&e, id, &escrow, &caller);
+        refund_depositor(&e, &escrow, refund_amount)?;
+        publish_refund(&e, id, &escrow, refund_amount, refunded);
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "cancel_subscription", None);
+        Ok(refund_amount)
+    }
+
+    /// Anyone releases every elapsed retainer period the depositor has not disputed. Returns
+    /// the amount paid.
+    pub fn release_retainer(e: Env, id: u32) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        let PayoutMode::Retainer(period) = escrow.mode else {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
+        };
+        // Other periods keep paying out while one is in arbitration
+        if !matches!(escrow.status, EscrowStatus::InProgress | EscrowStatus::Disputed) {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if is_frozen(&e, &escrow) {
+            release_lock(&e);
+            return Err(EscrowError::Frozen);
+        }
+
+        let now = e.ledger().timestamp();
+        let (periods, amount) = approve_elapsed_periods(&mut escrow, period, now);
+        if periods == 0 {
+            release_lock(&e);
+            return Err(EscrowError::ZeroAmount);
+        }
+
+        let actor = e.current_contract_address();
+        refresh_status(&mut escrow, now);
+        store_escrow(&e, id, &escrow, &actor);
+        pay_beneficiary(&e, id, &escrow, amount, None)?;
+
+        if escrow.private_events {
+            PeriodsClaimedMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            PeriodsClaimed {
+                id,
+                periods,
+                amount,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+        if escrow.status == EscrowStatus::Released {
+            complete_escrow(&e, id, &escrow, &actor)?;
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &actor, "release_retainer", None);
+        Ok(amount)
+    }
+
+    /// Either party ends a retainer with one period's notice: periods ending after the notice
+    /// runs out are refunded, the rest still pay out, and the escrow ends refunded. Returns the
+    /// amount refunded.
+    pub fn terminate_retainer(e: Env, caller: Address, id: u32) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if !matches!(resolve_role(&e, id, &caller, &escrow), Role::Depositor | Role::Beneficiary) {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        let PayoutMode::Retainer(period) = escrow.mode else {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
+        };
+        if !matches!(escrow.status, EscrowStatus::InProgress | EscrowStatus::Disputed) {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if is_frozen(&e, &escrow) {
+            release_lock(&e);
+            return Err(EscrowError::Frozen);
+        }
+
+        let now = e.ledger().timestamp();
+        let notice_ends = now.saturating_add(period);
+        let (refunded, refund_amount) = close_periods_after(&e, &mut escrow, period, notice_ends);
+        if refunded.is_empty() {
+            release_lock(&e);
+            return Err(EscrowError::ZeroAmount);
+        }
+
+        refresh_status(&mut escrow, now);
+        store_escrow(&e, id, &escrow, &caller);
+        refund_depositor(&e, &escrow, refund_amount)?;
+        publish_refund(&e, id, &escrow, refund_amount, refunded);
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "terminate_retainer", None);
+        Ok(refund_amount)
+    }
+
+    /// Anyone funds part of a crowdfunded escrow before work starts. Refunds are shared out in
+    /// proportion to contributions; the depositor alone makes the escrow's decisions.
+    /// Returns the amount still needed.
+    pub fn contribute(e: Env, caller: Address, id: u32, amount: i128) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if escrow.funding != FundingMode::Crowdfunded {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
+        }
+        if escrow.status != EscrowStatus::Pending {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if is_blocked(&e, &caller) {
+            release_lock(&e);
+            return Err(EscrowError::Blocked);
+        }
+        if amount <= 0 {
+            release_lock(&e);
+            return Err(EscrowError::ZeroAmount);
+        }
+        if amount > unfunded_amount(&escrow) {
+            release_lock(&e);
+            return Err(EscrowError::AmountOutOfBounds);
+        }
+
+        safe_transfer(&e, &escrow.token, &caller, &e.current_contract_address(), &amount)?;
+        add_contribution(&mut escrow, &caller, amount);
+        store_escrow(&e, id, &escrow, &caller);
+
+        let remaining = unfunded_amount(&escrow);
+        if escrow.private_events {
+            ContributionReceivedMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            ContributionReceived {
+                id,
+                contributor: caller.clone(),
+                amount,
+                remaining,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "contribute", None);
+        Ok(remaining)
+    }
+
+    /// Depositor of a pay-as-you-go escrow deposits the next unfunded milestone, which the
+    /// beneficiary can then submit. Returns the amount still committed but not deposited.
+    pub fn fund_milestone(e: Env, caller: Address, id: u32) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if escrow.funding != FundingMode::PerMilestone {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
+        }
+        if !is_active(&escrow) {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+
+        let contributed = contributed_amount(&escrow);
+        let next = (0..escrow.milestones.len())
+            .find(|index| funded_through(&escrow, *index) > contributed);
+        let Some(milestone_index) = next else {
+            release_lock(&e);
+            return Err(EscrowError::InvalidMilestone);
+        };
+        let amount = funded_through(&escrow, milestone_index) - contributed;
+
+        safe_transfer(&e, &escrow.token, &caller, &e.current_contract_address(), &amount)?;
+        add_contribution(&mut escrow, &caller, amount);
+        store_escrow(&e, id, &escrow, &caller);
+
+        let remaining = unfunded_amount(&escrow);
+        if escrow.private_events {
+            MilestoneFundedMinimal { id, milestone_index, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            MilestoneFunded {
+                id,
+                milestone_index,
+                amount,
+                remaining,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "fund_milestone", Some(milestone_index));
+        Ok(remaining)
+    }
+
+    /// Anyone but the depositor and arbiter enters a bounty before its deadline, one entry per
+    /// address. The work itself lives off-chain behind `work_hash`.
+    pub fn submit_entry(
+        e: Env,
+        caller: Address,
+        id: u32,
+        work_hash: BytesN<32>,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let escrow = load_escrow(&e, id)?;
+
+        if escrow.mode != PayoutMode::Bounty {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
+        }
+        if escrow.status != EscrowStatus::Pending {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if e.ledger().timestamp() >= escrow.deadline {
+            release_lock(&e);
+            return Err(EscrowError::SubmissionWindowClosed);
+        }
+        if matches!(resolve_role(&e, id, &caller, &escrow), Role::Depositor | Role::Arbiter) {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if is_blocked(&e, &caller) {
+            release_lock(&e);
+            return Err(EscrowError::Blocked);
+        }
+        let mut entries = load_entries(&e, id);
+        if entries.iter().any(|(entrant, _)| entrant == caller) {
+            release_lock(&e);
+            return Err(EscrowError::MilestoneAlreadySubmitted);
+        }
+        if entries.len() >= MAX_BOUNTY_ENTRIES {
+            release_lock(&e);
+            return Err(EscrowError::InvalidMilestone);
+        }
+
+        entries.push_back((caller.clone(), work_hash.clone()));
+        let key = entries_key(id);
+        let ttl = escrow_ttl_ledgers(&e, &escrow);
+        e.storage().persistent().set(&key, &entries);
+        e.storage().persistent().extend_ttl(&key, ttl, ttl);
+
+        if escrow.private_events {
+            BountyEntrySubmittedMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            BountyEntrySubmitted {
+                id,
+                entrant: caller.clone(),
+                work_hash,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "submit_entry", None);
+        Ok(())
+    }
+
+    /// Depositor picks the winning entry; the winner becomes the beneficiary and is paid the
+    /// whole bounty at once
+    pub fn pick_winner(e: Env, caller: Address, id: u32, winner: Address) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        let total = load_escrow(&e, id)?.total_amount;
+        award_bounty(&e, &caller, id, Vec::from_array(&e, [(winner, total)]), "pick_winner")
+    }
+
+    /// Depositor splits the prize among several entries, paying each finalist its amount in one
+    /// go. Whatever is not awarded goes back to the depositor; the first finalist listed becomes
+    /// the beneficiary.
+    pub fn pick_winners(
+        e: Env,
+        caller: Address,
+        id: u32,
+        winners: Vec<(Address, i128)>,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        award_bounty(&e, &caller, id, winners, "pick_winners")
+    }
+
+    /// Beneficiary bills hours against an hourly escrow. Time awaiting review counts against
+    /// the funded cap. Returns the timesheet's index.
+    pub fn log_time(
+        e: Env,
+        caller: Address,
+        id: u32,
+        hours: u32,
+        note_hash: BytesN<32>,
+    ) -> Result<u32, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Beneficiary {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        let PayoutMode::Hourly(rate) = escrow.mode else {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
+        };
+        if escrow.status != EscrowStatus::InProgress {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if is_frozen(&e, &escrow) {
+            release_lock(&e);
+            return Err(EscrowError::Frozen);
+        }
+        if hours == 0 {
+            release_lock(&e);
+            return Err(EscrowError::ZeroAmount);
+        }
+        let mut sheets = load_sheets(&e, id);
+        if sheets.len() >= MAX_TIMESHEETS {
+            release_lock(&e);
+            return Err(EscrowError::InvalidMilestone);
+        }
+        let amount = rate.saturating_mul(hours as i128);
+        let pending: i128 = sheets
+            .iter()
+            .filter(|sheet| sheet.status == MilestoneStatus::Submitted)
+            .map(|sheet| sheet.amount)
+            .sum();
+        if escrow.claimed + pending + amount > escrow.total_amount {
+            release_lock(&e);
+            return Err(EscrowError::AmountOutOfBounds);
+        }
+
+        let sheet = sheets.len();
+        sheets.push_back(Timesheet {
+            hours,
+            amount,
+            note_hash: note_hash.clone(),
+            status: MilestoneStatus::Submitted,
+            logged_at: e.ledger().timestamp(),
+        });
+        store_sheets(&e, id, &escrow, &sheets);
+
+        if escrow.private_events {
+            TimeLoggedMinimal { id, sheet, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            TimeLogged {
+                id,
+                sheet,
+                hours,
+                amount,
+                note_hash,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "log_time", Some(sheet));
+        Ok(sheet)
+    }
+
+    /// Depositor accepts a timesheet and its hours are paid at the escrow's rate. Billing the
+    /// whole cap completes the escrow.
+    pub fn approve_time(e: Env, caller: Address, id: u32, sheet: u32) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if !matches!(escrow.mode, PayoutMode::Hourly(_)) {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
+        }
+        if escrow.status != EscrowStatus::InProgress {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if is_frozen(&e, &escrow) {
+            release_lock(&e);
+            return Err(EscrowError::Frozen);
+        }
+        let mut sheets = load_sheets(&e, id);
+        let Some(mut timesheet) = sheets.get(sheet) else {
+            release_lock(&e);
+            return Err(EscrowError::InvalidMilestone);
+        };
+        if timesheet.status != MilestoneStatus::Submitted {
+            release_lock(&e);
+            return Err(EscrowError::MilestoneNotSubmitted);
+        }
+
+        let now = e.ledger().timestamp();
+        let amount = timesheet.amount;
+        timesheet.status = MilestoneStatus::Approved;
+        sheets.set(sheet, timesheet);
+        escrow.claimed += amount;
+        escrow.paid_amount += amount;
+        if escrow.claimed == escrow.total_amount {
+            let mut milestone = escrow.milestones.get(0).unwrap();
+            milestone.status = MilestoneStatus::Approved;
+            milestone.approved_at = Some(now);
+            escrow.milestones.set(0, milestone);
+            escrow.status = EscrowStatus::Released;
+        }
+        store_escrow(&e, id, &escrow, &caller);
+        store_sheets(&e, id, &escrow, &sheets);
+        pay_beneficiary(&e, id, &escrow, amount, None)?;
+
+        if escrow.private_events {
+            TimeApprovedMinimal { id, sheet, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            TimeApproved {
+                id,
+                sheet,
+                amount,
+                claimed: escrow.claimed,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+        if escrow.status == EscrowStatus::Released {
+            complete_escrow(&e, id, &escrow, &caller)?;
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "approve_time", Some(sheet));
+        Ok(amount)
+    }
+
+    /// Depositor disputes a timesheet. The escrow goes to arbitration like a disputed
+    /// milestone; the ruling splits that sheet's amount and the escrow carries on.
+    pub fn dispute_time(e: Env, caller: Address, id: u32, sheet: u32) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if !matches!(escrow.mode, PayoutMode::Hourly(_)) {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
+        }
+        if escrow.status != EscrowStatus::InProgress {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        let mut sheets = load_sheets(&e, id);
+        let Some(mut timesheet) = sheets.get(sheet) else {
+            release_lock(&e);
+            return Err(EscrowError::InvalidMilestone);
+        };
+        if timesheet.status != MilestoneStatus::Submitted {
+            release_lock(&e);
+            return Err(EscrowError::MilestoneNotSubmitted);
+        }
+
+        let amount = timesheet.amount;
+        timesheet.status = MilestoneStatus::Disputed;
+        sheets.set(sheet, timesheet);
+        let mut milestone = escrow.milestones.get(0).unwrap();
+        milestone.status = MilestoneStatus::Disputed;
+        escrow.milestones.set(0, milestone);
+        escrow.status = EscrowStatus::Disputed;
+        if escrow.disputed_since.is_none() {
+            escrow.disputed_since = Some(e.ledger().timestamp());
+        }
+
+        store_escrow(&e, id, &escrow, &caller);
+        store_sheets(&e, id, &escrow, &sheets);
+        record_dispute(&e, id, 0, DisputeAction::Opened, &caller, amount);
+        update_dispute_stats(&e, &caller, |s| s.opened += 1);
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "dispute_time", Some(sheet));
+        Ok(())
+    }
+
+    /// Depositor locks a payment for the beneficiary until `unlock_at`. Scheduled payments
+    /// have their own id sequence.
+    pub fn schedule_payment(
+        e: Env,
+        depositor: Address,
+        beneficiary: Address,
+        token: Address,
+        amount: i128,
+        unlock_at: u64,
+    ) -> Result<u32, EscrowError> {
+        ensure_not_paused(&e)?;
+        depositor.require_auth();
+
+        if amount <= 0 {
+            return Err(EscrowError::ZeroAmount);
+        }
+        if depositor == beneficiary {
+            return Err(EscrowError::InvalidBeneficiary);
+        }
+        if unlock_at <= e.ledger().timestamp() {
+            return Err(EscrowError::InvalidDeadline);
+        }
+        if is_blocked(&e, &depositor) || is_blocked(&e, &beneficiary) {
+            return Err(EscrowError::Blocked);
+        }
+        let config = load_config(&e)?;
+
+        acquire_lock(&e)?;
+        let id = e
+            .storage()
+            .instance()
+            .get::<_, u32>(&sym_schedule_counter())
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or(EscrowError::CounterOverflow)?;
+        e.storage().instance().set(&sym_schedule_counter(), &id);
+
+        if safe_transfer(&e, &token, &depositor, &e.current_contract_address(), &amount).is_err() {
+            release_lock(&e);
+            return Err(EscrowError::TransferFailed);
+        }
+        adjust_locked(&e, &token, amount);
+        store_scheduled(
+            &e,
+            id,
+            &ScheduledPayment {
+                depositor: depositor.clone(),
+                beneficiary: beneficiary.clone(),
+                token: token.clone(),
+                amount,
+                unlock_at,
+                fee_bps: config.fee_bps,
+                state: ScheduleState::Locked,
+            },
+        );
+
+        PaymentScheduled {
+            id,
+            depositor,
+            beneficiary,
+            token,
+            amount,
+            unlock_at,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        release_lock(&e);
+        Ok(id)
+    }
+
+    /// Anyone pushes a scheduled payment to its beneficiary once it has unlocked
+    pub fn execute_payment(e: Env, id: u32) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        acquire_lock(&e)?;
+
+        let mut payment = load_scheduled(&e, id)?;
+        if payment.state != ScheduleState::Locked {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if e.ledger().timestamp() < payment.unlock_at {
+            release_lock(&e);
+            return Err(EscrowError::NotYetDue);
+        }
+        if is_blocked(&e, &payment.beneficiary) {
+            release_lock(&e);
+            return Err(EscrowError::Blocked);
+        }
+
+        payment.state = ScheduleState::Paid;
+        store_scheduled(&e, id, &payment);
+        adjust_locked(&e, &payment.token, -payment.amount);
+        pay_out(
+            &e,
+            &payment.token,
+            &payment.depositor,
+            &payment.beneficiary,
+            payment.amount,
+            payment.fee_bps,
+        )?;
+        ScheduledPaymentClosed {
+            id,
+            state: ScheduleState::Paid,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        release_lock(&e);
+        Ok(())
+    }
+
+    /// Depositor takes a scheduled payment back before it unlocks
+    pub fn cancel_scheduled(e: Env, caller: Address, id: u32) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut payment = load_scheduled(&e, id)?;
+        if resolve_party(&caller, &payment.depositor, Some(&payment.beneficiary)) != Role::Depositor {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if payment.state != ScheduleState::Locked {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if e.ledger().timestamp() >= payment.unlock_at {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        payment.state = ScheduleState::Cancelled;
+        store_scheduled(&e, id, &payment);
+        adjust_locked(&e, &payment.token, -payment.amount);
+        safe_transfer(
+            &e,
+            &payment.token,
+            &e.current_contract_address(),
+            &payment.depositor,
+            &payment.amount,
+        )?;
+        ScheduledPaymentClosed {
+            id,
+            state: ScheduleState::Cancelled,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        release_lock(&e);
+        Ok(())
+    }
+
+    pub fn get_scheduled(e: Env, id: u32) -> Option<ScheduledPayment> {
+        e.storage().persistent().get(&scheduled_key(id))
+    }
+
+    /// Beneficiary invoices a depositor. Nothing moves until the depositor pays it with
+    /// `pay_request` or funds it into an escrow with `escrow_request`. Requests have their own
+    /// id sequence.
+    pub fn request_payment(
+        e: Env,
+        beneficiary: Address,
+        depositor: Address,
+        amount: i128,
+        token: Address,
+        memo_hash: BytesN<32>,
+    ) -> Result<u32, EscrowError> {
+        ensure_not_paused(&e)?;
+        beneficiary.require_auth();
+
+        if amount <= 0 {
+            return Err(EscrowError::ZeroAmount);
+        }
+        if beneficiary == depositor {
+            return Err(EscrowError::InvalidBeneficiary);
+        }
+        if is_blocked(&e, &beneficiary) || is_blocked(&e, &depositor) {
+            return Err(EscrowError::Blocked);
+        }
+
+        let id = e
+            .storage()
+            .instance()
+            .get::<_, u32>(&sym_request_counter())
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or(EscrowError::CounterOverflow)?;
+        e.storage().instance().set(&sym_request_counter(), &id);
+
+        store_request(
+            &e,
+            id,
+            &PaymentRequest {
+                beneficiary: beneficiary.clone(),
+                depositor: depositor.clone(),
+                token: token.clone(),
+                amount,
+                memo_hash: memo_hash.clone(),
+                status: RequestStatus::Open,
+                created_at: e.ledger().timestamp(),
+            },
+        );
+        PaymentRequested {
+            id,
+            beneficiary,
+            depositor,
+            token,
+            amount,
+            memo_hash,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        Ok(id)
+    }
+
+    /// Depositor pays an open request straight to the beneficiary, less the platform fee
+    pub fn pay_request(e: Env, caller: Address, id: u32) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut request = load_request(&e, id)?;
+        if resolve_party(&caller, &request.depositor, Some(&request.beneficiary)) != Role::Depositor {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if request.status != RequestStatus::Open {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if is_blocked(&e, &request.beneficiary) {
+            release_lock(&e);
+            return Err(EscrowError::Blocked);
+        }
+        if let Err(err) = pay_now(&e, &request.token, &caller, &request.beneficiary, request.amount) {
+            release_lock(&e);
+            return Err(err);
+        }
+        close_request(&e, id, &mut request, RequestStatus::Paid);
+
+        release_lock(&e);
+        Ok(())
+    }
+
+    /// Pays `to` immediately without an escrow, for counterparties who no longer need one.
+    /// The payment still counts towards both parties' stats.
+    pub fn pay_direct(
+        e: Env,
+        from: Address,
+        to: Address,
+        token: Address,
+        amount: i128,
+        memo_hash: BytesN<32>,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        from.require_auth();
+
+        if amount <= 0 {
+            return Err(EscrowError::ZeroAmount);
+        }
+        if from == to {
+            return Err(EscrowError::InvalidBeneficiary);
+        }
+        if is_blocked(&e, &from) || is_blocked(&e, &to) {
+            return Err(EscrowError::Blocked);
+        }
+
+        acquire_lock(&e)?;
+        if let Err(err) = pay_now(&e, &token, &from, &to, amount) {
+            release_lock(&e);
+            return Err(err);
+        }
+        DirectPayment {
+            from,
+            to,
+            token,
+            amount,
+            memo_hash,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        release_lock(&e);
+        Ok(())
+    }
+
+    /// Depositor funds an open request into a single-milestone escrow for the beneficiary, to be
+    /// released on delivery as usual. Returns the new escrow's id.
+    pub fn escrow_request(
+        e: Env,
+        caller: Address,
+        id: u32,
+        arbiter: Option<Address>,
+        duration: u64,
+    ) -> Result<u32, EscrowError> {
+        ensure_not_paused(&e)?;
+
+        let mut request = load_request(&e, id)?;
+        if resolve_party(&caller, &request.depositor, Some(&request.beneficiary)) != Role::Depositor {
+            return Err(EscrowError::NotAuthorized);
+        }
+        if request.status != RequestStatus::Open {
+            return Err(EscrowError::AlreadyCompleted);
+        }
+
+        let mut milestone_amounts = Vec::new(&e);
+        milestone_amounts.push_back(request.amount);
+        // Authorizes the depositor and takes the deposit
+        let escrow_id = create_escrow(
+            &e,
+            CreateParams {
+                depositor: request.depositor.clone(),
+                beneficiary: request.beneficiary.clone(),
+                arbiter,
+                milestone_amounts,
+                token: request.token.clone(),
+                duration,
+                private_events: false,
+                checklists: Vec::new(&e),
+                review_period: None,
+                resubmit_cooldown: None,
+                submission_cutoff: None,
+                governance: None,
+                insured: false,
+                milestone_deadlines: Vec::new(&e),
+                penalty: PenaltySchedule::default(),
+                bonus: 0,
+                kill_fee_bps: 0,
+                referrer: None,
+                referral_bps: 0,
+                mode: PayoutMode::Milestones,
+                splits: Vec::new(&e),
+                milestone_recipients: Vec::new(&e),
+                funding: FundingMode::Upfront,
+                transferable: false,
+                project: None,
+                payout_tail: PayoutTail::default(),
+            },
+            None,
+        )?;
+        close_request(&e, id, &mut request, RequestStatus::Escrowed(escrow_id));
+        Ok(escrow_id)
+    }
+
+    /// Either party closes an open request without payment
+    pub fn decline_request(e: Env, caller: Address, id: u32) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let mut request = load_request(&e, id)?;
+        if resolve_party(&caller, &request.depositor, Some(&request.beneficiary)) == Role::Stranger {
+            return Err(EscrowError::NotAuthorized);
+        }
+        if request.status != RequestStatus::Open {
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        close_request(&e, id, &mut request, RequestStatus::Declined);
+        Ok(())
+    }
+
+    pub fn get_request(e: Env, id: u32) -> Option<PaymentRequest> {
+        e.storage().persistent().get(&request_key(id))
+    }
+
+    /// Depositor funds one pay cycle for up to 50 recipients in a single deposit. Each recipient
+    /// can claim from `release_at`, or the depositor can pay everyone at once with
+    /// `release_payroll`. Payrolls have their own id sequence.
+    pub fn create_payroll(
+        e: Env,
+        depositor: Address,
+        token: Address,
+        recipients: Vec<(Address, i128)>,
+        release_at: u64,
+    ) -> Result<u32, EscrowError> {
+        ensure_not_paused(&e)?;
+        depositor.require_auth();
+
+        if recipients.is_empty() || recipients.len() > MAX_PAYROLL_RECIPIENTS {
+            return Err(EscrowError::InvalidMilestone);
+        }
+        if is_blocked(&e, &depositor) {
+            return Err(EscrowError::Blocked);
+        }
+        let mut total: i128 = 0;
+        let mut paid = Vec::new(&e);
+        for (index, (recipient, amount)) in recipients.iter().enumerate() {
+            if amount <= 0 {
+                return Err(EscrowError::ZeroAmount);
+            }
+            if recipient == depositor
+                || recipients.iter().take(index).any(|(other, _)| other == recipient)
+            {
+                return Err(EscrowError::InvalidBeneficiary);
+            }
+            if is_blocked(&e, &recipient) {
+                return Err(EscrowError::Blocked);
+            }
+            total = total.checked_add(amount).ok_or(EscrowError::InvalidMilestone)?;
+            paid.push_back(false);
+        }
+        let config = load_config(&e)?;
+
+        acquire_lock(&e)?;
+        let id = e
+            .storage()
+            .instance()
+            .get::<_, u32>(&sym_payroll_counter())
+            .unwrap_or(0)
+            .checked_add(1)
+            .ok_or(EscrowError::CounterOverflow)?;
+        e.storage().instance().set(&sym_payroll_counter(), &id);
+
+        if safe_transfer(&e, &token, &depositor, &e.current_contract_address(), &total).is_err() {
+            release_lock(&e);
+            return Err(EscrowError::TransferFailed);
+        }
+        adjust_locked(&e, &token, total);
+        let payroll = Payroll {
+            depositor: depositor.clone(),
+            token: token.clone(),
+            recipients: recipients.clone(),
+            paid,
+            release_at,
+            fee_bps: config.fee_bps,
+        };
+        store_payroll(&e, id, &payroll);
+
+        PayrollCreated {
+            id,
+            depositor,
+            token,
+            total,
+            recipients: recipients.len(),
+            release_at,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        release_lock(&e);
+        Ok(id)
+    }
+
+    /// Depositor pays every recipient not yet paid, whether or not `release_at` has come.
+    /// Blocked recipients are skipped and stay claimable. Returns the total paid.
+    pub fn release_payroll(e: Env, caller: Address, id: u32) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut payroll = load_payroll(&e, id)?;
+        if resolve_party(&caller, &payroll.depositor, None) != Role::Depositor {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        let mut total = 0;
+        for index in 0..payroll.recipients.len() {
+            let (recipient, _) = payroll.recipients.get(index).unwrap();
+            if payroll.paid.get(index).unwrap() || is_blocked(&e, &recipient) {
+                continue;
+            }
+            total += pay_payroll_recipient(&e, id, &mut payroll, index)?;
+        }
+        if total == 0 {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyClaimed);
+        }
+        store_payroll(&e, id, &payroll);
+
+        release_lock(&e);
+        Ok(total)
+    }
+
+    /// Recipient collects its share once the payroll's release time has passed
+    pub fn claim_payroll(e: Env, caller: Address, id: u32) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut payroll = load_payroll(&e, id)?;
+        let Some(index) = payroll.recipients.iter().position(|(recipient, _)| recipient == caller)
+        else {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        };
+        let index = index as u32;
+        if payroll.paid.get(index).unwrap() {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyClaimed);
+        }
+        if e.ledger().timestamp() < payroll.release_at {
+            release_lock(&e);
+            return Err(EscrowError::NotYetDue);
+        }
+        if is_blocked(&e, &caller) {
+            release_lock(&e);
+            return Err(EscrowError::Blocked);
+        }
+
+        let amount = pay_payroll_recipient(&e, id, &mut payroll, index)?;
+        store_payroll(&e, id, &payroll);
+
+        release_lock(&e);
+        Ok(amount)
+    }
+
+    pub fn get_payroll(e: Env, id: u32) -> Option<Payroll> {
+        e.storage().persistent().get(&payroll_key(id))
+    }
+
+    /// Anyone announces due dates falling within the next 48 hours: the escrow deadline and
+    /// those of undelivered milestones. Each due date is announced once; returns how many were.
+    pub fn ping(e: Env, id: u32) -> Result<u32, EscrowError> {
+        ensure_not_paused(&e)?;
+        let escrow = load_escrow(&e, id)?;
+        if !is_active(&escrow) {
+            return Err(EscrowError::AlreadyCompleted);
+        }
+
+        let mut due_dates = Vec::new(&e);
+        due_dates.push_back((None, escrow.deadline));
+        for (index, milestone) in escrow.milestones.iter().enumerate() {
+            if let (MilestoneStatus::NotStarted, Some(due)) = (milestone.status, milestone.deadline) {
+                due_dates.push_back((Some(index as u32), due));
+            }
+        }
+
+        let key = pinged_key(id);
+        let mut pinged: Vec<(Option<u32>, u64)> =
+            e.storage().persistent().get(&key).unwrap_or(Vec::new(&e));
+        let now = e.ledger().timestamp();
+        let mut announced = 0;
+        for (milestone_index, due) in due_dates.iter() {
+            if now > due || now + DEADLINE_WARNING < due || pinged.contains((milestone_index, due)) {
+                continue;
+            }
+            pinged.push_back((milestone_index, due));
+            DeadlineApproaching {
+                id,
+                milestone_index,
+                due,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+            announced += 1;
+        }
+
+        if announced > 0 {
+            let ttl = escrow_ttl_ledgers(&e, &escrow);
+            e.storage().persistent().set(&key, &pinged);
+            e.storage().persistent().extend_ttl(&key, ttl, ttl);
+        }
+        Ok(announced)
+    }
+
+    /// Anyone applies a fully attested rotation once its waiting period has passed
+    pub fn complete_rotation(e: Env, id: u32) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        let mut escrow = load_escrow(&e, id)?;
+
+        let key = rotation_key(id);
+        let rotation: BeneficiaryRotation = e
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(EscrowError::RotationNotReady)?;
+
+        if rotation.ready_at == 0 || e.ledger().timestamp() < rotation.ready_at {
+            return Err(EscrowError::RotationNotReady);
+        }
+
+        if !is_active(&escrow) {
+            return Err(EscrowError::AlreadyCompleted);
+        }
+
+        if index_remove(&e, &(sym_by_beneficiary(), escrow.beneficiary.clone()), id) {
+            index_add(&e, &(sym_by_beneficiary(), rotation.new_beneficiary.clone()), id);
+        }
+        escrow.beneficiary = rotation.new_beneficiary.clone();
+        store_escrow(&e, id, &escrow, &e.current_contract_address());
+        e.storage().persistent().remove(&key);
+        // A payout address set with the replaced key goes with it
+        e.storage().persistent().remove(&payto_key(id));
+
+        BeneficiaryRotated {
+            id,
+            new_beneficiary: rotation.new_beneficiary,
+            seq: next_event_seq(&e),
+        }
+        .publish(&e);
+        log_action(&e, id, &e.current_contract_address(), "complete_rotation", None);
+        Ok(())
+    }
+
+    /// Timesheets logged against an hourly escrow, by index
+    pub fn get_timesheets(e: Env, id: u32) -> Vec<Timesheet> {
+        load_sheets(&e, id)
+    }
+
+    /// Bounty entries in the order they were submitted
+    pub fn get_entries(e: Env, id: u32) -> Vec<(Address, BytesN<32>)> {
+        load_entries(&e, id)
+    }
+
+    pub fn get_rotation(e: Env, id: u32) -> Option<BeneficiaryRotation> {
+        e.storage().persistent().get(&rotation_key(id))
+    }
+
+    /// Beneficiary has its payouts sent to another wallet from now on; passing its own address
+    /// goes back to paying it directly. The depositor co-signs when the config requires it.
+    pub fn change_payout_address(
+        e: Env,
+        caller: Address,
+        id: u32,
+        new_address: Address,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Beneficiary {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        // A sold claim is the holder's to redirect, through `transfer_claim`
+        if matches!(escrow.claim, ClaimState::Held(_)) {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if !is_active(&escrow) {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if new_address == escrow.depositor {
+            release_lock(&e);
+            return Err(EscrowError::InvalidBeneficiary);
+        }
+        if is_blocked(&e, &new_address) {
+            release_lock(&e);
+            return Err(EscrowError::Blocked);
+        }
+        let config = match load_config(&e) {
+            Ok(config) => config,
+            Err(err) => {
+                release_lock(&e);
+                return Err(err);
+            }
+        };
+        if config.payout_cosign {
+            escrow.depositor.require_auth();
+        }
+
+        let key = payto_key(id);
+        if new_address == escrow.beneficiary {
+            e.storage().persistent().remove(&key);
+        } else {
+            let ttl = escrow_ttl_ledgers(&e, &escrow);
+            e.storage().persistent().set(&key, &new_address);
+            e.storage().persistent().extend_ttl(&key, ttl, ttl);
+        }
+
+        if escrow.private_events {
+            PayoutAddressChangedMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            PayoutAddressChanged {
+                id,
+                address: new_address,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "change_payout_address", None);
+        Ok(())
+    }
+
+    /// Where the beneficiary's own payouts are sent
+    pub fn get_payout_address(e: Env, id: u32) -> Result<Address, EscrowError> {
+        let escrow = load_escrow(&e, id)?;
+        Ok(load_payout_address(&e, id).unwrap_or(escrow.beneficiary))
+    }
+
+    /// Depositor names a delegate to review milestones on its behalf, or an observer who only
+    /// follows the escrow. The parties themselves cannot be given another role.
+    pub fn grant_role(
+        e: Env,
+        caller: Address,
+        id: u32,
+        address: Address,
+        role: Role,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if !matches!(role, Role::Delegate | Role::Observer) {
+            release_lock(&e);
+            return Err(EscrowError::InvalidConfig);
+        }
+        if !is_active(&escrow) {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if matches!(
+            resolve_role(&e, id, &address, &escrow),
+            Role::Depositor | Role::Beneficiary | Role::Arbiter
+        ) {
+            release_lock(&e);
+            return Err(EscrowError::InvalidBeneficiary);
+        }
+        if is_blocked(&e, &address) {
+            release_lock(&e);
+            return Err(EscrowError::Blocked);
+        }
+        let mut roles = load_granted_roles(&e, id);
+        if !roles.contains_key(address.clone()) && roles.len() >= MAX_GRANTED_ROLES {
+            release_lock(&e);
+            return Err(EscrowError::InvalidConfig);
+        }
+
+        roles.set(address.clone(), role);
+        let key = roles_key(id);
+        let ttl = escrow_ttl_ledgers(&e, &escrow);
+        e.storage().persistent().set(&key, &roles);
+        e.storage().persistent().extend_ttl(&key, ttl, ttl);
+
+        if escrow.private_events {
+            RoleChangedMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            RoleGranted { id, address, role, seq: next_event_seq(&e) }.publish(&e);
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "grant_role", None);
+        Ok(())
+    }
+
+    /// Depositor withdraws a delegate's or observer's role
+    pub fn revoke_role(e: Env, caller: Address, id: u32, address: Address) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        let mut roles = load_granted_roles(&e, id);
+        if roles.remove(address.clone()).is_none() {
+            release_lock(&e);
+            return Err(EscrowError::InvalidBeneficiary);
+        }
+
+        let key = roles_key(id);
+        if roles.is_empty() {
+            e.storage().persistent().remove(&key);
+        } else {
+            e.storage().persistent().set(&key, &roles);
+        }
+
+        if escrow.private_events {
+            RoleChangedMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            RoleRevoked { id, address, seq: next_event_seq(&e) }.publish(&e);
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "revoke_role", None);
+        Ok(())
+    }
+
+    /// Current holder of the beneficiary's right to payouts sells it on, where the depositor
+    /// allowed it at creation. The beneficiary still delivers the work; the holder is paid.
+    pub fn transfer_claim(
+        e: Env,
+        caller: Address,
+        id: u32,
+        new_holder: Address,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        let holder = match &escrow.claim {
+            ClaimState::Fixed => {
+                release_lock(&e);
+                return Err(EscrowError::NotAuthorized);
+            }
+            ClaimState::Transferable => escrow.beneficiary.clone(),
+            ClaimState::Held(holder) => holder.clone(),
+        };
+        if caller != holder {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if !is_active(&escrow) {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if new_holder == holder || new_holder == escrow.depositor {
+            release_lock(&e);
+            return Err(EscrowError::InvalidBeneficiary);
+        }
+        if is_blocked(&e, &new_holder) {
+            release_lock(&e);
+            return Err(EscrowError::Blocked);
+        }
+
+        escrow.claim = ClaimState::Held(new_holder.clone());
+        store_escrow(&e, id, &escrow, &caller);
+
+        if escrow.private_events {
+            ClaimTransferredMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            ClaimTransferred {
+                id,
+                from: holder,
+                to: new_holder,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "transfer_claim", None);
+        Ok(())
+    }
+
+    /// Depositor hands the escrow, and any refund it would be owed, to another address. The
+    /// beneficiary co-signs, since it will be dealing with the new depositor.
+    pub fn transfer_depositor_rights(
+        e: Env,
+        caller: Address,
+        id: u32,
+        new_depositor: Address,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+        acquire_lock(&e)?;
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
+            release_lock(&e);
+            return Err(EscrowError::NotAuthorized);
+        }
+        if !is_active(&escrow) {
+            release_lock(&e);
+            return Err(EscrowError::AlreadyCompleted);
+        }
+        if resolve_role(&e, id, &new_depositor, &escrow) != Role::Stranger {
+            release_lock(&e);
+            return Err(EscrowError::InvalidBeneficiary);
+        }
+        if is_blocked(&e, &new_depositor) {
+            release_lock(&e);
+            return Err(EscrowError::Blocked);
+        }
+        escrow.beneficiary.require_auth();
+
+        index_remove(&e, &(sym_by_depositor(), caller.clone()), id);
+        index_add(&e, &(sym_by_depositor(), new_depositor.clone()), id);
+        escrow.depositor = new_depositor.clone();
+        // The old depositor's stake in a crowdfunded escrow goes with the rights
+        if let Some(index) = escrow.contributions.iter().position(|(addr, _)| addr == caller) {
+            let (_, amount) = escrow.contributions.get(index as u32).unwrap();
+            escrow.contributions.remove(index as u32);
+            add_contribution(&mut escrow, &new_depositor, amount);
+        }
+        store_escrow(&e, id, &escrow, &caller);
+
+        if escrow.private_events {
+            DepositorTransferredMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            DepositorTransferred {
+                id,
+                from: caller.clone(),
+                to: new_depositor,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "transfer_depositor_rights", None);
+        Ok(())
+    }
+
+    /// Any party flags the escrow for fraud review; the report itself lives off-chain
+    pub fn report(
+        e: Env,
+        caller: Address,
+        id: u32,
+        report_hash: BytesN<32>,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        let role = resolve_role(&e, id, &caller, &escrow);
+        if role == Role::Stranger {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if !escrow.reported {
+            escrow.reported = true;
+            store_escrow(&e, id, &escrow, &caller);
+        }
+
+        // Carries the reporter's role rather than address so private escrows stay private
+        FraudReported {
             id,
-            depositor: depositor.clone(),
-            beneficiary: beneficiary.clone(),
-            amount: total_amount,
+            reporter: role,
+            report_hash,
+            seq: next_event_seq(&e),
         }
         .publish(&e);
+        log_action(&e, id, &caller, "report", None);
+        Ok(())
+    }
 
-        release_lock(&e);
-        Ok(id)
+    /// Depositor or beneficiary anchors a KYC/attestor proof hash; the PII itself stays off-chain
+    pub fn anchor_attestation(
+        e: Env,
+        caller: Address,
+        id: u32,
+        hash: BytesN<32>,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let escrow = load_escrow(&e, id)?;
+
+        let role = resolve_role(&e, id, &caller, &escrow);
+        if role != Role::Depositor && role != Role::Beneficiary {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        let key = attestation_key(id, &caller);
+        e.storage().persistent().set(&key, &hash);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        if escrow.private_events {
+            AttestationAnchoredMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            AttestationAnchored {
+                id,
+                party: caller.clone(),
+                hash,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+        log_action(&e, id, &caller, "anchor_attestation", None);
+        Ok(())
     }
 
-    /// Beneficiary marks work as started (blocks refunds)
-    pub fn start_work(e: Env, caller: Address, id: u32) -> Result<(), EscrowError> {
+    /// Either party proposes the next revision of the off-chain contract document
+    pub fn propose_document(
+        e: Env,
+        caller: Address,
+        id: u32,
+        hash: BytesN<32>,
+    ) -> Result<u32, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let escrow = load_escrow(&e, id)?;
+
+        let role = resolve_role(&e, id, &caller, &escrow);
+        if role != Role::Depositor && role != Role::Beneficiary {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        let mut documents = load_documents(&e, id);
+
+        // An unacknowledged proposal is replaced rather than stacked
+        if let Some(last) = documents.last() {
+            if !last.acknowledged {
+                documents.pop_back();
+            }
+        }
+
+        let version = documents.len() + 1;
+        documents.push_back(DocumentRevision {
+            version,
+            hash: hash.clone(),
+            proposed_by: caller.clone(),
+            proposed_at: e.ledger().timestamp(),
+            acknowledged: false,
+        });
+        store_documents(&e, id, &documents);
+
+        DocumentProposed { id, version, hash, seq: next_event_seq(&e) }.publish(&e);
+        log_action(&e, id, &caller, "propose_document", None);
+        Ok(version)
+    }
+
+    /// Counterparty signs off on the pending document revision
+    pub fn acknowledge_document(
+        e: Env,
+        caller: Address,
+        id: u32,
+        version: u32,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let escrow = load_escrow(&e, id)?;
+
+        let role = resolve_role(&e, id, &caller, &escrow);
+        if role != Role::Depositor && role != Role::Beneficiary {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        let mut documents = load_documents(&e, id);
+        let mut pending = match documents.last() {
+            Some(last) if !last.acknowledged && last.version == version => last,
+            _ => return Err(EscrowError::NotAuthorized),
+        };
+
+        if pending.proposed_by == caller {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        pending.acknowledged = true;
+        documents.set(documents.len() - 1, pending);
+        store_documents(&e, id, &documents);
+
+        DocumentAcknowledged { id, version, seq: next_event_seq(&e) }.publish(&e);
+        log_action(&e, id, &caller, "acknowledge_document", None);
+        Ok(())
+    }
+
+    /// Depositor or beneficiary quotes a forward rate for a submitted milestone; when the other
+    /// party quotes the same rate it is locked for payout. Returns whether the rate is now locked.
+    pub fn lock_rate(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+        rate: i128,
+    ) -> Result<bool, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let escrow = load_escrow(&e, id)?;
+
+        let role = resolve_role(&e, id, &caller, &escrow);
+        if role != Role::Depositor && role != Role::Beneficiary {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if milestone_index >= escrow.milestones.len() {
+            return Err(EscrowError::InvalidMilestone);
+        }
+
+        if escrow.milestones.get(milestone_index).unwrap().status != MilestoneStatus::Submitted {
+            return Err(EscrowError::MilestoneNotSubmitted);
+        }
+
+        if rate <= 0 {
+            return Err(EscrowError::ZeroAmount);
+        }
+
+        let key = rate_lock_key(id, milestone_index);
+        let existing: Option<RateLock> = e.storage().persistent().get(&key);
+        let lock = match existing {
+            Some(lock) if lock.locked_at.is_some() => return Err(EscrowError::AlreadyCompleted),
+            Some(lock) if lock.rate == rate && lock.proposed_by != caller => RateLock {
+                locked_at: Some(e.ledger().timestamp()),
+                ..lock
+            },
+            // A fresh quote replaces any unmatched one
+            _ => RateLock {
+                rate,
+                proposed_by: caller.clone(),
+                locked_at: None,
+            },
+        };
+        e.storage().persistent().set(&key, &lock);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        let locked = lock.locked_at.is_some();
+        if locked {
+            RateLocked {
+                id,
+                milestone_index,
+                rate,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+        log_action(&e, id, &caller, "lock_rate", Some(milestone_index));
+        Ok(locked)
+    }
+
+    pub fn get_rate_lock(e: Env, id: u32, milestone_index: u32) -> Option<RateLock> {
+        e.storage().persistent().get(&rate_lock_key(id, milestone_index))
+    }
+
+    /// Depositor or beneficiary puts a document on record for a disputed milestone; only its
+    /// hash is kept, in the dispute history
+    pub fn add_evidence(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+        evidence_hash: BytesN<32>,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let escrow = load_escrow(&e, id)?;
+
+        let role = resolve_role(&e, id, &caller, &escrow);
+        if role != Role::Depositor && role != Role::Beneficiary {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        let milestone = escrow
+            .milestones
+            .get(milestone_index)
+            .ok_or(EscrowError::InvalidMilestone)?;
+        if milestone.status != MilestoneStatus::Disputed {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        record_dispute(
+            &e,
+            id,
+            milestone_index,
+            DisputeAction::EvidenceAdded(evidence_hash),
+            &caller,
+            0,
+        );
+        log_action(&e, id, &caller, "add_evidence", Some(milestone_index));
+        Ok(())
+    }
+
+    /// Depositor or beneficiary proposes a split for a disputed milestone
+    pub fn offer_settlement(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+        pay_to_beneficiary: i128,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let escrow = load_escrow(&e, id)?;
+
+        let role = resolve_role(&e, id, &caller, &escrow);
+        if role != Role::Depositor && role != Role::Beneficiary {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if milestone_index >= escrow.milestones.len() {
+            return Err(EscrowError::InvalidMilestone);
+        }
+
+        let milestone = escrow.milestones.get(milestone_index).unwrap();
+
+        if milestone.status != MilestoneStatus::Disputed {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if pay_to_beneficiary < 0 || pay_to_beneficiary > milestone.amount {
+            return Err(EscrowError::InvalidMilestone);
+        }
+
+        // A new offer from either side replaces any earlier one
+        let key = offer_key(id, milestone_index);
+        let offer = SettlementOffer {
+            offered_by: caller.clone(),
+            pay_to_beneficiary,
+        };
+        e.storage().persistent().set(&key, &offer);
+        e.storage().persistent().extend_ttl(&key, 0u32, COUNTER_TTL_SECS);
+
+        record_dispute(
+            &e,
+            id,
+            milestone_index,
+            DisputeAction::SettlementOffered,
+            &caller,
+            pay_to_beneficiary,
+        );
+
+        if escrow.private_events {
+            SettlementOfferedMinimal { id, milestone_index, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            SettlementOffered {
+                id,
+                milestone_index,
+                offered_by: caller.clone(),
+                pay_to_beneficiary,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+
+        log_action(&e, id, &caller, "offer_settlement", Some(milestone_index));
+        Ok(())
+    }
+
+    /// Counterparty accepts the standing offer, settling the milestone without the arbiter
+    pub fn accept_settlement(
+        e: Env,
+        caller: Address,
+        id: u32,
+        milestone_index: u32,
+        pay_to_beneficiary: i128,
+    ) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
         caller.require_auth();
         acquire_lock(&e)?;
 
         let mut escrow = load_escrow(&e, id)?;
 
-        if caller != escrow.beneficiary {
+        let role = resolve_role(&e, id, &caller, &escrow);
+        if role != Role::Depositor && role != Role::Beneficiary {
             release_lock(&e);
             return Err(EscrowError::NotAuthorized);
         }
 
-        if escrow.work_started {
+        let key = offer_key(id, milestone_index);
+        let offer: SettlementOffer = match e.storage().persistent().get(&key) {
+            Some(offer) => offer,
+            None => {
+                release_lock(&e);
+                return Err(EscrowError::SettlementNotFound);
+            }
+        };
+
+        if offer.offered_by == caller {
             release_lock(&e);
-            return Err(EscrowError::WorkStarted);
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        // Guards against the offer being swapped out just before acceptance
+        if offer.pay_to_beneficiary != pay_to_beneficiary {
+            release_lock(&e);
+            return Err(EscrowError::SettlementNotFound);
+        }
+
+        let refund = match apply_ruling(&e, id, &mut escrow, milestone_index, pay_to_beneficiary, None) {
+            Ok(refund) => refund,
+            Err(err) => {
+                release_lock(&e);
+                return Err(err);
+            }
+        };
+
+        pay_ruling(&e, id, &escrow, pay_to_beneficiary, refund)?;
+        escrow.paid_amount += pay_to_beneficiary;
+        refresh_status(&mut escrow, e.ledger().timestamp());
+
+        store_escrow(&e, id, &escrow, &caller);
+        if escrow.status == EscrowStatus::Released {
+            complete_escrow(&e, id, &escrow, &caller)?;
+        }
+        e.storage().persistent().remove(&key);
+        record_dispute(
+            &e,
+            id,
+            milestone_index,
+            DisputeAction::Settled,
+            &caller,
+            pay_to_beneficiary,
+        );
+
+        if escrow.private_events {
+            SettlementAcceptedMinimal { id, milestone_index, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            SettlementAccepted {
+                id,
+                milestone_index,
+                pay_to_beneficiary,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
+        }
+
+        release_lock(&e);
+        log_action(&e, id, &caller, "accept_settlement", Some(milestone_index));
+        Ok(())
+    }
+
+    /// Arbiter blocks approvals and refunds while investigating fraud (once per escrow, auto-expires)
+    pub fn freeze(e: Env, caller: Address, id: u32) -> Result<u64, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Arbiter {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if !is_active(&escrow) {
+            return Err(EscrowError::AlreadyCompleted);
+        }
+
+        // Only one freeze per escrow so it can never be chained into a permanent lock
+        if escrow.frozen_until != 0 {
+            return Err(EscrowError::Frozen);
+        }
+
+        let until = e.ledger().timestamp().saturating_add(MAX_FREEZE_PERIOD);
+        escrow.frozen_until = until;
+        store_escrow(&e, id, &escrow, &caller);
+
+        PayoutsFrozen { id, until, seq: next_event_seq(&e) }.publish(&e);
+        log_action(&e, id, &caller, "freeze", None);
+        Ok(until)
+    }
+
+    /// Arbiter pushes the deadline back while a dispute is open, up to 30 days in total
+    pub fn extend_deadline(
+        e: Env,
+        caller: Address,
+        id: u32,
+        extra_secs: u64,
+    ) -> Result<u64, EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Arbiter
+            || escrow.status != EscrowStatus::Disputed
+        {
+            return Err(EscrowError::NotAuthorized);
         }
 
-        if escrow.status != EscrowStatus::Pending {
-            release_lock(&e);
-            return Err(EscrowError::AlreadyCompleted);
+        let extended = escrow.deadline_extended.saturating_add(extra_secs);
+        if extra_secs == 0 || extended > MAX_DEADLINE_EXTENSION {
+            return Err(EscrowError::InvalidDuration);
         }
 
-        escrow.work_started = true;
-        escrow.status = EscrowStatus::InProgress;
-        store_escrow(&e, id, &escrow);
+        escrow.deadline_extended = extended;
+        escrow.deadline = escrow.deadline.saturating_add(extra_secs);
+        store_escrow(&e, id, &escrow, &caller);
 
-        let now = e.ledger().timestamp();
-        WorkStarted {
+        DeadlineExtended {
             id,
-            started_at: now,
+            deadline: escrow.deadline,
+            seq: next_event_seq(&e),
         }
         .publish(&e);
-
-        release_lock(&e);
-        Ok(())
+        log_action(&e, id, &caller, "extend_deadline", None);
+        Ok(escrow.deadline)
     }
 
-    /// Beneficiary submits milestone for review (no payment yet)
-    pub fn submit_milestone(
-        e: Env,
-        caller: Address,
-        id: u32,
-        milestone_index: u32,
-    ) -> Result<(), EscrowError> {
+    /// Depositor adds funds to an in-progress escrow; the extra goes on the final milestone
+    pub fn top_up(e: Env, caller: Address, id: u32, amount: i128) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
         caller.require_auth();
         acquire_lock(&e)?;
 
         let mut escrow = load_escrow(&e, id)?;
 
-        if caller != escrow.beneficiary {
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
             release_lock(&e);
             return Err(EscrowError::NotAuthorized);
         }
-
         if escrow.status != EscrowStatus::InProgress {
             release_lock(&e);
-            return Err(EscrowError::NotAuthorized);
+            return Err(EscrowError::AlreadyCompleted);
         }
-
-        if milestone_index >= escrow.milestones.len() {
+        if amount <= 0 {
             release_lock(&e);
-            return Err(EscrowError::InvalidMilestone);
+            return Err(EscrowError::ZeroAmount);
+        }
+        if escrow.mode != PayoutMode::Milestones {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
         }
 
-        let mut milestone = escrow.milestones.get(milestone_index).unwrap();
-        
-        if milestone.status != MilestoneStatus::NotStarted {
+        // Raising a pay-as-you-go milestone that is still unfunded only adds to the commitment
+        let deposit = escrow.funding != FundingMode::PerMilestone || unfunded_amount(&escrow) == 0;
+        let last = escrow.milestones.len() - 1;
+        let mut milestone = escrow.milestones.get(last).unwrap();
+        if milestone.status == MilestoneStatus::Approved {
             release_lock(&e);
-            return Err(EscrowError::MilestoneAlreadySubmitted);
+            return Err(EscrowError::InvalidMilestone);
         }
+        milestone.amount = milestone.amount.checked_add(amount)
+            .ok_or(EscrowError::InvalidMilestone)?;
+        escrow.milestones.set(last, milestone);
+        escrow.total_amount += amount;
 
-        let now = e.ledger().timestamp();
-        milestone.status = MilestoneStatus::Submitted;
-        milestone.submitted_at = Some(now);
-        escrow.milestones.set(milestone_index, milestone);
+        if let Some(bounds) = load_amount_bounds(&e, &escrow.token) {
+            let amounts = Vec::from_iter(&e, escrow.milestones.iter().map(|m| m.amount));
+            check_amount_bounds(&bounds, &amounts, escrow.total_amount)?;
+        }
 
-        store_escrow(&e, id, &escrow);
+        if deposit {
+            if escrow.funding != FundingMode::Upfront {
+                add_contribution(&mut escrow, &caller, amount);
+            }
+            safe_transfer(&e, &escrow.token, &caller, &e.current_contract_address(), &amount)?;
+        }
+        store_escrow(&e, id, &escrow, &caller);
 
-        MilestoneSubmitted {
-            id,
-            milestone_index,
+        if escrow.private_events {
+            EscrowToppedUpMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            EscrowToppedUp {
+                id,
+                amount,
+                total_amount: escrow.total_amount,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
         }
-        .publish(&e);
 
         release_lock(&e);
+        log_action(&e, id, &caller, "top_up", None);
+        Ok(escrow.total_amount)
+    }
+
+    /// Arbiter lifts the freeze early once the investigation is done
+    pub fn unfreeze(e: Env, caller: Address, id: u32) -> Result<(), EscrowError> {
+        ensure_not_paused(&e)?;
+        caller.require_auth();
+
+        let mut escrow = load_escrow(&e, id)?;
+
+        if resolve_role(&e, id, &caller, &escrow) != Role::Arbiter {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        if !is_frozen(&e, &escrow) {
+            return Err(EscrowError::NotAuthorized);
+        }
+
+        escrow.frozen_until = e.ledger().timestamp();
+        store_escrow(&e, id, &escrow, &caller);
+
+        PayoutsUnfrozen { id, seq: next_event_seq(&e) }.publish(&e);
+        log_action(&e, id, &caller, "unfreeze", None);
         Ok(())
     }
 
-    /// Client approves milestone (triggers payment)
-    pub fn approve_milestone(
+    /// Client walks away after work has started but before anything was submitted. The
+    /// beneficiary keeps the kill fee agreed at creation and the rest is refunded.
+    pub fn cancel(
         e: Env,
         caller: Address,
         id: u32,
-        milestone_index: u32,
-    ) -> Result<(), EscrowError> {
+        reason: Option<BytesN<32>>,
+    ) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
         caller.require_auth();
         acquire_lock(&e)?;
 
         let mut escrow = load_escrow(&e, id)?;
 
-        if caller != escrow.depositor {
+        if resolve_role(&e, id, &caller, &escrow) != Role::Depositor {
             release_lock(&e);
             return Err(EscrowError::NotAuthorized);
         }
 
-        if milestone_index >= escrow.milestones.len() {
+        if is_frozen(&e, &escrow) {
             release_lock(&e);
-            return Err(EscrowError::InvalidMilestone);
+            return Err(EscrowError::Frozen);
         }
 
-        let mut milestone = escrow.milestones.get(milestone_index).unwrap();
-        
-        if milestone.status != MilestoneStatus::Submitted {
+        if escrow.status != EscrowStatus::InProgress {
             release_lock(&e);
-            return Err(EscrowError::MilestoneNotSubmitted);
+            return Err(EscrowError::AlreadyCompleted);
         }
 
-        let now = e.ledger().timestamp();
-        milestone.status = MilestoneStatus::Approved;
-        milestone.approved_at = Some(now);
-        
-        let amount = milestone.amount;
-        escrow.milestones.set(milestone_index, milestone);
-        escrow.paid_amount += amount;
+        // Running streams and vesting schedules are stopped through a dispute instead
+        if escrow.mode != PayoutMode::Milestones {
+            release_lock(&e);
+            return Err(EscrowError::WrongMode);
+        }
 
-        store_escrow(&e, id, &escrow);
+        if escrow.milestones.iter().any(|m| m.status != MilestoneStatus::NotStarted) {
+            release_lock(&e);
+            return Err(EscrowError::MilestoneAlreadySubmitted);
+        }
 
-        // Transfer payment
-        let tf_res = safe_transfer(
-            &e,
-            &escrow.token,
-            &e.current_contract_address(),
-            &escrow.beneficiary,
-            &amount,
-        );
+        let fee = kill_fee(&escrow);
+        let bonus = escrow.bonus_held;
+        let principal = escrow.total_amount - unfunded_amount(&escrow) - fee;
+        let refund_amount = principal + bonus;
+        escrow.status = EscrowStatus::Refunded;
+        escrow.close_reason = reason.clone();
+        escrow.bonus_held = 0;
+        store_escrow(&e, id, &escrow, &caller);
 
-        if tf_res.is_err() {
-            release_lock(&e);
-            return Err(EscrowError::TransferFailed);
+        if fee > 0 {
+            pay_beneficiary(&e, id, &escrow, fee, None)?;
+        }
+        if refund_amount > 0 {
+            refund_depositor(&e, &escrow, principal)?;
+            if bonus > 0 {
+                safe_transfer(
+                    &e,
+                    &escrow.token,
+                    &e.current_contract_address(),
+                    &escrow.depositor,
+                    &bonus,
+                )?;
+            }
+            publish_refund(&e, id, &escrow, refund_amount, unapproved_milestones(&e, &escrow));
         }
 
-        MilestoneApproved {
-            id,
-            milestone_index,
-            amount,
+        if escrow.private_events {
+            EscrowCancelledMinimal { id, seq: next_event_seq(&e) }.publish(&e);
+        } else {
+            EscrowCancelled {
+                id,
+                kill_fee: fee,
+                seq: next_event_seq(&e),
+            }
+            .publish(&e);
         }
-        .publish(&e);
 
         release_lock(&e);
-        Ok(())
+        log_action_with_reason(&e, id, &caller, "cancel", None, reason);
+        Ok(fee)
     }
 
-    /// Client disputes milestone quality
-    pub fn dispute_milestone(
-        e: Env,
-        caller: Address,
-        id: u32,
-        milestone_index: u32,
-    ) -> Result<(), EscrowError> {
-        caller.require_auth();
-        acquire_lock(&e)?;
-
+    /// Client and beneficiary agree to end the escrow early: every milestone not yet paid is
+    /// refunded, including any in dispute. Both must sign. Returns the amount refunded.
+    pub fn mutual_cancel(e: Env, id: u32, reason: Option<BytesN<32>>) -> Result<i128, EscrowError> {
+        ensure_not_paused(&e)?;
         let mut escrow = load_escrow(&e, id)?;
+        escrow.depositor.require_auth();
+        escrow.beneficiary.require_auth();
+        acquire_lock(&e)?;
 
-        if caller != escrow.depositor {
+        if !matches!(escrow.status, EscrowStatus::InProgress | EscrowStatus::Disputed) {
             release_lock(&e);
-            return Err(EscrowError::NotAuthorized);
+            return Err(EscrowError::AlreadyCompleted);
         }
-
-        if milestone_index >= escrow.milestones.len() {
+        if is_frozen(&e, &escrow) {
             release_lock(&e);
-            return Err(EscrowError::InvalidMilestone);
+            return Err(EscrowError::Frozen);
         }
 
-        let mut milestone = escrow.milestones.get(milestone_index).unwrap();
-        
-        if milestone.status != MilestoneStatus::Submitted {
-            release_lock(&e);
-            return Err(EscrowError::MilestoneNotSubmitted);
+        let refunded = unapproved_milestones(&e, &escrow);
+        let bonus = escrow.bonus_held;
+        let principal = outstanding_amount(&escrow) - unfunded_amount(&escrow);
+        for index in refunded.iter() {
+            let mut milestone = escrow.milestones.get(index).unwrap();
+            milestone.status = MilestoneStatus::Refunded;
+            escrow.milestones.set(index, milestone);
         }
+        refresh_status(&mut escrow, e.ledger().timestamp());
+        escrow.close_reason = reason.clone();
+        escrow.bonus_held = 0;
+        let actor = escrow.depositor.clone();
+        store_escrow(&e, id, &escrow, &actor);
 
-        milestone.status = MilestoneStatus::Disputed;
-        escrow.milestones.set(milestone_index, milestone);
-        escrow.status = EscrowStatus::Disputed;
-
-        store_escrow(&e, id, &escrow);
+        // The bonus always came from the depositor, even on a crowdfunded escrow
+        refund_depositor(&e, &escrow, principal)?;
+        if bonus > 0 {
+            safe_transfer(&e, &escrow.token, &e.current_contract_address(), &escrow.depositor, &bonus)?;
+        }
+        publish_refund(&e, id, &escrow, principal + bonus, refunded);
 
         release_lock(&e);
-        Ok(())
+        log_action_with_reason(&e, id, &actor, "mutual_cancel", None, reason);
+        Ok(principal + bonus)
     }
 
-    /// Arbiter resolves disputed milestone
-    pub fn resolve_milestone_dispute(
+    /// Client can only refund BEFORE work starts; `reason` anchors why the escrow ended
+    pub fn refund(
         e: Env,
         caller: Address,
         id: u32,
-        milestone_index: u32,
-        pay_to_beneficiary: i128,
+        reason: Option<BytesN<32>>,
     ) -> Result<(), EscrowError> {
         caller.require_auth();
         acquire_lock(&e)?;
 
         let mut escrow = load_escrow(&e, id)?;
 
-        if caller != escrow.arbiter {
+        if let Err(err) = check_refund(&e, id, &escrow, &caller) {
             release_lock(&e);
-            return Err(EscrowError::NotAuthorized);
+            return Err(err);
         }
 
-        if milestone_index >= escrow.milestones.len() {
-            release_lock(&e);
-            return Err(EscrowError::InvalidMilestone);
-        }
+        escrow.status = EscrowStatus::Refunded;
+        escrow.close_reason = reason.clone();
+        let bonus = escrow.bonus_held;
+        let principal = escrow.total_amount - escrow.paid_amount - unfunded_amount(&escrow);
+        let refund_amount = principal + bonus;
+        escrow.bonus_held = 0;
+        store_escrow(&e, id, &escrow, &caller);
 
-        let mut milestone = escrow.milestones.get(milestone_index).unwrap();
-        
-        if milestone.status != MilestoneStatus::Disputed {
+        // The bonus always came from the depositor, even on a crowdfunded escrow
+        let tf_res = refund_depositor(&e, &escrow, principal).and_then(|_| {
+            if bonus == 0 {
+                return Ok(());
+            }
+            safe_transfer(&e, &escrow.token, &e.current_contract_address(), &escrow.depositor, &bonus)
+        });
+
+        if tf_res.is_err() {
             release_lock(&e);
-            return Err(EscrowError::NotAuthorized);
+            return Err(EscrowError::TransferFailed);
         }
+        publish_refund(&e, id, &escrow, refund_amount, unapproved_milestones(&e, &escrow));
+
+        release_lock(&e);
+        log_action_with_reason(&e, id, &caller, "refund", None, reason);
+        Ok(())
+    }
 
-        let milestone_amount = milestone.amount;
+    pub fn get_escrow(e: Env, id: u32) -> Result<EscrowData, EscrowError> {
+        load_escrow(&e, id)
+    }
 
-        if pay_to_beneficiary < 0 || pay_to_beneficiary > milestone_amount {
-            release_lock(&e);
-            return Err(EscrowError::InvalidMilestone);
-        }
+    pub fn exists(e: Env, id: u32) -> bool {
+        e.storage().persistent().has(&escrow_key(id))
+    }
 
-        // Pay beneficiary their portion
-        if pay_to_beneficiary > 0 {
-            safe_transfer(
-                &e,
-                &escrow.token,
-                &e.current_contract_address(),
-                &escrow.beneficiary,
-                &pay_to_beneficiary,
-            )?;
-            escrow.paid_amount += pay_to_beneficiary;
+    /// Current status without reading the escrow record
+    pub fn get_status(e: Env, id: u32) -> Result<EscrowStatus, EscrowError> {
+        e.storage()
+            .persistent()
+            .get(&status_key(id))
+            .ok_or(EscrowError::EscrowNotFound)
+    }
+
+    /// Several escrows in one call, `None` for ids that don't exist
+    pub fn get_escrows(e: Env, ids: Vec<u32>) -> Vec<Option<EscrowData>> {
+        let mut escrows = Vec::new(&e);
+        for id in ids.iter() {
+            escrows.push_back(load_escrow(&e, id).ok());
         }
+        escrows
+    }
 
-        // Refund depositor the rest
-        let refund = milestone_amount - pay_to_beneficiary;
-        if refund > 0 {
-            safe_transfer(
-                &e,
-                &escrow.token,
-                &e.current_contract_address(),
-                &escrow.depositor,
-                &refund,
-            )?;
+    pub fn get_milestone(e: Env, id: u32, milestone_index: u32) -> Result<Milestone, EscrowError> {
+        let escrow = load_escrow(&e, id)?;
+        escrow.milestones.get(milestone_index).ok_or(EscrowError::InvalidMilestone)
+    }
+
+    pub fn get_milestone_count(e: Env, id: u32) -> Result<u32, EscrowError> {
+        Ok(load_escrow(&e, id)?.milestones.len())
+    }
+
+    /// Parties, totals, status and milestone counts, without the milestones themselves
+    pub fn get_summary(e: Env, id: u32) -> Result<EscrowSummary, EscrowError> {
+        let escrow = load_escrow(&e, id)?;
+        Ok(summarize(&escrow, e.ledger().timestamp()))
+    }
+
+    /// Escrowed funds not yet paid out or returned
+    pub fn get_remaining(e: Env, id: u32) -> Result<i128, EscrowError> {
+        let escrow = load_escrow(&e, id)?;
+        Ok(funding_status(&escrow).held)
+    }
+
+    /// Approved milestones and amounts against the escrow's totals
+    pub fn get_progress(e: Env, id: u32) -> Result<Progress, EscrowError> {
+        let escrow = load_escrow(&e, id)?;
+        let mut progress = Progress {
+            approved_milestones: 0,
+            total_milestones: escrow.milestones.len(),
+            approved_amount: 0,
+            total_amount: escrow.total_amount,
+        };
+        for milestone in escrow.milestones.iter() {
+            if milestone.status == MilestoneStatus::Approved {
+                progress.approved_milestones += 1;
+                progress.approved_amount += milestone.amount;
+            }
         }
+        Ok(progress)
+    }
 
-        milestone.status = MilestoneStatus::Approved;
-        escrow.milestones.set(milestone_index, milestone);
-        escrow.status = EscrowStatus::InProgress;
+    /// Secs until the effective deadline; 0 once it has passed
+    pub fn time_remaining(e: Env, id: u32) -> Result<u64, EscrowError> {
+        let escrow = load_escrow(&e, id)?;
+        let now = e.ledger().timestamp();
+        Ok(effective_deadline(&escrow, now).saturating_sub(now))
+    }
 
-        store_escrow(&e, id, &escrow);
+    /// Breakdown of deposited, paid, refunded and still-held funds
+    pub fn get_funding_status(e: Env, id: u32) -> Result<FundingStatus, EscrowError> {
+        let escrow = load_escrow(&e, id)?;
+        Ok(funding_status(&escrow))
+    }
 
-        release_lock(&e);
-        Ok(())
+    /// Ids of escrows `addr` funded; oldest first unless one was handed to a new depositor
+    pub fn get_escrows_by_depositor(e: Env, addr: Address, offset: u32, limit: u32) -> Vec<u32> {
+        index_page(&e, &(sym_by_depositor(), addr), offset, limit)
     }
 
-    /// Client can only refund BEFORE work starts
-    pub fn refund(e: Env, caller: Address, id: u32) -> Result<(), EscrowError> {
-        caller.require_auth();
-        acquire_lock(&e)?;
+    /// Ids of escrows `addr` has started work on and is still the beneficiary of
+    pub fn get_escrows_by_beneficiary(e: Env, addr: Address, offset: u32, limit: u32) -> Vec<u32> {
+        index_page(&e, &(sym_by_beneficiary(), addr), offset, limit)
+    }
 
-        let mut escrow = load_escrow(&e, id)?;
+    /// Ids of escrows `addr` currently arbitrates
+    pub fn get_escrows_by_arbiter(e: Env, addr: Address, offset: u32, limit: u32) -> Vec<u32> {
+        index_page(&e, &(sym_by_arbiter(), addr), offset, limit)
+    }
 
-        if caller != escrow.depositor {
-            release_lock(&e);
-            return Err(EscrowError::NotAuthorized);
-        }
+    /// Indexes of the escrow's milestones currently in dispute
+    pub fn get_open_disputes(e: Env, id: u32) -> Result<Vec<u32>, EscrowError> {
+        Ok(disputed_milestones(&e, &load_escrow(&e, id)?))
+    }
 
-        if escrow.work_started {
-            release_lock(&e);
-            return Err(EscrowError::WorkStarted);
-        }
+    /// Disputed milestones across all escrows, awaiting a ruling or settlement
+    pub fn count_open_disputes(e: Env) -> u32 {
+        count_open_disputes(&e)
+    }
 
-        if escrow.status != EscrowStatus::Pending {
-            release_lock(&e);
-            return Err(EscrowError::AlreadyCompleted);
-        }
+    /// The arbiter's work queue: escrows it arbitrates that have an open dispute
+    pub fn get_disputes_by_arbiter(e: Env, addr: Address, offset: u32, limit: u32) -> Vec<u32> {
+        index_page(&e, &(sym_arbiter_disputes(), addr), offset, limit)
+    }
+
+    /// Ids of escrows denominated in `token`, oldest first
+    pub fn get_escrows_by_token(e: Env, token: Address, offset: u32, limit: u32) -> Vec<u32> {
+        index_page(&e, &(sym_by_token(), token), offset, limit)
+    }
+
+    /// Ids of escrows currently pending, in progress or disputed. Released and refunded escrows
+    /// are not listed; find them through the party and token indexes
+    pub fn list_escrows(e: Env, status: EscrowStatus, offset: u32, limit: u32) -> Vec<u32> {
+        index_page(&e, &(sym_by_status(), status), offset, limit)
+    }
 
+    /// Aggregated open obligations between `a` and `b`, whichever side deposited
+    pub fn get_open_between(e: Env, a: Address, b: Address) -> PairSummary {
         let now = e.ledger().timestamp();
-        if now >= escrow.deadline {
-            release_lock(&e);
-            return Err(EscrowError::NotAuthorized);
+        let mut summary = PairSummary {
+            count: 0,
+            total_locked: 0,
+            next_deadline: None,
+        };
+
+        // Both directions, or just the one when `a` and `b` are the same address
+        let directions = if a == b { 1 } else { 2 };
+        let pairs = [(sym_by_pair(), a.clone(), b.clone()), (sym_by_pair(), b, a)];
+        let open = pairs[..directions].iter().flat_map(|pair| {
+            (0..index_len(&e, pair))
+                .step_by(MAX_PAGE_SIZE as usize)
+                .flat_map(|from| index_page(&e, pair, from, MAX_PAGE_SIZE))
+        });
+        for id in open {
+            let Ok(escrow) = load_escrow(&e, id) else {
+                continue;
+            };
+
+            let deadline = effective_deadline(&escrow, now);
+            summary.count += 1;
+            summary.total_locked += outstanding_amount(&escrow);
+            summary.next_deadline = Some(summary.next_deadline.map_or(deadline, |d| d.min(deadline)));
         }
+        summary
+    }
 
-        escrow.status = EscrowStatus::Refunded;
-        store_escrow(&e, id, &escrow);
+    /// Heuristic risk signals for wallets to surface before locking more funds
+    pub fn get_risk_score(e: Env, id: u32) -> Result<RiskReport, EscrowError> {
+        let escrow = load_escrow(&e, id)?;
+        Ok(compute_risk(&e, &escrow))
+    }
 
-        let refund_amount = escrow.total_amount - escrow.paid_amount;
-        let tf_res = safe_transfer(
-            &e,
-            &escrow.token,
-            &e.current_contract_address(),
-            &escrow.depositor,
-            &refund_amount,
-        );
+    /// Identity attestation hash anchored by `party`, if any
+    pub fn get_attestation(e: Env, id: u32, party: Address) -> Option<BytesN<32>> {
+        e.storage().persistent().get(&attestation_key(id, &party))
+    }
 
-        if tf_res.is_err() {
-            release_lock(&e);
-            return Err(EscrowError::TransferFailed);
-        }
+    pub fn get_documents(e: Env, id: u32) -> Vec<DocumentRevision> {
+        load_documents(&e, id)
+    }
 
-        release_lock(&e);
-        Ok(())
+    /// Latest revision both parties have acknowledged
+    pub fn get_current_document(e: Env, id: u32) -> Option<BytesN<32>> {
+        load_documents(&e, id)
+            .iter()
+            .rev()
+            .find(|d| d.acknowledged)
+            .map(|d| d.hash)
     }
 
-    pub fn get_escrow(e: Env, id: u32) -> Result<EscrowData, EscrowError> {
-        load_escrow(&e, id)
+    /// Completed escrows and full-payment streak for a beneficiary
+    pub fn get_completion_record(e: Env, addr: Address) -> CompletionRecord {
+        load_completion_record(&e, &addr)
+    }
+
+    /// Trust signal: disputes opened, won and lost (and rulings issued, for arbiters)
+    pub fn get_dispute_stats(e: Env, addr: Address) -> DisputeStats {
+        load_dispute_stats(&e, &addr)
+    }
+
+    /// Audit log of mutating calls on the escrow, oldest first; the last 100 are kept
+    pub fn get_history(e: Env, id: u32, offset: u32, limit: u32) -> Vec<HistoryEntry> {
+        page(&load_escrow_history(&e, id), offset, limit)
+    }
+
+    pub fn get_dispute_history(e: Env, id: u32, milestone_index: u32) -> Vec<DisputeEntry> {
+        load_dispute_history(&e, id, milestone_index)
+    }
+
+    pub fn get_settlement_offer(e: Env, id: u32, milestone_index: u32) -> Option<SettlementOffer> {
+        e.storage().persistent().get(&offer_key(id, milestone_index))
+    }
+
+    pub fn get_role(e: Env, caller: Address, id: u32) -> Result<Role, EscrowError> {
+        let escrow = load_escrow(&e, id)?;
+        Ok(resolve_role(&e, id, &caller, &escrow))
+    }
+
+    /// Sequence number of the most recently published event
+    pub fn last_event_seq(e: Env) -> u64 {
+        e.storage().instance().get(&sym_event_seq()).unwrap_or(0)
+    }
+
+    /// Whether `caller` could refund the escrow right now
+    pub fn can_refund(e: Env, id: u32, caller: Address) -> Capability {
+        capability(&e, id, |escrow| check_refund(&e, id, escrow, &caller))
+    }
+
+    /// Whether `caller` could submit the milestone right now
+    pub fn can_submit(e: Env, id: u32, caller: Address, milestone_index: u32) -> Capability {
+        capability(&e, id, |escrow| {
+            ensure_not_paused(&e)?;
+            check_submit(&e, id, escrow, &caller, milestone_index)
+        })
+    }
+
+    /// Whether `caller` could approve the milestone right now
+    pub fn can_approve(e: Env, id: u32, caller: Address, milestone_index: u32) -> Capability {
+        capability(&e, id, |escrow| {
+            ensure_not_paused(&e)?;
+            check_approve(&e, id, escrow, &caller, milestone_index)
+        })
+    }
+
+    /// Stable short name for an `EscrowError` code, for SDKs in any language
+    pub fn describe_error(e: Env, code: u32) -> Symbol {
+        Symbol::new(&e, error_name(code))
+    }
+
+    pub fn get_arbiter_pool(e: Env) -> Vec<Address> {
+        load_arbiter_pool(&e)
     }
 
     pub fn next_id(e: Env) -> Result<u32, EscrowError> {
         peek_next_id(&e)
     }
+
+    pub fn total_escrows(e: Env) -> u32 {
+        escrow_count(&e)
+    }
+
+    /// Lowest id that may still be pending, in progress or disputed; everything below it is
+    /// settled
+    pub fn first_active_id(e: Env) -> Option<u32> {
+        first_active_id(&e)
+    }
+
+    /// Inclusive range of ids a scanner must visit to see every live escrow, or `None` if
+    /// there are none
+    pub fn id_range(e: Env) -> Option<(u32, u32)> {
+        first_active_id(&e).map(|first| (first, escrow_count(&e)))
+    }
 }
\ No newline at end of file
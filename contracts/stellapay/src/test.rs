@@ -1,17 +1,40 @@
 #![cfg(test)]
 
-use crate::{EscrowContract, EscrowContractClient, EscrowError, EscrowStatus, MilestoneStatus};
+use crate::{
+    AdminAction, AmountBounds, ClaimState, Config, CreateParams, DisputeAction, PenaltySchedule, DisputeStats, EscrowContract, EscrowContractClient, EscrowData, EscrowDataV1, EscrowError, EscrowStatus,
+    FundingMode, HistoryEntry, MilestoneStatus, MilestoneV1, PayoutMode, PayoutTail, PlatformStats, Progress, ProjectSummary,
+    RequestStatus, Role, ScheduleState, UserStats, VestingSchedule,
+};
 use soroban_sdk::{
-    testutils::{Address as _, Ledger},
-    token, Address, Env, Vec,
+    contract, contractimpl, symbol_short,
+    testutils::{storage::Persistent as _, Address as _, Events, Ledger},
+    token, vec, Address, BytesN, Env, Map, Symbol, TryFromVal, Val, Vec,
 };
 
+fn default_config(fee_recipient: &Address) -> Config {
+    Config {
+        fee_bps: 0,
+        fee_recipient: fee_recipient.clone(),
+        min_duration: 3600,
+        max_duration: 365 * 24 * 3600,
+        dispute_period: 7 * 24 * 3600,
+        timelock_delay: 0,
+        premium_bps: 0,
+        ttl_buffer: 30 * 24 * 3600,
+        create_limit: 0,
+        create_window: 0,
+        payout_cosign: false,
+    }
+}
+
 struct TestFixture<'a> {
     env: Env,
     depositor: Address,
     beneficiary: Address,
     arbiter: Address,
+    admin: Address,
     token: token::Client<'a>,
+    #[allow(dead_code)]
     token_admin: token::StellarAssetClient<'a>,
     contract_id: Address,
     client: EscrowContractClient<'a>,
@@ -32,7 +55,8 @@ impl<'a> TestFixture<'a> {
         let token_admin = token::StellarAssetClient::new(&env, &token_address);
         token_admin.mint(&depositor, &100_000);
 
-        let contract_id = env.register(EscrowContract, ());
+        let admin = Address::generate(&env);
+        let contract_id = env.register(EscrowContract, (admin.clone(), default_config(&admin)));
         let client = EscrowContractClient::new(&env, &contract_id);
 
         Self {
@@ -40,6 +64,7 @@ impl<'a> TestFixture<'a> {
             depositor,
             beneficiary,
             arbiter,
+            admin,
             token,
             token_admin,
             contract_id,
@@ -47,6 +72,51 @@ impl<'a> TestFixture<'a> {
         }
     }
 
+    fn hash(&self, byte: u8) -> BytesN<32> {
+        BytesN::from_array(&self.env, &[byte; 32])
+    }
+
+    /// Pushes a config change through the admin timelock
+    fn set_config(&self, config: &Config) {
+        let action_id = self
+            .client
+            .propose_action(&AdminAction::SetConfig(config.clone()));
+        self.env.ledger().with_mut(|li| li.timestamp += config.timelock_delay);
+        self.client.execute_action(&action_id);
+    }
+
+    /// Default terms between the fixture parties; override fields with struct update syntax
+    fn params(&self, milestone_amounts: &Vec<i128>) -> CreateParams {
+        CreateParams {
+            depositor: self.depositor.clone(),
+            beneficiary: self.beneficiary.clone(),
+            arbiter: Some(self.arbiter.clone()),
+            milestone_amounts: milestone_amounts.clone(),
+            token: self.token.address.clone(),
+            duration: 7200,
+            private_events: false,
+            checklists: Vec::new(&self.env),
+            review_period: None,
+            resubmit_cooldown: None,
+            submission_cutoff: None,
+            governance: None,
+            insured: false,
+            milestone_deadlines: Vec::new(&self.env),
+            penalty: PenaltySchedule::default(),
+            bonus: 0,
+            kill_fee_bps: 0,
+            referrer: None,
+            referral_bps: 0,
+            mode: PayoutMode::Milestones,
+            splits: Vec::new(&self.env),
+            milestone_recipients: Vec::new(&self.env),
+            funding: FundingMode::Upfront,
+            transferable: false,
+            project: None,
+            payout_tail: PayoutTail::default(),
+        }
+    }
+
     fn create_milestone_amounts(&self, amounts: &[i128]) -> Vec<i128> {
         let mut vec = Vec::new(&self.env);
         for amount in amounts {
@@ -63,14 +133,7 @@ fn test_create_escrow_with_milestones() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[500, 1000, 1500]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     assert_eq!(id, 1);
     assert_eq!(f.token.balance(&f.contract_id), 3000);
@@ -87,14 +150,7 @@ fn test_milestone_submit_and_approve_flow() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[500, 1000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     
@@ -105,7 +161,7 @@ fn test_milestone_submit_and_approve_flow() {
     assert_eq!(f.token.balance(&f.beneficiary), 0); // Not paid yet
     
     // Client approves milestone 0
-    f.client.approve_milestone(&f.depositor, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
     assert_eq!(f.token.balance(&f.beneficiary), 500); // Now paid
     
     let escrow = f.client.get_escrow(&id);
@@ -119,16 +175,9 @@ fn test_refund_before_work_starts() {
     let milestones = f.create_milestone_amounts(&[1000]);
     let initial = f.token.balance(&f.depositor);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
-    f.client.refund(&f.depositor, &id);
+    f.client.refund(&f.depositor, &id, &None);
     
     let escrow = f.client.get_escrow(&id);
     assert_eq!(escrow.status, EscrowStatus::Refunded);
@@ -140,14 +189,7 @@ fn test_dispute_and_resolution() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[1000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     f.client.submit_milestone(&f.beneficiary, &id, &0);
@@ -160,7 +202,11 @@ fn test_dispute_and_resolution() {
     assert_eq!(escrow.milestones.get(0).unwrap().status, MilestoneStatus::Disputed);
     
     // Arbiter decides: 70% quality, pay 700
-    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &700);
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &700, &f.hash(1));
+    assert_eq!(
+        f.client.get_escrow(&id).milestones.get(0).unwrap().rationale_hash,
+        Some(f.hash(1))
+    );
     
     assert_eq!(f.token.balance(&f.beneficiary), 700);
     assert_eq!(f.token.balance(&f.depositor), 100_000 - 1000 + 300); // Got 300 refund
@@ -173,18 +219,11 @@ fn test_cannot_refund_after_work_starts() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[1000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     
-    let result = f.client.try_refund(&f.depositor, &id);
+    let result = f.client.try_refund(&f.depositor, &id, &None);
     
     assert!(result.is_err());
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::WorkStarted);
@@ -195,14 +234,7 @@ fn test_only_beneficiary_can_submit_milestone() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[1000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     
@@ -218,20 +250,13 @@ fn test_only_depositor_can_approve_milestone() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[1000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     f.client.submit_milestone(&f.beneficiary, &id, &0);
     
     // Beneficiary tries to approve their own work
-    let result = f.client.try_approve_milestone(&f.beneficiary, &id, &0);
+    let result = f.client.try_approve_milestone(&f.beneficiary, &id, &0, &None);
     
     assert!(result.is_err());
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
@@ -242,19 +267,12 @@ fn test_cannot_approve_unsubmitted_milestone() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[1000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     
     // Try to approve without submission
-    let result = f.client.try_approve_milestone(&f.depositor, &id, &0);
+    let result = f.client.try_approve_milestone(&f.depositor, &id, &0, &None);
     
     assert!(result.is_err());
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::MilestoneNotSubmitted);
@@ -265,14 +283,7 @@ fn test_cannot_submit_milestone_twice() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[1000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     f.client.submit_milestone(&f.beneficiary, &id, &0);
@@ -289,14 +300,7 @@ fn test_cannot_dispute_unsubmitted_milestone() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[1000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     
@@ -312,21 +316,14 @@ fn test_only_arbiter_can_resolve_dispute() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[1000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     f.client.submit_milestone(&f.beneficiary, &id, &0);
     f.client.dispute_milestone(&f.depositor, &id, &0);
     
     // Depositor tries to resolve
-    let result = f.client.try_resolve_milestone_dispute(&f.depositor, &id, &0, &500);
+    let result = f.client.try_resolve_milestone_dispute(&f.depositor, &id, &0, &500, &f.hash(1));
     
     assert!(result.is_err());
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
@@ -337,14 +334,7 @@ fn test_empty_milestones_error() {
     let f = TestFixture::new();
     let milestones = Vec::new(&f.env);
     
-    let result = f.client.try_create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let result = f.client.try_create(&f.params(&milestones));
     
     assert!(result.is_err());
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidMilestone);
@@ -355,21 +345,14 @@ fn test_invalid_arbiter_dispute_resolution_amount() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[1000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     f.client.submit_milestone(&f.beneficiary, &id, &0);
     f.client.dispute_milestone(&f.depositor, &id, &0);
     
     // Arbiter tries to pay more than milestone amount
-    let result = f.client.try_resolve_milestone_dispute(&f.arbiter, &id, &0, &1500);
+    let result = f.client.try_resolve_milestone_dispute(&f.arbiter, &id, &0, &1500, &f.hash(1));
     
     assert!(result.is_err());
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidMilestone);
@@ -383,14 +366,7 @@ fn test_full_successful_workflow() {
     let milestones = f.create_milestone_amounts(&[1000, 2000, 1500]);
     let initial_depositor = f.token.balance(&f.depositor);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     assert_eq!(f.token.balance(&f.depositor), initial_depositor - 4500);
     
@@ -398,17 +374,17 @@ fn test_full_successful_workflow() {
     
     // Milestone 1: Submit and approve
     f.client.submit_milestone(&f.beneficiary, &id, &0);
-    f.client.approve_milestone(&f.depositor, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
     assert_eq!(f.token.balance(&f.beneficiary), 1000);
     
     // Milestone 2: Submit and approve
     f.client.submit_milestone(&f.beneficiary, &id, &1);
-    f.client.approve_milestone(&f.depositor, &id, &1);
+    f.client.approve_milestone(&f.depositor, &id, &1, &None);
     assert_eq!(f.token.balance(&f.beneficiary), 3000);
     
     // Milestone 3: Submit and approve
     f.client.submit_milestone(&f.beneficiary, &id, &2);
-    f.client.approve_milestone(&f.depositor, &id, &2);
+    f.client.approve_milestone(&f.depositor, &id, &2, &None);
     assert_eq!(f.token.balance(&f.beneficiary), 4500);
     
     let escrow = f.client.get_escrow(&id);
@@ -420,20 +396,13 @@ fn test_mixed_approval_and_dispute() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[1000, 1000, 1000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     
     // Milestone 1: Approve (good quality)
     f.client.submit_milestone(&f.beneficiary, &id, &0);
-    f.client.approve_milestone(&f.depositor, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
     assert_eq!(f.token.balance(&f.beneficiary), 1000);
     
     // Milestone 2: Dispute (poor quality)
@@ -441,12 +410,12 @@ fn test_mixed_approval_and_dispute() {
     f.client.dispute_milestone(&f.depositor, &id, &1);
     
     // Arbiter: 50% quality, pay 500
-    f.client.resolve_milestone_dispute(&f.arbiter, &id, &1, &500);
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &1, &500, &f.hash(1));
     assert_eq!(f.token.balance(&f.beneficiary), 1500);
     
     // Milestone 3: Approve (good quality again)
     f.client.submit_milestone(&f.beneficiary, &id, &2);
-    f.client.approve_milestone(&f.depositor, &id, &2);
+    f.client.approve_milestone(&f.depositor, &id, &2, &None);
     assert_eq!(f.token.balance(&f.beneficiary), 2500);
     
     // Client got 500 refund from milestone 2
@@ -459,14 +428,7 @@ fn test_client_protection_scenario() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[5000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     
@@ -477,7 +439,7 @@ fn test_client_protection_scenario() {
     f.client.dispute_milestone(&f.depositor, &id, &0);
     
     // Arbiter reviews and decides: 0% quality, full refund
-    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &0);
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &0, &f.hash(1));
     
     // Client gets full refund
     assert_eq!(f.token.balance(&f.depositor), 100_000);
@@ -489,19 +451,12 @@ fn test_freelancer_protection_scenario() {
     let f = TestFixture::new();
     let milestones = f.create_milestone_amounts(&[5000]);
     
-    let id = f.client.create(
-        &f.depositor,
-        &f.beneficiary,
-        &f.arbiter,
-        &milestones,
-        &f.token.address,
-        &7200,
-    );
+    let id = f.client.create(&f.params(&milestones));
     
     f.client.start_work(&f.beneficiary, &id);
     
     // Once work starts, client CANNOT refund
-    let result = f.client.try_refund(&f.depositor, &id);
+    let result = f.client.try_refund(&f.depositor, &id, &None);
     assert!(result.is_err());
     assert_eq!(result.unwrap_err().unwrap(), EscrowError::WorkStarted);
     
@@ -510,4 +465,3894 @@ fn test_freelancer_protection_scenario() {
     
     // Client must either approve or dispute (with arbiter resolution)
     // Cannot just walk away with money
-}
\ No newline at end of file
+}
+// ==================== RISK SCORE TESTS ====================
+
+#[test]
+fn test_risk_score_clean_escrow() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    let report = f.client.get_risk_score(&id);
+    assert_eq!(report.score, 0);
+    assert!(!report.overdue);
+    assert!(!report.shortfall);
+}
+
+#[test]
+fn test_risk_score_flags_overdue_and_stalled_dispute() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+
+    // Arbiter and client stay silent well past the review period
+    f.env.ledger().with_mut(|li| li.timestamp += 8 * 24 * 3600);
+
+    let report = f.client.get_risk_score(&id);
+    assert_eq!(report.disputed_milestones, 1);
+    assert_eq!(report.stalled_disputes, 1);
+    assert_eq!(report.stale_reviews, 1);
+    assert!(!report.overdue); // Deadline is paused during the dispute
+    assert!(!report.shortfall);
+    assert_eq!(report.score, 15 + 10 + 10);
+
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &500, &f.hash(1));
+    f.env.ledger().with_mut(|li| li.timestamp += 7200);
+
+    let report = f.client.get_risk_score(&id);
+    assert!(report.overdue);
+    assert_eq!(report.score, 10 + 25);
+}
+
+// ==================== ARBITER POOL TESTS ====================
+
+#[test]
+fn test_create_without_arbiter_picks_pool_arbiter() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let second = Address::generate(&f.env);
+
+    f.client.register_arbiter(&f.arbiter);
+    f.client.register_arbiter(&second);
+    f.client.register_arbiter(&f.arbiter); // Idempotent
+    assert_eq!(f.client.get_arbiter_pool().len(), 2);
+
+    let id = f.client.create(&CreateParams {
+        arbiter: None,
+        ..f.params(&milestones)
+    });
+
+    let escrow = f.client.get_escrow(&id);
+    assert!(escrow.arbiter == f.arbiter || escrow.arbiter == second);
+}
+
+#[test]
+fn test_pool_arbiter_never_a_counterparty() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    // Only the parties themselves are in the pool
+    f.client.register_arbiter(&f.depositor);
+    f.client.register_arbiter(&f.beneficiary);
+
+    let result = f.client.try_create(&CreateParams {
+        arbiter: None,
+        ..f.params(&milestones)
+    });
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidArbiter);
+
+    f.client.register_arbiter(&f.arbiter);
+    f.client.unregister_arbiter(&f.depositor);
+    let id = f.client.create(&CreateParams {
+        arbiter: None,
+        ..f.params(&milestones)
+    });
+    assert_eq!(f.client.get_escrow(&id).arbiter, f.arbiter);
+}
+
+// ==================== ROLE TESTS ====================
+
+#[test]
+fn test_get_role_for_each_party() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    assert_eq!(f.client.get_role(&f.depositor, &id), Role::Depositor);
+    assert_eq!(f.client.get_role(&f.beneficiary, &id), Role::Beneficiary);
+    assert_eq!(f.client.get_role(&f.arbiter, &id), Role::Arbiter);
+    assert_eq!(f.client.get_role(&Address::generate(&f.env), &id), Role::Stranger);
+}
+
+#[test]
+fn test_delegate_reviews_and_observer_only_watches() {
+    let f = TestFixture::new();
+    let delegate = Address::generate(&f.env);
+    let observer = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+    let id = f.client.create(&f.params(&milestones));
+
+    let result = f.client.try_grant_role(&f.beneficiary, &id, &delegate, &Role::Delegate);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+    let result = f.client.try_grant_role(&f.depositor, &id, &f.arbiter, &Role::Observer);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidBeneficiary);
+    let result = f.client.try_grant_role(&f.depositor, &id, &delegate, &Role::Arbiter);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidConfig);
+
+    f.client.grant_role(&f.depositor, &id, &delegate, &Role::Delegate);
+    f.client.grant_role(&f.depositor, &id, &observer, &Role::Observer);
+    assert_eq!(f.client.get_role(&delegate, &id), Role::Delegate);
+    assert_eq!(f.client.get_role(&observer, &id), Role::Observer);
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    let result = f.client.try_approve_milestone(&observer, &id, &0, &None);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+    let result = f.client.try_approve_milestone(&delegate, &id, &0, &Some(50));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+    f.client.approve_milestone(&delegate, &id, &0, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+
+    // A delegate still cannot take the depositor's other decisions
+    let result = f.client.try_top_up(&delegate, &id, &100);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    f.client.revoke_role(&f.depositor, &id, &delegate);
+    assert_eq!(f.client.get_role(&delegate, &id), Role::Stranger);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    let result = f.client.try_request_revision(&delegate, &id, &1);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+}
+
+// ==================== BATCH RESOLUTION TESTS ====================
+
+#[test]
+fn test_resolve_multiple_disputes_in_one_call() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 2000, 500]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &id);
+    for index in 0..3u32 {
+        f.client.submit_milestone(&f.beneficiary, &id, &index);
+    }
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+    f.client.dispute_milestone(&f.depositor, &id, &1);
+    f.client.approve_milestone(&f.depositor, &id, &2, &None);
+
+    let mut rulings = Vec::new(&f.env);
+    rulings.push_back((0u32, 600i128));
+    rulings.push_back((1u32, 2000i128));
+    f.client.resolve_multiple(&f.arbiter, &id, &rulings, &f.hash(1));
+
+    assert_eq!(f.token.balance(&f.beneficiary), 500 + 600 + 2000);
+    assert_eq!(f.token.balance(&f.depositor), 100_000 - 3500 + 400);
+
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.status, EscrowStatus::Released);
+    assert_eq!(escrow.paid_amount, 3100);
+}
+
+#[test]
+fn test_resolve_multiple_is_atomic() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+    f.client.dispute_milestone(&f.depositor, &id, &1);
+
+    // Second ruling exceeds the milestone amount, so nothing is settled
+    let mut rulings = Vec::new(&f.env);
+    rulings.push_back((0u32, 500i128));
+    rulings.push_back((1u32, 5000i128));
+    let result = f.client.try_resolve_multiple(&f.arbiter, &id, &rulings, &f.hash(1));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidMilestone);
+
+    // Duplicate index is rejected as well
+    let mut rulings = Vec::new(&f.env);
+    rulings.push_back((0u32, 500i128));
+    rulings.push_back((0u32, 500i128));
+    let result = f.client.try_resolve_multiple(&f.arbiter, &id, &rulings, &f.hash(1));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.status, EscrowStatus::Disputed);
+    assert_eq!(f.token.balance(&f.beneficiary), 0);
+}
+
+// ==================== REVISION COOLDOWN TESTS ====================
+
+#[test]
+fn test_resubmission_blocked_during_cooldown() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&CreateParams {
+        duration: 30 * 24 * 3600,
+        ..f.params(&milestones)
+    });
+
+    f.client.set_resubmit_cooldown(&f.depositor, &id, &(24 * 3600));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.request_revision(&f.depositor, &id, &0);
+
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.milestones.get(0).unwrap().status, MilestoneStatus::NotStarted);
+
+    // Instant resubmission is rejected
+    let result = f.client.try_submit_milestone(&f.beneficiary, &id, &0);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::SubmissionCooldown);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 24 * 3600);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+}
+
+#[test]
+fn test_cooldown_fixed_once_work_starts() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    let result = f.client.try_set_resubmit_cooldown(&f.depositor, &id, &(8 * 24 * 3600));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidDuration);
+
+    f.client.start_work(&f.beneficiary, &id);
+    let result = f.client.try_set_resubmit_cooldown(&f.depositor, &id, &3600);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WorkStarted);
+
+    // Without a cooldown the beneficiary may resubmit immediately
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.request_revision(&f.depositor, &id, &0);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+}
+
+// ==================== SETTLEMENT TESTS ====================
+
+#[test]
+fn test_parties_settle_dispute_without_arbiter() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+
+    f.client.offer_settlement(&f.depositor, &id, &0, &400);
+    // Beneficiary counters; the standing offer is replaced
+    f.client.offer_settlement(&f.beneficiary, &id, &0, &800);
+    let offer = f.client.get_settlement_offer(&id, &0).unwrap();
+    assert_eq!(offer.offered_by, f.beneficiary);
+
+    // Offerer cannot accept their own offer
+    let result = f.client.try_accept_settlement(&f.beneficiary, &id, &0, &800);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    // Stale amount is rejected
+    let result = f.client.try_accept_settlement(&f.depositor, &id, &0, &400);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::SettlementNotFound);
+
+    f.client.accept_settlement(&f.depositor, &id, &0, &800);
+    assert_eq!(f.token.balance(&f.beneficiary), 800);
+    assert_eq!(f.token.balance(&f.depositor), 100_000 - 1000 + 200);
+    assert!(f.client.get_settlement_offer(&id, &0).is_none());
+
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.status, EscrowStatus::Released);
+    assert_eq!(escrow.milestones.get(0).unwrap().status, MilestoneStatus::Approved);
+}
+
+#[test]
+fn test_settlement_requires_open_dispute() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+
+    let result = f.client.try_offer_settlement(&f.depositor, &id, &0, &500);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    let result = f.client.try_accept_settlement(&f.beneficiary, &id, &0, &500);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::SettlementNotFound);
+}
+
+// ==================== CLOSE REASON TESTS ====================
+
+#[test]
+fn test_refund_records_close_reason() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    assert!(f.client.get_escrow(&id).close_reason.is_none());
+
+    let reason = BytesN::from_array(&f.env, &[7u8; 32]);
+    f.client.refund(&f.depositor, &id, &Some(reason.clone()));
+
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.status, EscrowStatus::Refunded);
+    assert_eq!(escrow.close_reason, Some(reason.clone()));
+    let last = f.client.get_history(&id, &1, &1).get(0).unwrap();
+    assert_eq!(last.action, Symbol::new(&f.env, "refund"));
+    assert_eq!(last.reason, Some(reason));
+}
+
+#[test]
+fn test_release_all_pays_every_open_milestone() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 500, 300]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+
+    let result = f.client.try_release_all(&f.beneficiary, &id, &None);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    assert_eq!(f.client.release_all(&f.depositor, &id, &Some(f.hash(5))), 800);
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.status, EscrowStatus::Released);
+    assert_eq!(escrow.close_reason, Some(f.hash(5)));
+    assert_eq!(f.token.balance(&f.beneficiary), 1800);
+    let history = f.client.get_history(&id, &0, &20);
+    let last = history.get(history.len() - 1).unwrap();
+    assert_eq!(last.action, Symbol::new(&f.env, "release_all"));
+    assert_eq!(last.reason, Some(f.hash(5)));
+}
+
+#[test]
+fn test_mutual_cancel_refunds_unpaid_milestones() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 500]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.dispute_milestone(&f.depositor, &id, &1);
+
+    assert_eq!(f.client.mutual_cancel(&id, &Some(f.hash(6))), 500);
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.status, EscrowStatus::Refunded);
+    assert_eq!(escrow.milestones.get(1).unwrap().status, MilestoneStatus::Refunded);
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+    assert_eq!(f.token.balance(&f.depositor), 99_000);
+    assert_eq!(f.client.count_open_disputes(), 0);
+    assert_eq!(f.client.get_completion_record(&f.beneficiary).completed, 0);
+    let history = f.client.get_history(&id, &0, &20);
+    assert_eq!(history.get(history.len() - 1).unwrap().reason, Some(f.hash(6)));
+}
+
+// ==================== FREEZE TESTS ====================
+
+#[test]
+fn test_freeze_blocks_approve_and_refund() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let refundable = f.client.create(&CreateParams {
+        duration: 30 * 24 * 3600,
+        ..f.params(&milestones)
+    });
+    let id = f.client.create(&CreateParams {
+        duration: 30 * 24 * 3600,
+        ..f.params(&milestones)
+    });
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+
+    let result = f.client.try_freeze(&f.depositor, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    f.client.freeze(&f.arbiter, &id);
+    f.client.freeze(&f.arbiter, &refundable);
+
+    let result = f.client.try_approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::Frozen);
+    let result = f.client.try_refund(&f.depositor, &refundable, &None);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::Frozen);
+
+    f.client.unfreeze(&f.arbiter, &id);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+
+    // The freeze is single-use
+    let result = f.client.try_freeze(&f.arbiter, &refundable);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::Frozen);
+}
+
+#[test]
+fn test_freeze_expires_automatically() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&CreateParams {
+        duration: 30 * 24 * 3600,
+        ..f.params(&milestones)
+    });
+
+    f.client.freeze(&f.arbiter, &id);
+    f.env.ledger().with_mut(|li| li.timestamp += 14 * 24 * 3600);
+
+    let result = f.client.try_unfreeze(&f.arbiter, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    f.client.refund(&f.depositor, &id, &None);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Refunded);
+}
+
+// ==================== ERROR REGISTRY TESTS ====================
+
+#[test]
+fn test_describe_error_names_are_stable() {
+    let f = TestFixture::new();
+
+    assert_eq!(
+        f.client.describe_error(&(EscrowError::NotAuthorized as u32)),
+        Symbol::new(&f.env, "NotAuthorized")
+    );
+    assert_eq!(
+        f.client.describe_error(&(EscrowError::Frozen as u32)),
+        Symbol::new(&f.env, "Frozen")
+    );
+    assert_eq!(f.client.describe_error(&0), Symbol::new(&f.env, "Unknown"));
+    assert_eq!(f.client.describe_error(&9999), Symbol::new(&f.env, "Unknown"));
+
+    // Every code up to the newest variant has a name
+    for code in 1..=(EscrowError::Frozen as u32) {
+        assert_ne!(f.client.describe_error(&code), Symbol::new(&f.env, "Unknown"));
+    }
+}
+
+// ==================== DISPUTE HISTORY TESTS ====================
+
+#[test]
+fn test_dispute_history_tracks_lifecycle() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+    f.client.dispute_milestone(&f.depositor, &id, &1);
+    f.client.offer_settlement(&f.beneficiary, &id, &0, &900);
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &600, &f.hash(1));
+    f.client.offer_settlement(&f.depositor, &id, &1, &300);
+    f.client.accept_settlement(&f.beneficiary, &id, &1, &300);
+
+    let history = f.client.get_dispute_history(&id, &0);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(0).unwrap().action, DisputeAction::Opened);
+    assert_eq!(history.get(0).unwrap().amount, 1000);
+    assert_eq!(history.get(1).unwrap().action, DisputeAction::SettlementOffered);
+    assert_eq!(history.get(1).unwrap().actor, f.beneficiary);
+    assert_eq!(history.get(2).unwrap().action, DisputeAction::Resolved);
+    assert_eq!(history.get(2).unwrap().amount, 600);
+
+    let history = f.client.get_dispute_history(&id, &1);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(2).unwrap().action, DisputeAction::Settled);
+    assert_eq!(history.get(2).unwrap().actor, f.beneficiary);
+
+    assert!(f.client.get_dispute_history(&id, &5).is_empty());
+}
+
+#[test]
+fn test_parties_add_evidence_to_dispute_history() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+
+    let result = f.client.try_add_evidence(&f.beneficiary, &id, &0, &f.hash(1));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+    f.client.add_evidence(&f.beneficiary, &id, &0, &f.hash(1));
+    f.client.add_evidence(&f.depositor, &id, &0, &f.hash(2));
+    let outsider = Address::generate(&f.env);
+    let result = f.client.try_add_evidence(&outsider, &id, &0, &f.hash(3));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    let history = f.client.get_dispute_history(&id, &0);
+    assert_eq!(history.len(), 3);
+    assert_eq!(history.get(1).unwrap().action, DisputeAction::EvidenceAdded(f.hash(1)));
+    assert_eq!(history.get(1).unwrap().actor, f.beneficiary);
+    assert_eq!(history.get(2).unwrap().action, DisputeAction::EvidenceAdded(f.hash(2)));
+    assert_eq!(history.get(2).unwrap().actor, f.depositor);
+}
+
+// ==================== PRIVATE EVENT TESTS ====================
+
+fn contract_event_names(f: &TestFixture) -> Vec<Symbol> {
+    let mut names = Vec::new(&f.env);
+    for (contract, topics, _) in f.env.events().all().iter() {
+        if contract == f.contract_id {
+            names.push_back(Symbol::try_from_val(&f.env, &topics.get(0).unwrap()).unwrap());
+        }
+    }
+    names
+}
+
+#[test]
+fn test_private_escrow_emits_minimal_events() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&CreateParams {
+        private_events: true,
+        ..f.params(&milestones)
+    });
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "escrow_created_minimal")]
+    );
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(
+        contract_event_names(&f),
+        vec![
+            &f.env,
+            Symbol::new(&f.env, "escrow_status_changed_minimal"),
+            Symbol::new(&f.env, "milestone_approved_minimal"),
+            Symbol::new(&f.env, "escrow_completed"),
+        ]
+    );
+
+    // Full data remains readable from storage
+    let escrow = f.client.get_escrow(&id);
+    assert!(escrow.private_events);
+    assert_eq!(escrow.paid_amount, 1000);
+}
+
+#[test]
+fn test_public_escrow_emits_full_events() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "escrow_created")]
+    );
+
+    // The payload carries everything an indexer needs
+    let (_, _, data) = f.env.events().all().last().unwrap();
+    let fields = Map::<Symbol, Val>::try_from_val(&f.env, &data).unwrap();
+    let field = |name: &str| fields.get(Symbol::new(&f.env, name)).unwrap();
+    assert_eq!(Address::try_from_val(&f.env, &field("arbiter")).unwrap(), f.arbiter);
+    assert_eq!(Address::try_from_val(&f.env, &field("token")).unwrap(), f.token.address);
+    assert_eq!(
+        Vec::<i128>::try_from_val(&f.env, &field("milestone_amounts")).unwrap(),
+        milestones
+    );
+    assert_eq!(
+        u64::try_from_val(&f.env, &field("deadline")).unwrap(),
+        f.client.get_escrow(&id).deadline
+    );
+}
+
+// ==================== COMPLETION TESTS ====================
+
+#[test]
+fn test_single_milestone_approval_completes_escrow() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[2500]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+
+    // The fast path leaves the same record and side effects as the general one
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.status, EscrowStatus::Released);
+    assert_eq!(escrow.paid_amount, 2500);
+    let milestone = escrow.milestones.get(0).unwrap();
+    assert_eq!(milestone.status, MilestoneStatus::Approved);
+    assert_eq!(milestone.approved_at, Some(f.env.ledger().timestamp()));
+    assert_eq!(f.client.get_status(&id), EscrowStatus::Released);
+    assert_eq!(f.client.get_completion_record(&f.beneficiary).completed, 1);
+    assert_eq!(f.client.first_active_id(), None);
+    assert_eq!(f.client.verify_solvency(&f.token.address), 0);
+    assert_eq!(f.token.balance(&f.beneficiary), 2500);
+    assert_eq!(f.token.balance(&f.contract_id), 0);
+}
+
+#[test]
+fn test_multi_milestone_escrow_completes_on_last_approval() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 500]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::InProgress);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.approve_milestone(&f.depositor, &id, &1, &None);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Released);
+}
+
+// ==================== RULING RATIONALE TESTS ====================
+
+#[test]
+fn test_batch_ruling_records_rationale_on_each_milestone() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+    f.client.dispute_milestone(&f.depositor, &id, &1);
+
+    let mut rulings = Vec::new(&f.env);
+    rulings.push_back((0u32, 100i128));
+    rulings.push_back((1u32, 900i128));
+    f.client.resolve_multiple(&f.arbiter, &id, &rulings, &f.hash(9));
+
+    assert_eq!(
+        contract_event_names(&f),
+        vec![
+            &f.env,
+            Symbol::new(&f.env, "dispute_resolved"),
+            Symbol::new(&f.env, "dispute_resolved"),
+            Symbol::new(&f.env, "escrow_status_changed"),
+            Symbol::new(&f.env, "escrow_completed"),
+        ]
+    );
+
+    let escrow = f.client.get_escrow(&id);
+    for milestone in escrow.milestones.iter() {
+        assert_eq!(milestone.rationale_hash, Some(f.hash(9)));
+    }
+}
+
+// ==================== GOVERNANCE APPEAL TESTS ====================
+
+#[contract]
+pub struct MockGovernance;
+
+#[contractimpl]
+impl MockGovernance {
+    pub fn set_verdict(env: Env, id: u32, milestone_index: u32, pay_to_beneficiary: i128) {
+        env.storage()
+            .instance()
+            .set(&(symbol_short!("verdict"), id, milestone_index), &pay_to_beneficiary);
+    }
+
+    pub fn get_verdict(
+        env: Env,
+        _escrow_contract: Address,
+        id: u32,
+        milestone_index: u32,
+    ) -> Option<i128> {
+        env.storage()
+            .instance()
+            .get(&(symbol_short!("verdict"), id, milestone_index))
+    }
+}
+
+#[test]
+fn test_appeal_escalates_to_governance_verdict() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let governance_id = f.env.register(MockGovernance, ());
+    let governance = MockGovernanceClient::new(&f.env, &governance_id);
+
+    let id = f.client.create(&f.params(&milestones));
+    f.client.set_governance(&f.depositor, &id, &governance_id);
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+    f.client.appeal(&f.beneficiary, &id, &0);
+
+    // The arbiter is out of the loop once appealed
+    let result = f.client.try_resolve_milestone_dispute(&f.arbiter, &id, &0, &500, &f.hash(1));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AppealPending);
+
+    // No verdict yet
+    let result = f.client.try_enforce_verdict(&id, &0);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AppealPending);
+
+    governance.set_verdict(&id, &0, &750);
+    f.client.enforce_verdict(&id, &0);
+
+    assert_eq!(f.token.balance(&f.beneficiary), 750);
+    assert_eq!(f.token.balance(&f.depositor), 100_000 - 1000 + 250);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Released);
+
+    let history = f.client.get_dispute_history(&id, &0);
+    assert_eq!(history.get(1).unwrap().action, DisputeAction::Appealed);
+    assert_eq!(history.get(2).unwrap().actor, governance_id);
+}
+
+#[test]
+fn test_appeal_requires_configured_governance() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+
+    let result = f.client.try_appeal(&f.beneficiary, &id, &0);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    let result = f.client.try_set_governance(&f.depositor, &id, &f.arbiter);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WorkStarted);
+}
+
+// ==================== DOCUMENT REVISION TESTS ====================
+
+#[test]
+fn test_document_revisions_need_counterparty_ack() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    assert_eq!(f.client.propose_document(&f.depositor, &id, &f.hash(1)), 1);
+    assert_eq!(f.client.get_current_document(&id), None);
+
+    // Proposer cannot acknowledge their own revision
+    let result = f.client.try_acknowledge_document(&f.depositor, &id, &1);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    f.client.acknowledge_document(&f.beneficiary, &id, &1);
+    assert_eq!(f.client.get_current_document(&id), Some(f.hash(1)));
+
+    // Amendment: an unacknowledged draft is replaced by a newer one
+    assert_eq!(f.client.propose_document(&f.beneficiary, &id, &f.hash(2)), 2);
+    assert_eq!(f.client.propose_document(&f.beneficiary, &id, &f.hash(3)), 2);
+    assert_eq!(f.client.get_current_document(&id), Some(f.hash(1)));
+
+    f.client.acknowledge_document(&f.depositor, &id, &2);
+    assert_eq!(f.client.get_current_document(&id), Some(f.hash(3)));
+    assert_eq!(f.client.get_documents(&id).len(), 2);
+
+    let result = f.client.try_propose_document(&f.arbiter, &id, &f.hash(4));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+}
+
+// ==================== STORAGE TTL TESTS ====================
+
+fn escrow_ttl(f: &TestFixture, id: u32) -> u32 {
+    f.env.as_contract(&f.contract_id, || {
+        f.env
+            .storage()
+            .persistent()
+            .get_ttl(&(symbol_short!("escrows"), id))
+    })
+}
+
+#[test]
+fn test_escrow_ttl_covers_review_window_past_deadline() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    f.env.ledger().with_mut(|li| li.timestamp = 1_700_000_000);
+
+    let id = f.client.create(&CreateParams {
+        duration: 3600,
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    // Submission just before the deadline opens a 7 day review window beyond it
+    f.env.ledger().with_mut(|li| li.timestamp += 3000);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    assert!(escrow_ttl(&f, id) as u64 * 5 >= 7 * 24 * 3600);
+
+    // Well past the deadline, the record is still live for the client to act
+    f.env.ledger().with_mut(|li| {
+        li.timestamp += 6 * 24 * 3600;
+        li.sequence_number += (6 * 24 * 3600) / 5;
+    });
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Released);
+}
+
+#[test]
+fn test_escrow_ttl_covers_freeze() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&CreateParams {
+        duration: 3600,
+        ..f.params(&milestones)
+    });
+    let before = escrow_ttl(&f, id);
+
+    f.client.freeze(&f.arbiter, &id);
+    let after = escrow_ttl(&f, id);
+    assert!(after > before);
+    assert!(after as u64 * 5 >= 14 * 24 * 3600);
+}
+
+// ==================== DISPUTE STATS TESTS ====================
+
+#[test]
+fn test_dispute_stats_per_address() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000, 1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &id);
+    for index in 0..3u32 {
+        f.client.submit_milestone(&f.beneficiary, &id, &index);
+    }
+    for index in 0..3u32 {
+        f.client.dispute_milestone(&f.depositor, &id, &index);
+    }
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &800, &f.hash(1));
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &1, &100, &f.hash(1));
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &2, &500, &f.hash(1));
+
+    let depositor = f.client.get_dispute_stats(&f.depositor);
+    assert_eq!(depositor.opened, 3);
+    assert_eq!(depositor.won, 1);
+    assert_eq!(depositor.lost, 1);
+
+    let beneficiary = f.client.get_dispute_stats(&f.beneficiary);
+    assert_eq!(beneficiary.opened, 0);
+    assert_eq!(beneficiary.won, 1);
+    assert_eq!(beneficiary.lost, 1);
+
+    assert_eq!(f.client.get_dispute_stats(&f.arbiter).arbitrated, 3);
+    assert_eq!(
+        f.client.get_dispute_stats(&Address::generate(&f.env)),
+        DisputeStats::default()
+    );
+}
+
+// ==================== REVIEW PERIOD TESTS ====================
+
+#[test]
+fn test_beneficiary_claims_after_review_period() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 500]);
+
+    let id = f.client.create(&CreateParams {
+        duration: 30 * 24 * 3600,
+        ..f.params(&milestones)
+    });
+    f.client.set_review_period(&f.depositor, &id, &(2 * 24 * 3600));
+    assert_eq!(f.client.get_escrow(&id).review_period, 2 * 24 * 3600);
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+
+    let result = f.client.try_claim_milestone(&f.beneficiary, &id, &0);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::DisputePeriodActive);
+
+    // Client stays silent for the whole window
+    f.env.ledger().with_mut(|li| li.timestamp += 2 * 24 * 3600);
+    f.client.claim_milestone(&f.beneficiary, &id, &0);
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.milestones.get(0).unwrap().status, MilestoneStatus::Approved);
+    assert_eq!(escrow.status, EscrowStatus::InProgress);
+}
+
+#[test]
+fn test_review_period_bounds() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+    assert_eq!(f.client.get_escrow(&id).review_period, 7 * 24 * 3600);
+
+    let result = f.client.try_set_review_period(&f.depositor, &id, &3600);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidDuration);
+    let result = f.client.try_set_review_period(&f.depositor, &id, &(31 * 24 * 3600));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidDuration);
+    let result = f.client.try_set_review_period(&f.beneficiary, &id, &(24 * 3600));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+}
+
+// ==================== FUNDING STATUS TESTS ====================
+
+#[test]
+fn test_funding_status_tracks_paid_refunded_and_held() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000, 1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    let status = f.client.get_funding_status(&id);
+    assert_eq!(status.funded, 3000);
+    assert_eq!(status.unfunded, 0);
+    assert_eq!(status.held, 3000);
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.dispute_milestone(&f.depositor, &id, &1);
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &1, &400, &f.hash(1));
+
+    let status = f.client.get_funding_status(&id);
+    assert_eq!(status.paid, 1400);
+    assert_eq!(status.refunded, 600);
+    assert_eq!(status.held, 1000);
+    assert_eq!(status.held, f.token.balance(&f.contract_id));
+}
+
+#[test]
+fn test_funding_status_after_refund() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 500]);
+
+    let id = f.client.create(&f.params(&milestones));
+    f.client.refund(&f.depositor, &id, &None);
+
+    let status = f.client.get_funding_status(&id);
+    assert_eq!(status.refunded, 1500);
+    assert_eq!(status.held, 0);
+    assert_eq!(status.paid, 0);
+}
+
+// ==================== DEADLINE PAUSE TESTS ====================
+
+#[test]
+fn test_deadline_paused_while_disputed() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+
+    let id = f.client.create(&CreateParams {
+        duration: 10 * 24 * 3600,
+        ..f.params(&milestones)
+    });
+    let deadline = f.client.get_escrow(&id).deadline;
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+
+    // Arbitration outlasts the original deadline, but the escrow is not overdue
+    f.env.ledger().with_mut(|li| li.timestamp += 12 * 24 * 3600);
+    assert!(!f.client.get_risk_score(&id).overdue);
+
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &500, &f.hash(1));
+
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.deadline, deadline + 12 * 24 * 3600);
+    assert_eq!(escrow.disputed_since, None);
+    assert!(!f.client.get_risk_score(&id).overdue);
+}
+
+// ==================== ACCEPTANCE CHECKLIST TESTS ====================
+
+#[test]
+fn test_approval_records_checklist_results() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 500]);
+    let checklists = vec![
+        &f.env,
+        vec![&f.env, f.hash(1), f.hash(2), f.hash(3)],
+        Vec::new(&f.env),
+    ];
+
+    let id = f.client.create(&CreateParams {
+        checklists,
+        ..f.params(&milestones)
+    });
+    assert_eq!(f.client.get_escrow(&id).milestones.get(0).unwrap().checklist.len(), 3);
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+
+    // One flag per checklist item is required
+    let result = f.client.try_approve_milestone_with_results(
+        &f.depositor,
+        &id,
+        &0,
+        &vec![&f.env, true, false],
+    );
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidMilestone);
+
+    let results = vec![&f.env, true, false, true];
+    f.client.approve_milestone_with_results(&f.depositor, &id, &0, &results);
+
+    let milestone = f.client.get_escrow(&id).milestones.get(0).unwrap();
+    assert_eq!(milestone.status, MilestoneStatus::Approved);
+    assert_eq!(milestone.checklist_results, Some(results));
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+}
+
+#[test]
+fn test_checklists_must_match_milestones() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 500]);
+
+    let result = f.client.try_create(&CreateParams {
+        checklists: vec![&f.env, vec![&f.env, f.hash(1)]],
+        ..f.params(&milestones)
+    });
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidMilestone);
+}
+
+// ==================== DEADLINE EXTENSION TESTS ====================
+
+#[test]
+fn test_arbiter_extends_deadline_only_while_disputed() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+    let deadline = f.client.get_escrow(&id).deadline;
+
+    f.client.start_work(&f.beneficiary, &id);
+    let result = f.client.try_extend_deadline(&f.arbiter, &id, &3600);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+
+    let result = f.client.try_extend_deadline(&f.depositor, &id, &3600);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    let extended = f.client.extend_deadline(&f.arbiter, &id, &(20 * 24 * 3600));
+    assert_eq!(extended, deadline + 20 * 24 * 3600);
+
+    // Extensions are capped at 30 days in total
+    let result = f.client.try_extend_deadline(&f.arbiter, &id, &(11 * 24 * 3600));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidDuration);
+    f.client.extend_deadline(&f.arbiter, &id, &(10 * 24 * 3600));
+    assert_eq!(f.client.get_escrow(&id).deadline_extended, 30 * 24 * 3600);
+}
+
+// ==================== SUBMISSION CUTOFF TESTS ====================
+
+#[test]
+fn test_submissions_blocked_in_cutoff_window() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[500, 500]);
+
+    let id = f.client.create(&CreateParams {
+        duration: 3 * 24 * 3600,
+        ..f.params(&milestones)
+    });
+
+    let result = f.client.try_set_submission_cutoff(&f.depositor, &id, &(8 * 24 * 3600));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidDuration);
+    f.client.set_submission_cutoff(&f.depositor, &id, &(12 * 3600));
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+
+    // 11 hours before the deadline falls inside the 12 hour cutoff
+    f.env.ledger().with_mut(|li| li.timestamp += 3 * 24 * 3600 - 11 * 3600);
+    let result = f.client.try_submit_milestone(&f.beneficiary, &id, &1);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::SubmissionWindowClosed);
+    assert_eq!(
+        f.client.describe_error(&(EscrowError::SubmissionWindowClosed as u32)),
+        Symbol::new(&f.env, "SubmissionWindowClosed")
+    );
+}
+
+// ==================== ATTESTATION TESTS ====================
+
+#[test]
+fn test_parties_anchor_identity_attestations() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.anchor_attestation(&f.beneficiary, &id, &f.hash(7));
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "attestation_anchored")]
+    );
+
+    assert_eq!(f.client.get_attestation(&id, &f.beneficiary), Some(f.hash(7)));
+    assert_eq!(f.client.get_attestation(&id, &f.depositor), None);
+
+    let result = f.client.try_anchor_attestation(&f.arbiter, &id, &f.hash(8));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+}
+
+// ==================== FRAUD REPORT TESTS ====================
+
+#[test]
+fn test_party_reports_fraud() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&CreateParams {
+        private_events: true,
+        ..f.params(&milestones)
+    });
+    assert!(!f.client.get_escrow(&id).reported);
+
+    let stranger = Address::generate(&f.env);
+    let result = f.client.try_report(&stranger, &id, &f.hash(9));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    f.client.report(&f.beneficiary, &id, &f.hash(9));
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "fraud_reported")]
+    );
+    assert!(f.client.get_escrow(&id).reported);
+}
+
+// ==================== ADMIN TESTS ====================
+
+#[test]
+fn test_admin_and_config_set_at_deployment() {
+    let f = TestFixture::new();
+
+    assert_eq!(f.client.get_admin(), f.admin);
+    assert_eq!(f.client.get_config(), default_config(&f.admin));
+    assert_eq!(f.client.get_schema_version(), crate::SCHEMA_VERSION);
+}
+
+#[test]
+#[should_panic]
+fn test_deployment_rejects_invalid_config() {
+    let f = TestFixture::new();
+    let config = Config { fee_bps: 10_001, ..default_config(&f.admin) };
+    f.env.register(EscrowContract, (f.admin.clone(), config));
+}
+
+#[test]
+fn test_config_change_applies_to_new_escrows() {
+    let f = TestFixture::new();
+    let mut config = default_config(&f.admin);
+
+    config.fee_bps = 5000;
+    let result = f.client.try_propose_action(&AdminAction::SetConfig(config.clone()));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidConfig);
+
+    config.fee_bps = 100;
+    config.min_duration = 24 * 3600;
+    config.dispute_period = 3 * 24 * 3600;
+    f.set_config(&config);
+
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let result = f.client.try_create(&f.params(&milestones));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidDuration);
+
+    let id = f.client.create(&CreateParams {
+        duration: 2 * 24 * 3600,
+        ..f.params(&milestones)
+    });
+    assert_eq!(f.client.get_escrow(&id).review_period, 3 * 24 * 3600);
+}
+
+#[test]
+fn test_two_step_admin_transfer() {
+    let f = TestFixture::new();
+    let new_admin = Address::generate(&f.env);
+
+    let result = f.client.try_accept_admin();
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    f.client.transfer_admin(&new_admin);
+    // Nomination alone does not hand over control
+    assert_eq!(f.client.get_admin(), f.admin);
+
+    f.client.accept_admin();
+    assert_eq!(f.client.get_admin(), new_admin);
+    assert!(f.client.try_accept_admin().is_err());
+}
+
+// ==================== BENEFICIARY ROTATION TESTS ====================
+
+#[test]
+fn test_beneficiary_rotation_after_joint_attestation() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&CreateParams {
+        duration: 30 * 24 * 3600,
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    let replacement = Address::generate(&f.env);
+    assert_eq!(f.client.attest_rotation(&f.depositor, &id, &replacement), 0);
+    let result = f.client.try_attest_rotation(&f.beneficiary, &id, &replacement);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    let ready_at = f.client.attest_rotation(&f.arbiter, &id, &replacement);
+    assert_eq!(ready_at, f.env.ledger().timestamp() + 7 * 24 * 3600);
+
+    let result = f.client.try_complete_rotation(&id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::RotationNotReady);
+
+    f.env.ledger().with_mut(|li| li.timestamp = ready_at);
+    f.client.complete_rotation(&id);
+    assert_eq!(f.client.get_escrow(&id).beneficiary, replacement);
+    assert_eq!(f.client.get_rotation(&id), None);
+
+    // The replacement key now drives the escrow
+    f.client.submit_milestone(&replacement, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&replacement), 1000);
+}
+
+#[test]
+fn test_beneficiary_rotation_drops_payout_address() {
+    let f = TestFixture::new();
+    let wallet = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.change_payout_address(&f.beneficiary, &id, &wallet);
+    f.client.start_work(&f.beneficiary, &id);
+
+    let replacement = Address::generate(&f.env);
+    f.client.attest_rotation(&f.depositor, &id, &replacement);
+    let ready_at = f.client.attest_rotation(&f.arbiter, &id, &replacement);
+    f.env.ledger().with_mut(|li| li.timestamp = ready_at);
+    f.client.complete_rotation(&id);
+    assert_eq!(f.client.get_payout_address(&id), replacement);
+
+    f.client.submit_milestone(&replacement, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&replacement), 1000);
+    assert_eq!(f.token.balance(&wallet), 0);
+}
+
+#[test]
+fn test_beneficiary_can_cancel_rotation() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let id = f.client.create(&f.params(&milestones));
+
+    let replacement = Address::generate(&f.env);
+    f.client.attest_rotation(&f.depositor, &id, &replacement);
+    f.client.attest_rotation(&f.arbiter, &id, &replacement);
+
+    f.client.cancel_rotation(&f.beneficiary, &id);
+    f.env.ledger().with_mut(|li| li.timestamp += 7 * 24 * 3600);
+
+    let result = f.client.try_complete_rotation(&id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::RotationNotReady);
+    assert_eq!(f.client.get_escrow(&id).beneficiary, f.beneficiary);
+}
+
+// ==================== PLATFORM FEE TESTS ====================
+
+#[test]
+fn test_platform_fee_locked_at_creation() {
+    let f = TestFixture::new();
+    let mut config = default_config(&f.admin);
+    config.fee_bps = 250;
+    f.set_config(&config);
+
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+    let id = f.client.create(&f.params(&milestones));
+
+    // Later config changes do not touch the existing agreement
+    config.fee_bps = 1000;
+    f.set_config(&config);
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 975);
+    assert_eq!(f.client.get_accrued_fees(&f.token.address), 25);
+
+    // Arbiter rulings carry the same fee on the beneficiary's share
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.dispute_milestone(&f.depositor, &id, &1);
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &1, &400, &f.hash(1));
+    assert_eq!(f.token.balance(&f.beneficiary), 975 + 390);
+    assert_eq!(f.client.get_accrued_fees(&f.token.address), 25 + 10);
+    assert_eq!(f.client.get_escrow(&id).paid_amount, 1400);
+}
+
+// ==================== PAIR SUMMARY TESTS ====================
+
+#[test]
+fn test_open_obligations_between_pair() {
+    let f = TestFixture::new();
+    let start = f.env.ledger().timestamp();
+
+    let first = f.client.create(&CreateParams {
+        duration: 10 * 24 * 3600,
+        ..f.params(&f.create_milestone_amounts(&[1000, 500]))
+    });
+    f.client.create(&CreateParams {
+        duration: 2 * 24 * 3600,
+        ..f.params(&f.create_milestone_amounts(&[300]))
+    });
+    let refunded = f.client.create(&CreateParams {
+        duration: 3600,
+        ..f.params(&f.create_milestone_amounts(&[700]))
+    });
+    f.client.refund(&f.depositor, &refunded, &None);
+
+    f.client.start_work(&f.beneficiary, &first);
+    f.client.submit_milestone(&f.beneficiary, &first, &0);
+    f.client.approve_milestone(&f.depositor, &first, &0, &None);
+
+    // Order of the pair does not matter
+    let summary = f.client.get_open_between(&f.beneficiary, &f.depositor);
+    assert_eq!(summary.count, 2);
+    assert_eq!(summary.total_locked, 500 + 300);
+    assert_eq!(summary.next_deadline, Some(start + 2 * 24 * 3600));
+
+    let empty = f.client.get_open_between(&f.depositor, &f.arbiter);
+    assert_eq!(empty.count, 0);
+    assert_eq!(empty.next_deadline, None);
+}
+
+#[test]
+fn test_fee_tier_reduces_fee_at_payout() {
+    let f = TestFixture::new();
+    let mut config = default_config(&f.admin);
+    config.fee_bps = 500;
+    f.set_config(&config);
+
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+
+    // Tier granted after creation still applies, since it is checked at payout time
+    f.client.set_fee_tier(&f.depositor, &100);
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "fee_tier_set")]
+    );
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.client.get_accrued_fees(&f.token.address), 10);
+
+    f.client.remove_fee_tier(&f.depositor);
+    assert_eq!(f.client.get_fee_tier(&f.depositor), None);
+    f.client.approve_milestone(&f.depositor, &id, &1, &None);
+    assert_eq!(f.client.get_accrued_fees(&f.token.address), 10 + 50);
+    assert_eq!(f.token.balance(&f.beneficiary), 990 + 950);
+}
+
+// ==================== CREATE PARAMS TESTS ====================
+
+#[test]
+fn test_create_params_set_optional_policies() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let governance = Address::generate(&f.env);
+
+    let id = f.client.create(&CreateParams {
+        review_period: Some(2 * 24 * 3600),
+        resubmit_cooldown: Some(3600),
+        submission_cutoff: Some(1800),
+        governance: Some(governance.clone()),
+        ..f.params(&milestones)
+    });
+
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.review_period, 2 * 24 * 3600);
+    assert_eq!(escrow.resubmit_cooldown, 3600);
+    assert_eq!(escrow.submission_cutoff, 1800);
+    assert_eq!(escrow.governance, Some(governance));
+
+    // Optional policies are held to the same bounds as their setters
+    let result = f.client.try_create(&CreateParams {
+        review_period: Some(3600),
+        ..f.params(&milestones)
+    });
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidDuration);
+}
+
+// ==================== COMPLETION RECORD TESTS ====================
+
+#[test]
+fn test_completion_streak_unlocks_achievement() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100]);
+
+    for i in 1..=5 {
+        let id = f.client.create(&f.params(&milestones));
+        f.client.start_work(&f.beneficiary, &id);
+        f.client.submit_milestone(&f.beneficiary, &id, &0);
+        f.client.approve_milestone(&f.depositor, &id, &0, &None);
+
+        let events = contract_event_names(&f);
+        let unlocked = events.contains(Symbol::new(&f.env, "achievement_unlocked"));
+        assert_eq!(unlocked, i == 5);
+    }
+
+    // A split ruling completes the escrow but breaks the streak
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &60, &f.hash(1));
+
+    let record = f.client.get_completion_record(&f.beneficiary);
+    assert_eq!(record.completed, 6);
+    assert_eq!(record.streak, 0);
+    assert_eq!(record.best_streak, 5);
+}
+
+// ==================== PAUSE TESTS ====================
+
+#[test]
+fn test_pause_blocks_mutations_but_not_pending_refunds() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let active = f.client.create(&f.params(&milestones));
+    let pending = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &active);
+
+    f.client.pause();
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "contract_paused")]
+    );
+    assert!(f.client.is_paused());
+
+    let result = f.client.try_submit_milestone(&f.beneficiary, &active, &0);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::Paused);
+    let result = f.client.try_create(&f.params(&milestones));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::Paused);
+
+    // Views and refunds of untouched escrows stay available
+    assert_eq!(f.client.get_escrow(&active).status, EscrowStatus::InProgress);
+    f.client.refund(&f.depositor, &pending, &None);
+
+    f.client.unpause();
+    f.client.submit_milestone(&f.beneficiary, &active, &0);
+}
+
+#[test]
+fn test_self_pause_blocks_new_escrows_until_timelock() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    f.client.self_pause(&f.beneficiary);
+    assert!(f.client.is_self_paused(&f.beneficiary));
+    let result = f.client.try_create(&f.params(&milestones));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::Paused);
+
+    let result = f.client.try_self_unpause(&f.beneficiary);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::TimelockActive);
+
+    let unpause_at = f.client.request_self_unpause(&f.beneficiary);
+    f.env.ledger().with_mut(|li| li.timestamp = unpause_at - 1);
+    let result = f.client.try_self_unpause(&f.beneficiary);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::TimelockActive);
+
+    f.env.ledger().with_mut(|li| li.timestamp = unpause_at);
+    f.client.self_unpause(&f.beneficiary);
+    assert!(!f.client.is_self_paused(&f.beneficiary));
+    f.client.create(&f.params(&milestones));
+}
+
+#[test]
+fn test_admin_actions_wait_for_timelock() {
+    let f = TestFixture::new();
+    let mut config = default_config(&f.admin);
+    config.timelock_delay = 2 * 24 * 3600;
+    f.set_config(&config);
+
+    config.fee_bps = 300;
+    let action_id = f.client.propose_action(&AdminAction::SetConfig(config.clone()));
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "admin_action_proposed")]
+    );
+
+    let result = f.client.try_execute_action(&action_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::TimelockActive);
+    assert_eq!(f.client.get_config().fee_bps, 0);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 2 * 24 * 3600);
+    f.client.execute_action(&action_id);
+    assert_eq!(f.client.get_config().fee_bps, 300);
+    assert_eq!(f.client.get_pending_action(&action_id), None);
+
+    // Cancelled actions can never run
+    let action_id = f.client.propose_action(&AdminAction::SetConfig(default_config(&f.admin)));
+    f.client.cancel_action(&action_id);
+    f.env.ledger().with_mut(|li| li.timestamp += 2 * 24 * 3600);
+    let result = f.client.try_execute_action(&action_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::ActionNotFound);
+}
+
+// ==================== UPGRADE TESTS ====================
+
+#[test]
+fn test_upgrade_is_timelocked() {
+    let f = TestFixture::new();
+    let mut config = default_config(&f.admin);
+    config.timelock_delay = 24 * 3600;
+    f.set_config(&config);
+
+    let action_id = f.client.upgrade(&f.hash(42));
+    assert_eq!(
+        f.client.get_pending_action(&action_id).unwrap().action,
+        AdminAction::Upgrade(f.hash(42))
+    );
+    let result = f.client.try_execute_action(&action_id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::TimelockActive);
+}
+
+#[test]
+fn test_migrate_rewrites_escrows() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+
+    assert_eq!(f.client.migrate(&vec![&f.env, id]), 2);
+    assert_eq!(f.client.get_schema_version(), 2);
+    assert_eq!(f.client.get_escrow(&id).total_amount, 1000);
+
+    let result = f.client.try_migrate(&vec![&f.env, id + 1]);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::EscrowNotFound);
+}
+
+/// Stand-in for a record written before the upgrade
+fn write_schema_1_record(f: &TestFixture, id: u32, current: &EscrowData) {
+    f.env.as_contract(&f.contract_id, || {
+        let old_milestones = Vec::from_iter(&f.env, current.milestones.iter().map(|m| MilestoneV1 {
+            description: m.description,
+            amount: m.amount,
+            status: m.status,
+            submitted_at: m.submitted_at,
+            approved_at: m.approved_at,
+            revision_requested_at: m.revision_requested_at,
+            rationale_hash: m.rationale_hash,
+            appealed: m.appealed,
+            checklist: m.checklist,
+            checklist_results: m.checklist_results,
+        }));
+        let old = EscrowDataV1 {
+            depositor: current.depositor.clone(),
+            beneficiary: current.beneficiary.clone(),
+            arbiter: current.arbiter.clone(),
+            token: current.token.clone(),
+            total_amount: current.total_amount,
+            paid_amount: current.paid_amount,
+            deadline: current.deadline,
+            status: current.status.clone(),
+            milestones: old_milestones,
+            work_started: current.work_started,
+            resubmit_cooldown: current.resubmit_cooldown,
+            close_reason: None,
+            frozen_until: 0,
+            private_events: false,
+            governance: None,
+            review_period: current.review_period,
+            disputed_since: None,
+            deadline_extended: 0,
+            submission_cutoff: current.submission_cutoff,
+            reported: false,
+            fee_bps: current.fee_bps,
+            fee_recipient: f.admin.clone(),
+        };
+        f.env.storage().persistent().set(&crate::escrow_key(id), &old);
+        f.env.storage().instance().set(&crate::sym_schema(), &1u32);
+    });
+}
+
+#[test]
+fn test_migrate_converts_schema_1_records() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 500]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    let current = f.client.get_escrow(&id);
+
+    write_schema_1_record(&f, id, &current);
+    assert!(f.client.try_get_escrow(&id).is_err());
+
+    assert_eq!(f.client.migrate(&vec![&f.env, id]), 2);
+    assert_eq!(f.client.get_schema_version(), 2);
+    let migrated = f.client.get_escrow(&id);
+    assert_eq!(migrated.total_amount, 1500);
+    assert_eq!(migrated.status, EscrowStatus::InProgress);
+    assert_eq!(migrated.milestones.get(0).unwrap().status, MilestoneStatus::Submitted);
+    assert_eq!(migrated.funding, FundingMode::Upfront);
+    assert_eq!(migrated.claim, ClaimState::Fixed);
+
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+}
+
+#[test]
+fn test_migrate_accounts_for_schema_1_escrows() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 500]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    let current = f.client.get_escrow(&id);
+
+    // A schema 1 escrow has none of the bookkeeping later versions keep beside the record
+    write_schema_1_record(&f, id, &current);
+    f.env.as_contract(&f.contract_id, || {
+        crate::adjust_locked(&f.env, &current.token, -1500);
+        f.env.storage().persistent().remove(&crate::status_key(id));
+        crate::index_remove(&f.env, &(crate::sym_by_status(), EscrowStatus::InProgress), id);
+        crate::index_remove(&f.env, &(crate::sym_by_depositor(), f.depositor.clone()), id);
+        crate::index_remove(&f.env, &(crate::sym_by_beneficiary(), f.beneficiary.clone()), id);
+    });
+    assert_eq!(f.client.verify_solvency(&f.token.address), 1500);
+    assert!(f.client.try_get_status(&id).is_err());
+
+    f.client.migrate(&vec![&f.env, id]);
+    assert_eq!(f.client.verify_solvency(&f.token.address), 0);
+    assert_eq!(f.client.get_status(&id), EscrowStatus::InProgress);
+    assert_eq!(f.client.list_escrows(&EscrowStatus::InProgress, &0, &10), vec![&f.env, id]);
+    assert_eq!(f.client.first_active_id(), Some(id));
+    assert_eq!(f.client.get_escrows_by_depositor(&f.depositor, &0, &10), vec![&f.env, id]);
+    assert_eq!(f.client.get_escrows_by_beneficiary(&f.beneficiary, &0, &10), vec![&f.env, id]);
+    let result = f.client.try_rescue_excess(&f.token.address, &f.admin);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::ZeroAmount);
+
+    // Migrating it again changes nothing
+    f.client.migrate(&vec![&f.env, id]);
+    assert_eq!(f.client.verify_solvency(&f.token.address), 0);
+    assert_eq!(f.client.list_escrows(&EscrowStatus::InProgress, &0, &10), vec![&f.env, id]);
+}
+
+// ==================== INSURANCE TESTS ====================
+
+#[test]
+fn test_insured_escrow_pays_quoted_premium() {
+    let f = TestFixture::new();
+    let mut config = default_config(&f.admin);
+    config.premium_bps = 200;
+    f.set_config(&config);
+
+    let milestones = f.create_milestone_amounts(&[1000, 1500]);
+    let params = CreateParams {
+        insured: true,
+        ..f.params(&milestones)
+    };
+    assert_eq!(f.client.quote_premium(&params), 50);
+
+    let id = f.client.create(&params);
+    assert!(f.client.get_escrow(&id).insured);
+    assert_eq!(f.token.balance(&f.depositor), 100_000 - 2500 - 50);
+    assert_eq!(f.client.get_insurance_pool(&f.token.address), 50);
+
+    // Uninsured escrows pay nothing extra
+    f.client.create(&f.params(&milestones));
+    assert_eq!(f.token.balance(&f.depositor), 100_000 - 2 * 2500 - 50);
+}
+
+// ==================== EVENT SEQUENCE TESTS ====================
+
+fn contract_event_seqs(f: &TestFixture) -> Vec<u64> {
+    let mut seqs = Vec::new(&f.env);
+    for (contract, _, data) in f.env.events().all().iter() {
+        if contract == f.contract_id {
+            let fields = Map::<Symbol, Val>::try_from_val(&f.env, &data).unwrap();
+            let seq = fields.get(Symbol::new(&f.env, "seq")).unwrap();
+            seqs.push_back(u64::try_from_val(&f.env, &seq).unwrap());
+        }
+    }
+    seqs
+}
+
+#[test]
+fn test_events_carry_gapless_sequence() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 500]);
+    // Initialization publishes the starting config
+    assert_eq!(f.client.last_event_seq(), 1);
+
+    let id = f.client.create(&f.params(&milestones));
+    assert_eq!(contract_event_seqs(&f), vec![&f.env, 2]);
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    assert_eq!(contract_event_seqs(&f), vec![&f.env, 5]);
+
+    // Failed calls roll back and never consume a number
+    assert!(f.client.try_submit_milestone(&f.beneficiary, &id, &0).is_err());
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(contract_event_seqs(&f), vec![&f.env, 6]);
+    assert_eq!(f.client.last_event_seq(), 6);
+}
+
+// ==================== AMOUNT BOUNDS TESTS ====================
+
+#[test]
+fn test_amount_bounds_enforced_per_token() {
+    let f = TestFixture::new();
+    let bounds = AmountBounds {
+        min_milestone: 100,
+        max_milestone: 5000,
+        min_total: 500,
+        max_total: 8000,
+    };
+    f.client.set_amount_bounds(&f.token.address, &bounds);
+    assert_eq!(f.client.get_amount_bounds(&f.token.address), Some(bounds));
+
+    for amounts in [&[50, 1000][..], &[6000][..], &[200, 200][..], &[5000, 4000][..]] {
+        let result = f.client.try_create(&f.params(&f.create_milestone_amounts(amounts)));
+        assert_eq!(result.unwrap_err().unwrap(), EscrowError::AmountOutOfBounds);
+    }
+
+    f.client.create(&f.params(&f.create_milestone_amounts(&[100, 5000])));
+}
+
+// ==================== RATE LOCK TESTS ====================
+
+#[test]
+fn test_forward_rate_locks_when_both_quote_it() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+
+    let result = f.client.try_lock_rate(&f.beneficiary, &id, &0, &10_500);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::MilestoneNotSubmitted);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    assert!(!f.client.lock_rate(&f.beneficiary, &id, &0, &10_500));
+    // A differing counter-quote replaces the proposal instead of locking
+    assert!(!f.client.lock_rate(&f.depositor, &id, &0, &10_400));
+    assert!(f.client.lock_rate(&f.beneficiary, &id, &0, &10_400));
+
+    let lock = f.client.get_rate_lock(&id, &0).unwrap();
+    assert_eq!(lock.rate, 10_400);
+    assert!(lock.locked_at.is_some());
+
+    let result = f.client.try_lock_rate(&f.depositor, &id, &0, &9_000);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AlreadyCompleted);
+}
+
+// ==================== BLOCKLIST TESTS ====================
+
+#[test]
+fn test_blocklist_rejects_creation_and_payouts() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+
+    f.client.block_address(&f.beneficiary);
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "address_blocked")]
+    );
+
+    let result = f.client.try_create(&f.params(&milestones));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::Blocked);
+    let result = f.client.try_approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::Blocked);
+
+    f.client.unblock_address(&f.beneficiary);
+    assert!(!f.client.is_blocked(&f.beneficiary));
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+}
+
+// ==================== INVARIANT TESTS ====================
+
+#[test]
+#[should_panic(expected = "paid amount exceeds approved milestones")]
+fn test_invariants_catch_corrupted_escrow() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+
+    f.env.as_contract(&f.contract_id, || {
+        let mut escrow = crate::load_escrow(&f.env, id).unwrap();
+        escrow.paid_amount = 500;
+        crate::store_escrow(&f.env, id, &escrow, &f.depositor);
+    });
+}
+
+// ==================== LATE PENALTY TESTS ====================
+
+#[test]
+fn test_late_submission_penalty_refunded_on_approval() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+    let id = f.client.create(&CreateParams {
+        duration: 30 * 24 * 3600,
+        milestone_deadlines: vec![&f.env, 5 * 24 * 3600, 10 * 24 * 3600],
+        penalty: PenaltySchedule {
+            bps_per_day: 200,
+            max_bps: 500,
+        },
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    // On time: no penalty
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+
+    // Two started days late costs 4%
+    f.env.ledger().with_mut(|li| li.timestamp += 11 * 24 * 3600 + 1);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    assert_eq!(f.client.get_escrow(&id).milestones.get(1).unwrap().penalty_bps, 400);
+
+    f.client.approve_milestone(&f.depositor, &id, &1, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 1000 + 960);
+    assert_eq!(f.token.balance(&f.depositor), 100_000 - 2000 + 40);
+
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.status, EscrowStatus::Released);
+    assert_eq!(escrow.paid_amount, 1960);
+    assert_eq!(f.client.get_funding_status(&id).refunded, 40);
+}
+
+// ==================== EARLY BONUS TESTS ====================
+
+#[test]
+fn test_early_bonus_paid_pro_rata_and_remainder_refunded() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 3000]);
+    let id = f.client.create(&CreateParams {
+        duration: 30 * 24 * 3600,
+        milestone_deadlines: vec![&f.env, 5 * 24 * 3600, 10 * 24 * 3600],
+        bonus: 400,
+        ..f.params(&milestones)
+    });
+    assert_eq!(f.token.balance(&f.contract_id), 4400);
+    f.client.start_work(&f.beneficiary, &id);
+
+    // Delivered early: earns its 1000/4000 share of the bonus
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 1100);
+    assert_eq!(f.client.get_escrow(&id).bonus_held, 300);
+
+    // Delivered late: no bonus, and the unearned 300 goes back at close
+    f.env.ledger().with_mut(|li| li.timestamp += 11 * 24 * 3600);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.approve_milestone(&f.depositor, &id, &1, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 4100);
+    assert_eq!(f.token.balance(&f.depositor), 100_000 - 4400 + 300);
+    assert_eq!(f.token.balance(&f.contract_id), 0);
+    assert_eq!(f.client.get_escrow(&id).bonus_held, 0);
+}
+
+#[test]
+fn test_refund_returns_unearned_bonus() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&CreateParams {
+        bonus: 250,
+        ..f.params(&milestones)
+    });
+
+    f.client.refund(&f.depositor, &id, &None);
+    assert_eq!(f.token.balance(&f.depositor), 100_000);
+    assert_eq!(f.token.balance(&f.contract_id), 0);
+}
+
+// ==================== TIP TESTS ====================
+
+#[test]
+fn test_approve_with_tip_pays_beneficiary_from_depositor() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+
+    f.client.approve_milestone(&f.depositor, &id, &0, &Some(150));
+    assert_eq!(f.token.balance(&f.beneficiary), 1150);
+    assert_eq!(f.token.balance(&f.depositor), 100_000 - 1150);
+    assert_eq!(f.token.balance(&f.contract_id), 0);
+    // The tip never touches escrowed funds
+    assert_eq!(f.client.get_escrow(&id).paid_amount, 1000);
+}
+
+#[test]
+fn test_tip_follows_the_milestone_payout() {
+    let f = TestFixture::new();
+    let wallet = Address::generate(&f.env);
+    let designer = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+    let id = f.client.create(&CreateParams {
+        milestone_recipients: vec![&f.env, Some(designer.clone()), None],
+        ..f.params(&milestones)
+    });
+    f.client.change_payout_address(&f.beneficiary, &id, &wallet);
+    f.client.start_work(&f.beneficiary, &id);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &Some(100));
+    assert_eq!(f.token.balance(&designer), 1100);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.approve_milestone(&f.depositor, &id, &1, &Some(50));
+    assert_eq!(f.token.balance(&wallet), 1050);
+    assert_eq!(f.token.balance(&f.beneficiary), 0);
+}
+
+#[test]
+fn test_zero_tip_rejected() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+
+    let result = f.client.try_approve_milestone(&f.depositor, &id, &0, &Some(0));
+    assert_eq!(result, Err(Ok(EscrowError::ZeroAmount)));
+}
+
+// ==================== TOP-UP TESTS ====================
+
+#[test]
+fn test_top_up_grows_final_milestone() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 2000]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+
+    assert_eq!(f.client.top_up(&f.depositor, &id, &500), 3500);
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.milestones.get(1).unwrap().amount, 2500);
+    assert_eq!(f.token.balance(&f.contract_id), 3500);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.approve_milestone(&f.depositor, &id, &1, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 3500);
+}
+
+#[test]
+fn test_top_up_requires_in_progress_depositor() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+
+    let result = f.client.try_top_up(&f.depositor, &id, &500);
+    assert_eq!(result, Err(Ok(EscrowError::AlreadyCompleted)));
+
+    f.client.start_work(&f.beneficiary, &id);
+    let result = f.client.try_top_up(&f.beneficiary, &id, &500);
+    assert_eq!(result, Err(Ok(EscrowError::NotAuthorized)));
+}
+
+// ==================== KILL FEE TESTS ====================
+
+#[test]
+fn test_cancel_after_start_pays_kill_fee() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+    let id = f.client.create(&CreateParams {
+        kill_fee_bps: 2500,
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    assert_eq!(f.client.cancel(&f.depositor, &id, &None), 500);
+    assert_eq!(f.token.balance(&f.beneficiary), 500);
+    assert_eq!(f.token.balance(&f.depositor), 100_000 - 500);
+
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.status, EscrowStatus::Refunded);
+    let funding = f.client.get_funding_status(&id);
+    assert_eq!(funding.paid, 500);
+    assert_eq!(funding.refunded, 1500);
+}
+
+#[test]
+fn test_cancel_blocked_once_submitted() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+    let id = f.client.create(&CreateParams {
+        kill_fee_bps: 2500,
+        ..f.params(&milestones)
+    });
+
+    let result = f.client.try_cancel(&f.depositor, &id, &None);
+    assert_eq!(result, Err(Ok(EscrowError::AlreadyCompleted)));
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    let result = f.client.try_cancel(&f.depositor, &id, &None);
+    assert_eq!(result, Err(Ok(EscrowError::MilestoneAlreadySubmitted)));
+}
+
+// ==================== REFERRAL TESTS ====================
+
+#[test]
+fn test_referral_cut_taken_from_each_payout() {
+    let f = TestFixture::new();
+    let referrer = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[1000, 2000]);
+    let id = f.client.create(&CreateParams {
+        referrer: Some(referrer.clone()),
+        referral_bps: 500,
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&referrer), 50);
+    assert_eq!(f.token.balance(&f.beneficiary), 950);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.approve_milestone(&f.depositor, &id, &1, &None);
+    assert_eq!(f.token.balance(&referrer), 150);
+    assert_eq!(f.token.balance(&f.beneficiary), 2850);
+}
+
+#[test]
+fn test_referral_bps_capped() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let result = f.client.try_create(&CreateParams {
+        referrer: Some(Address::generate(&f.env)),
+        referral_bps: 1001,
+        ..f.params(&milestones)
+    });
+    assert_eq!(result, Err(Ok(EscrowError::InvalidConfig)));
+}
+
+// ==================== MARKETPLACE TESTS ====================
+
+#[test]
+fn test_marketplace_fee_split_on_payout() {
+    let f = TestFixture::new();
+    f.set_config(&Config {
+        fee_bps: 100,
+        ..default_config(&f.admin)
+    });
+    let marketplace = Address::generate(&f.env);
+    f.client.register_marketplace(&marketplace);
+    f.client.set_marketplace_fee(&marketplace, &200);
+
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create_for_marketplace(&marketplace, &f.params(&milestones));
+    assert_eq!(f.client.get_escrow(&id).marketplace, Some(marketplace.clone()));
+
+    // Later fee changes don't affect escrows already created
+    f.client.set_marketplace_fee(&marketplace, &500);
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.client.get_accrued_fees(&f.token.address), 10);
+    assert_eq!(f.token.balance(&marketplace), 20);
+    assert_eq!(f.token.balance(&f.beneficiary), 970);
+}
+
+#[test]
+fn test_marketplace_must_be_registered() {
+    let f = TestFixture::new();
+    let marketplace = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[1000]);
+
+    let result = f.client.try_create_for_marketplace(&marketplace, &f.params(&milestones));
+    assert_eq!(result, Err(Ok(EscrowError::MarketplaceNotFound)));
+
+    f.client.register_marketplace(&marketplace);
+    let result = f.client.try_set_marketplace_fee(&marketplace, &1001);
+    assert_eq!(result, Err(Ok(EscrowError::InvalidConfig)));
+}
+
+// ==================== TREASURY TESTS ====================
+
+#[test]
+fn test_fees_accrue_until_withdrawn() {
+    let f = TestFixture::new();
+    let fee_recipient = Address::generate(&f.env);
+    f.set_config(&Config {
+        fee_bps: 200,
+        ..default_config(&fee_recipient)
+    });
+
+    let milestones = f.create_milestone_amounts(&[1000, 1500]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    for i in 0..2u32 {
+        f.client.submit_milestone(&f.beneficiary, &id, &i);
+        f.client.approve_milestone(&f.depositor, &id, &i, &None);
+    }
+    assert_eq!(f.client.get_accrued_fees(&f.token.address), 50);
+    assert_eq!(f.token.balance(&fee_recipient), 0);
+    assert_eq!(f.token.balance(&f.contract_id), 50);
+
+    let result = f.client.try_withdraw_fees(&f.token.address, &51);
+    assert_eq!(result, Err(Ok(EscrowError::InsufficientFees)));
+
+    f.client.withdraw_fees(&f.token.address, &30);
+    assert_eq!(f.token.balance(&fee_recipient), 30);
+    assert_eq!(f.client.get_accrued_fees(&f.token.address), 20);
+}
+
+// ==================== INSURANCE CLAIM TESTS ====================
+
+#[test]
+fn test_insurance_claim_after_ruling() {
+    let f = TestFixture::new();
+    let mut config = default_config(&f.admin);
+    config.premium_bps = 500;
+    f.set_config(&config);
+
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+    let id = f.client.create(&CreateParams {
+        insured: true,
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+
+    // No ruling yet, so nothing to compensate
+    let result = f.client.try_pay_insurance_claim(&id, &f.beneficiary, &50);
+    assert_eq!(result, Err(Ok(EscrowError::NotAuthorized)));
+
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &0, &f.hash(1));
+
+    let result = f.client.try_pay_insurance_claim(&id, &f.beneficiary, &101);
+    assert_eq!(result, Err(Ok(EscrowError::InsufficientPool)));
+
+    f.client.pay_insurance_claim(&id, &f.beneficiary, &80);
+    assert_eq!(f.token.balance(&f.beneficiary), 80);
+    assert_eq!(f.client.get_insurance_pool(&f.token.address), 20);
+
+    let result = f.client.try_pay_insurance_claim(&id, &f.beneficiary, &10);
+    assert_eq!(result, Err(Ok(EscrowError::AlreadyClaimed)));
+}
+
+// ==================== RESCUE TESTS ====================
+
+#[test]
+fn test_rescue_excess_leaves_escrowed_funds() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+    let id = f.client.create(&CreateParams {
+        bonus: 100,
+        ..f.params(&milestones)
+    });
+
+    let result = f.client.try_rescue_excess(&f.token.address, &f.admin);
+    assert_eq!(result, Err(Ok(EscrowError::ZeroAmount)));
+
+    // Someone sends tokens straight to the contract
+    f.token.transfer(&f.depositor, &f.contract_id, &300);
+    assert_eq!(f.client.rescue_excess(&f.token.address, &f.admin), 300);
+    assert_eq!(f.token.balance(&f.admin), 300);
+    assert_eq!(f.token.balance(&f.contract_id), 2100);
+
+    // The escrow still settles in full
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.approve_milestone(&f.depositor, &id, &1, &None);
+    assert_eq!(f.token.balance(&f.contract_id), 0);
+}
+
+// ==================== TOTAL LOCKED TESTS ====================
+
+#[test]
+fn test_total_locked_tracks_create_payout_and_refund() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 2000]);
+    let id = f.client.create(&f.params(&milestones));
+    let refundable = f.client.create(&CreateParams {
+        bonus: 100,
+        ..f.params(&milestones)
+    });
+    assert_eq!(f.client.get_total_locked(&f.token.address), 6100);
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.client.get_total_locked(&f.token.address), 5100);
+
+    f.client.top_up(&f.depositor, &id, &500);
+    assert_eq!(f.client.get_total_locked(&f.token.address), 5600);
+
+    f.client.refund(&f.depositor, &refundable, &None);
+    assert_eq!(f.client.get_total_locked(&f.token.address), 2500);
+    assert_eq!(f.token.balance(&f.contract_id), 2500);
+}
+
+// ==================== SOLVENCY TESTS ====================
+
+#[test]
+fn test_verify_solvency_reports_surplus_and_alerts_on_shortfall() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+    assert_eq!(f.client.verify_solvency(&f.token.address), 0);
+
+    f.token.transfer(&f.depositor, &f.contract_id, &25);
+    assert_eq!(f.client.verify_solvency(&f.token.address), 25);
+
+    // Simulate funds leaking out of the contract behind its back
+    f.token.transfer(&f.contract_id, &f.depositor, &125);
+    assert_eq!(f.client.verify_solvency(&f.token.address), -100);
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "solvency_alert")]
+    );
+    assert_eq!(f.client.get_escrow(&id).total_amount, 1000);
+}
+
+// ==================== CONFIGURABLE TTL TESTS ====================
+
+#[test]
+fn test_ttl_buffer_comes_from_config() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let short = f.client.create(&f.params(&milestones));
+    assert!((escrow_ttl(&f, short) as u64) * 5 < 60 * 24 * 3600);
+
+    f.set_config(&Config {
+        ttl_buffer: 90 * 24 * 3600,
+        ..default_config(&f.admin)
+    });
+    let long = f.client.create(&f.params(&milestones));
+    assert!((escrow_ttl(&f, long) as u64) * 5 >= 90 * 24 * 3600);
+
+    let result = f.client.try_propose_action(&AdminAction::SetConfig(Config {
+        ttl_buffer: 3600,
+        ..default_config(&f.admin)
+    }));
+    assert_eq!(result, Err(Ok(EscrowError::InvalidConfig)));
+}
+
+// ==================== RATE LIMIT TESTS ====================
+
+#[test]
+fn test_create_rate_limited_per_depositor() {
+    let f = TestFixture::new();
+    f.set_config(&Config {
+        create_limit: 2,
+        create_window: 600,
+        ..default_config(&f.admin)
+    });
+    let milestones = f.create_milestone_amounts(&[100]);
+
+    f.client.create(&f.params(&milestones));
+    f.client.create(&f.params(&milestones));
+    let result = f.client.try_create(&f.params(&milestones));
+    assert_eq!(result, Err(Ok(EscrowError::RateLimited)));
+
+    // Other depositors have their own allowance
+    let other = Address::generate(&f.env);
+    f.token.transfer(&f.depositor, &other, &100);
+    f.client.create(&CreateParams {
+        depositor: other,
+        ..f.params(&milestones)
+    });
+
+    f.env.ledger().with_mut(|li| li.timestamp += 600);
+    f.client.create(&f.params(&milestones));
+}
+
+// ==================== ARBITER REASSIGNMENT TESTS ====================
+
+#[test]
+fn test_reassign_arbiter_after_abandoned_dispute() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+
+    let replacement = Address::generate(&f.env);
+    let result = f.client.try_reassign_arbiter(&id, &replacement);
+    assert_eq!(result, Err(Ok(EscrowError::DisputePeriodActive)));
+
+    f.env.ledger().with_mut(|li| li.timestamp += 60 * 24 * 3600);
+    let result = f.client.try_reassign_arbiter(&id, &f.beneficiary);
+    assert_eq!(result, Err(Ok(EscrowError::InvalidArbiter)));
+
+    let action_id = f.client.reassign_arbiter(&id, &replacement);
+    f.client.execute_action(&action_id);
+    assert_eq!(f.client.get_escrow(&id).arbiter, replacement);
+
+    // The old arbiter is locked out; the new one can rule
+    let result = f.client.try_resolve_milestone_dispute(&f.arbiter, &id, &0, &1000, &f.hash(1));
+    assert_eq!(result, Err(Ok(EscrowError::NotAuthorized)));
+    f.client.resolve_milestone_dispute(&replacement, &id, &0, &1000, &f.hash(1));
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+}
+
+// ==================== ESCROW INDEX TESTS ====================
+
+#[test]
+fn test_escrows_by_depositor_paginated() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100]);
+    let mut ids = Vec::new(&f.env);
+    for _ in 0..5 {
+        ids.push_back(f.client.create(&f.params(&milestones)));
+    }
+
+    assert_eq!(f.client.get_escrows_by_depositor(&f.depositor, &0, &10), ids);
+    assert_eq!(f.client.get_escrows_by_depositor(&f.depositor, &1, &2), ids.slice(1..3));
+    assert_eq!(f.client.get_escrows_by_depositor(&f.depositor, &9, &2).len(), 0);
+    assert_eq!(f.client.get_escrows_by_depositor(&f.beneficiary, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_escrows_by_beneficiary_follow_rotation() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100]);
+    let first = f.client.create(&CreateParams {
+        duration: 30 * 24 * 3600,
+        ..f.params(&milestones)
+    });
+    let second = f.client.create(&f.params(&milestones));
+    // Naming a beneficiary doesn't list the escrow until they take it on
+    assert_eq!(f.client.get_escrows_by_beneficiary(&f.beneficiary, &0, &10).len(), 0);
+
+    f.client.start_work(&f.beneficiary, &first);
+    f.client.start_work(&f.beneficiary, &second);
+    assert_eq!(
+        f.client.get_escrows_by_beneficiary(&f.beneficiary, &0, &10),
+        vec![&f.env, first, second]
+    );
+
+    let replacement = Address::generate(&f.env);
+    f.client.attest_rotation(&f.depositor, &first, &replacement);
+    let ready_at = f.client.attest_rotation(&f.arbiter, &first, &replacement);
+    f.env.ledger().with_mut(|li| li.timestamp = ready_at);
+    f.client.complete_rotation(&first);
+
+    assert_eq!(
+        f.client.get_escrows_by_beneficiary(&f.beneficiary, &0, &10),
+        vec![&f.env, second]
+    );
+    assert_eq!(
+        f.client.get_escrows_by_beneficiary(&replacement, &0, &10),
+        vec![&f.env, first]
+    );
+}
+
+#[test]
+fn test_arbiter_dispute_queue() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100]);
+    let quiet = f.client.create(&f.params(&milestones));
+    let disputed = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &disputed);
+    f.client.submit_milestone(&f.beneficiary, &disputed, &0);
+    f.client.dispute_milestone(&f.depositor, &disputed, &0);
+
+    assert_eq!(
+        f.client.get_escrows_by_arbiter(&f.arbiter, &0, &10),
+        vec![&f.env, quiet, disputed]
+    );
+    assert_eq!(
+        f.client.get_disputes_by_arbiter(&f.arbiter, &0, &10),
+        vec![&f.env, disputed]
+    );
+
+    f.client.resolve_milestone_dispute(&f.arbiter, &disputed, &0, &100, &f.hash(1));
+    assert_eq!(f.client.get_disputes_by_arbiter(&f.arbiter, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_list_escrows_by_status() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100]);
+    let a = f.client.create(&f.params(&milestones));
+    let b = f.client.create(&f.params(&milestones));
+    assert_eq!(
+        f.client.list_escrows(&EscrowStatus::Pending, &0, &10),
+        vec![&f.env, a, b]
+    );
+
+    f.client.start_work(&f.beneficiary, &b);
+    f.client.submit_milestone(&f.beneficiary, &b, &0);
+    f.client.dispute_milestone(&f.depositor, &b, &0);
+    assert_eq!(f.client.list_escrows(&EscrowStatus::Pending, &0, &10), vec![&f.env, a]);
+    assert_eq!(f.client.list_escrows(&EscrowStatus::Disputed, &0, &10), vec![&f.env, b]);
+
+    f.client.resolve_milestone_dispute(&f.arbiter, &b, &0, &100, &f.hash(1));
+    assert_eq!(f.client.list_escrows(&EscrowStatus::Disputed, &0, &10).len(), 0);
+    assert_eq!(f.client.list_escrows(&EscrowStatus::Released, &0, &10).len(), 0);
+}
+
+#[test]
+fn test_status_index_spans_buckets() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[10]);
+    let ids: Vec<u32> = Vec::from_iter(&f.env, (0..120).map(|_| f.client.create(&f.params(&milestones))));
+    assert_eq!(f.client.list_escrows(&EscrowStatus::Pending, &0, &200), ids.slice(0..100));
+    assert_eq!(f.client.list_escrows(&EscrowStatus::Pending, &45, &10), ids.slice(45..55));
+    assert_eq!(f.client.list_escrows(&EscrowStatus::Pending, &100, &50), ids.slice(100..120));
+
+    // The last id fills the gap a closed escrow leaves
+    f.client.refund(&f.depositor, &ids.get(10).unwrap(), &None);
+    let pending = f.client.list_escrows(&EscrowStatus::Pending, &0, &10);
+    assert_eq!(pending.get(9), Some(ids.get(9).unwrap()));
+    assert_eq!(f.client.list_escrows(&EscrowStatus::Pending, &10, &1), vec![&f.env, ids.get(119).unwrap()]);
+    assert_eq!(f.client.list_escrows(&EscrowStatus::Pending, &100, &50).len(), 19);
+    assert_eq!(f.client.first_active_id(), Some(ids.get(0).unwrap()));
+}
+
+// ==================== BATCH READ TESTS ====================
+
+#[test]
+fn test_get_escrows_batch() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100]);
+    let small = f.client.create(&f.params(&milestones));
+    let large = f.client.create(&f.params(&f.create_milestone_amounts(&[500])));
+
+    let escrows = f.client.get_escrows(&vec![&f.env, large, 99, small]);
+    assert_eq!(escrows.len(), 3);
+    assert_eq!(escrows.get(0).unwrap().unwrap().total_amount, 500);
+    assert!(escrows.get(1).unwrap().is_none());
+    assert_eq!(escrows.get(2).unwrap().unwrap().total_amount, 100);
+}
+
+// ==================== SUMMARY VIEW TESTS ====================
+
+#[test]
+fn test_summary_counts_milestones_by_status() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100, 200, 300]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+
+    let summary = f.client.get_summary(&id);
+    assert_eq!(summary.status, EscrowStatus::InProgress);
+    assert_eq!(summary.total_amount, 600);
+    assert_eq!(summary.paid_amount, 100);
+    assert_eq!(summary.milestones, 3);
+    assert_eq!(
+        (summary.not_started, summary.submitted, summary.approved, summary.disputed),
+        (1, 1, 1, 0)
+    );
+    assert_eq!(summary.deadline, f.client.get_escrow(&id).deadline);
+}
+
+#[test]
+fn test_single_milestone_getters() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100, 200]);
+    let id = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+
+    assert_eq!(f.client.get_milestone_count(&id), 2);
+    let milestone = f.client.get_milestone(&id, &1);
+    assert_eq!(milestone.amount, 200);
+    assert_eq!(milestone.status, MilestoneStatus::Submitted);
+
+    let result = f.client.try_get_milestone(&id, &2);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidMilestone);
+}
+
+// ==================== CAPABILITY TESTS ====================
+
+#[test]
+fn test_capabilities_match_entrypoint_checks() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100]);
+    let id = f.client.create(&f.params(&milestones));
+
+    assert!(f.client.can_refund(&id, &f.depositor).allowed);
+    let denied = f.client.can_refund(&id, &f.beneficiary);
+    assert!(!denied.allowed);
+    assert_eq!(denied.reason, EscrowError::NotAuthorized as u32);
+
+    f.client.start_work(&f.beneficiary, &id);
+    assert_eq!(f.client.can_refund(&id, &f.depositor).reason, EscrowError::WorkStarted as u32);
+    assert!(f.client.can_submit(&id, &f.beneficiary, &0).allowed);
+    assert_eq!(
+        f.client.can_approve(&id, &f.depositor, &0).reason,
+        EscrowError::MilestoneNotSubmitted as u32
+    );
+
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    assert!(f.client.can_approve(&id, &f.depositor, &0).allowed);
+    assert_eq!(
+        f.client.can_submit(&id, &f.beneficiary, &0).reason,
+        EscrowError::MilestoneAlreadySubmitted as u32
+    );
+
+    f.client.pause();
+    assert_eq!(f.client.can_approve(&id, &f.depositor, &0).reason, EscrowError::Paused as u32);
+    assert_eq!(f.client.can_submit(&99, &f.beneficiary, &0).reason, EscrowError::EscrowNotFound as u32);
+}
+
+// ==================== PROGRESS VIEW TESTS ====================
+
+#[test]
+fn test_remaining_progress_and_time_views() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100, 300]);
+    let id = f.client.create(&f.params(&milestones));
+    assert_eq!(f.client.time_remaining(&id), 7200);
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    f.env.ledger().with_mut(|li| li.timestamp += 7000);
+
+    assert_eq!(f.client.get_remaining(&id), 300);
+    assert_eq!(
+        f.client.get_progress(&id),
+        Progress {
+            approved_milestones: 1,
+            total_milestones: 2,
+            approved_amount: 100,
+            total_amount: 400,
+        }
+    );
+    assert_eq!(f.client.time_remaining(&id), 200);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 1000);
+    assert_eq!(f.client.time_remaining(&id), 0);
+}
+
+// ==================== MICRO VIEW TESTS ====================
+
+#[test]
+fn test_exists_and_status_micro_views() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100]);
+    assert!(!f.client.exists(&1));
+    assert_eq!(f.client.try_get_status(&1), Err(Ok(EscrowError::EscrowNotFound)));
+
+    let id = f.client.create(&f.params(&milestones));
+    assert!(f.client.exists(&id));
+    assert_eq!(f.client.get_status(&id), EscrowStatus::Pending);
+
+    f.client.start_work(&f.beneficiary, &id);
+    assert_eq!(f.client.get_status(&id), EscrowStatus::InProgress);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.client.get_status(&id), EscrowStatus::Released);
+}
+
+// ==================== PLATFORM STATS TESTS ====================
+
+#[test]
+fn test_platform_stats_and_volume() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100]);
+    let done = f.client.create(&f.params(&milestones));
+    let refunded = f.client.create(&f.params(&milestones));
+    let disputed = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &done);
+    f.client.submit_milestone(&f.beneficiary, &done, &0);
+    f.client.approve_milestone(&f.depositor, &done, &0, &None);
+    f.client.refund(&f.depositor, &refunded, &None);
+    f.client.start_work(&f.beneficiary, &disputed);
+    f.client.top_up(&f.depositor, &disputed, &50);
+    f.client.submit_milestone(&f.beneficiary, &disputed, &0);
+    f.client.dispute_milestone(&f.depositor, &disputed, &0);
+
+    assert_eq!(
+        f.client.get_stats(),
+        PlatformStats {
+            created: 3,
+            completed: 1,
+            refunded: 1,
+            disputed: 1,
+        }
+    );
+    assert_eq!(f.client.get_volume(&f.token.address), 350);
+}
+
+#[test]
+fn test_user_stats_track_both_roles() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100, 200]);
+    let done = f.client.create(&f.params(&milestones));
+    f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &done);
+    for i in 0..2u32 {
+        f.client.submit_milestone(&f.beneficiary, &done, &i);
+        f.client.approve_milestone(&f.depositor, &done, &i, &None);
+    }
+
+    assert_eq!(
+        f.client.get_user_stats(&f.beneficiary),
+        UserStats {
+            as_client: 0,
+            as_freelancer: 2,
+            completed: 1,
+            total_earned: 300,
+            total_spent: 0,
+            completion_bps: 5000,
+        }
+    );
+    let client_stats = f.client.get_user_stats(&f.depositor);
+    assert_eq!(client_stats.as_client, 2);
+    assert_eq!(client_stats.total_spent, 300);
+}
+
+#[test]
+fn test_escrows_by_token() {
+    let f = TestFixture::new();
+    let other_token = f.env.register_stellar_asset_contract_v2(f.depositor.clone()).address();
+    token::StellarAssetClient::new(&f.env, &other_token).mint(&f.depositor, &1000);
+
+    let milestones = f.create_milestone_amounts(&[100]);
+    let first = f.client.create(&f.params(&milestones));
+    let other = f.client.create(&CreateParams {
+        token: other_token.clone(),
+        ..f.params(&milestones)
+    });
+    let second = f.client.create(&f.params(&milestones));
+
+    assert_eq!(
+        f.client.get_escrows_by_token(&f.token.address, &0, &10),
+        vec![&f.env, first, second]
+    );
+    assert_eq!(f.client.get_escrows_by_token(&other_token, &0, &10), vec![&f.env, other]);
+}
+
+#[test]
+fn test_token_index_pages_across_buckets() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[10]);
+    let ids: Vec<u32> = Vec::from_iter(&f.env, (0..130).map(|_| f.client.create(&f.params(&milestones))));
+
+    let mut listed: Vec<u32> = Vec::new(&f.env);
+    listed.append(&f.client.get_escrows_by_token(&f.token.address, &0, &100));
+    listed.append(&f.client.get_escrows_by_token(&f.token.address, &100, &100));
+    assert_eq!(listed, ids);
+    assert_eq!(f.client.get_escrows_by_token(&f.token.address, &48, &4), ids.slice(48..52));
+    assert_eq!(f.client.get_escrows_by_token(&f.token.address, &130, &10).len(), 0);
+}
+
+// ==================== AUDIT LOG TESTS ====================
+
+#[test]
+fn test_history_records_each_mutating_call() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[100]);
+    let id = f.client.create(&f.params(&milestones));
+    f.env.ledger().with_mut(|li| li.timestamp += 60);
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+
+    let history = f.client.get_history(&id, &0, &10);
+    let expected = ["create", "start_work", "submit_milestone", "approve_milestone"];
+    assert_eq!(history.len(), expected.len() as u32);
+    for (entry, name) in history.iter().zip(expected) {
+        assert_eq!(entry.action, Symbol::new(&f.env, name));
+    }
+    assert_eq!(
+        history.get(2).unwrap(),
+        HistoryEntry {
+            at: f.env.ledger().timestamp(),
+            actor: f.beneficiary.clone(),
+            action: Symbol::new(&f.env, "submit_milestone"),
+            milestone_index: Some(0),
+            reason: None,
+        }
+    );
+    assert_eq!(f.client.get_history(&id, &3, &10).len(), 1);
+}
+
+// ==================== GOVERNANCE EVENT TESTS ====================
+
+#[test]
+fn test_governance_changes_emit_events() {
+    let f = TestFixture::new();
+
+    f.set_config(&Config { fee_bps: 200, ..default_config(&f.admin) });
+    assert_eq!(
+        contract_event_names(&f),
+        vec![
+            &f.env,
+            Symbol::new(&f.env, "config_updated"),
+            Symbol::new(&f.env, "admin_action_executed")
+        ]
+    );
+
+    let bounds = AmountBounds {
+        min_milestone: 100,
+        max_milestone: 5000,
+        min_total: 500,
+        max_total: 8000,
+    };
+    f.client.set_amount_bounds(&f.token.address, &bounds);
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "amount_bounds_set")]
+    );
+
+    let new_admin = Address::generate(&f.env);
+    f.client.transfer_admin(&new_admin);
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "admin_transfer_proposed")]
+    );
+    f.client.accept_admin();
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "admin_transferred")]
+    );
+}
+
+// ==================== STATUS CHANGE EVENT TESTS ====================
+
+#[test]
+fn test_status_changes_publish_generic_event() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.start_work(&f.beneficiary, &id);
+    let (_, topics, data) = f.env.events().all().get(0).unwrap();
+    assert_eq!(
+        Symbol::try_from_val(&f.env, &topics.get(0).unwrap()).unwrap(),
+        Symbol::new(&f.env, "escrow_status_changed")
+    );
+    let fields = Map::<Symbol, Val>::try_from_val(&f.env, &data).unwrap();
+    let field = |name: &str| fields.get(Symbol::new(&f.env, name)).unwrap();
+    assert_eq!(
+        EscrowStatus::try_from_val(&f.env, &field("from")).unwrap(),
+        EscrowStatus::Pending
+    );
+    assert_eq!(
+        EscrowStatus::try_from_val(&f.env, &field("to")).unwrap(),
+        EscrowStatus::InProgress
+    );
+    assert_eq!(Address::try_from_val(&f.env, &field("actor")).unwrap(), f.beneficiary);
+
+    // Calls that leave the status alone stay quiet
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "milestone_submitted")]
+    );
+}
+
+// ==================== REFUND EVENT TESTS ====================
+
+#[test]
+fn test_refund_event_breaks_down_amount() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 500]);
+    let id = f.client.create(&f.params(&milestones));
+
+    let reason = BytesN::from_array(&f.env, &[7u8; 32]);
+    f.client.refund(&f.depositor, &id, &Some(reason.clone()));
+
+    let (_, topics, data) = f.env.events().all().last().unwrap();
+    assert_eq!(
+        Symbol::try_from_val(&f.env, &topics.get(0).unwrap()).unwrap(),
+        Symbol::new(&f.env, "escrow_refunded")
+    );
+    let fields = Map::<Symbol, Val>::try_from_val(&f.env, &data).unwrap();
+    let field = |name: &str| fields.get(Symbol::new(&f.env, name)).unwrap();
+    assert_eq!(Address::try_from_val(&f.env, &field("to")).unwrap(), f.depositor);
+    assert_eq!(i128::try_from_val(&f.env, &field("amount")).unwrap(), 1500);
+    assert_eq!(
+        Option::<BytesN<32>>::try_from_val(&f.env, &field("reason")).unwrap(),
+        Some(reason)
+    );
+    assert_eq!(
+        Vec::<u32>::try_from_val(&f.env, &field("milestones")).unwrap(),
+        vec![&f.env, 0u32, 1u32]
+    );
+
+    // Cancellation reports the refunded part too
+    let cancelled = f.client.create(&f.params(&milestones));
+    f.client.start_work(&f.beneficiary, &cancelled);
+    f.client.cancel(&f.depositor, &cancelled, &None);
+    assert!(contract_event_names(&f).contains(Symbol::new(&f.env, "escrow_refunded")));
+}
+
+// ==================== KEEPER PING TESTS ====================
+
+#[test]
+fn test_ping_announces_each_due_date_once() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000]);
+    let id = f.client.create(&CreateParams {
+        duration: 30 * 24 * 3600,
+        milestone_deadlines: vec![&f.env, 5 * 24 * 3600, 10 * 24 * 3600],
+        ..f.params(&milestones)
+    });
+    assert_eq!(f.client.ping(&id), 0);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 4 * 24 * 3600);
+    assert_eq!(f.client.ping(&id), 1);
+    assert_eq!(
+        contract_event_names(&f),
+        vec![&f.env, Symbol::new(&f.env, "deadline_approaching")]
+    );
+    assert_eq!(f.client.ping(&id), 0);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 5 * 24 * 3600);
+    assert_eq!(f.client.ping(&id), 1);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 20 * 24 * 3600);
+    assert_eq!(f.client.ping(&id), 1);
+    assert_eq!(f.client.ping(&id), 0);
+}
+
+// ==================== VERSION TESTS ====================
+
+#[test]
+fn test_version_recorded_and_refreshed_by_migrate() {
+    let f = TestFixture::new();
+    let current = (crate::CONTRACT_VERSION, Symbol::new(&f.env, "dev"));
+    assert_eq!(f.client.version(), current);
+
+    // Stand-in for a deployment still reporting the code it was initialized with
+    f.env.as_contract(&f.contract_id, || {
+        let old = (1u32, Symbol::new(&f.env, "abc1234"));
+        f.env.storage().instance().set(&crate::sym_version(), &old);
+    });
+    assert_eq!(f.client.version(), (1, Symbol::new(&f.env, "abc1234")));
+
+    f.client.migrate(&Vec::new(&f.env));
+    assert_eq!(f.client.version(), current);
+}
+
+// ==================== ID RANGE TESTS ====================
+
+#[test]
+fn test_id_range_skips_settled_escrows() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    assert_eq!(f.client.total_escrows(), 0);
+    assert_eq!(f.client.id_range(), None);
+
+    let first = f.client.create(&f.params(&milestones));
+    let second = f.client.create(&f.params(&milestones));
+    let third = f.client.create(&f.params(&milestones));
+    assert_eq!(f.client.total_escrows(), 3);
+    assert_eq!(f.client.id_range(), Some((first, third)));
+
+    f.client.refund(&f.depositor, &first, &None);
+    f.client.start_work(&f.beneficiary, &second);
+    assert_eq!(f.client.first_active_id(), Some(second));
+    assert_eq!(f.client.id_range(), Some((second, third)));
+
+    f.client.refund(&f.depositor, &third, &None);
+    f.client.submit_milestone(&f.beneficiary, &second, &0);
+    f.client.approve_milestone(&f.depositor, &second, &0, &None);
+    assert_eq!(f.client.first_active_id(), None);
+    assert_eq!(f.client.total_escrows(), 3);
+}
+
+// ==================== OPEN DISPUTE TESTS ====================
+
+#[test]
+fn test_open_disputes_per_escrow_and_globally() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 1000, 1000]);
+    let id = f.client.create(&f.params(&milestones));
+    let other = f.client.create(&f.params(&milestones));
+    assert_eq!(f.client.count_open_disputes(), 0);
+
+    for escrow in [id, other] {
+        f.client.start_work(&f.beneficiary, &escrow);
+        f.client.submit_milestone(&f.beneficiary, &escrow, &0);
+        f.client.submit_milestone(&f.beneficiary, &escrow, &2);
+    }
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+    f.client.dispute_milestone(&f.depositor, &id, &2);
+    f.client.dispute_milestone(&f.depositor, &other, &2);
+    assert_eq!(f.client.get_open_disputes(&id), vec![&f.env, 0u32, 2u32]);
+    assert_eq!(f.client.count_open_disputes(), 3);
+
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &500, &f.hash(1));
+    assert_eq!(f.client.get_open_disputes(&id), vec![&f.env, 2u32]);
+    assert_eq!(f.client.count_open_disputes(), 2);
+}
+
+// ==================== STREAMING TESTS ====================
+
+#[test]
+fn test_stream_vests_linearly_until_deadline() {
+    let f = TestFixture::new();
+    let amounts = f.create_milestone_amounts(&[10_000]);
+    let id = f.client.create(&CreateParams {
+        duration: 10_000,
+        mode: PayoutMode::Stream,
+        ..f.params(&amounts)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    let result = f.client.try_submit_milestone(&f.beneficiary, &id, &0);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WrongMode);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 2_500);
+    assert_eq!(f.client.claim_streamed(&f.beneficiary, &id), 2_500);
+    assert_eq!(f.token.balance(&f.beneficiary), 2_500);
+    let result = f.client.try_claim_streamed(&f.beneficiary, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::ZeroAmount);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 10_000);
+    assert_eq!(f.client.claim_streamed(&f.beneficiary, &id), 7_500);
+    assert_eq!(f.token.balance(&f.beneficiary), 10_000);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Released);
+}
+
+#[test]
+fn test_stream_stopped_only_through_dispute() {
+    let f = TestFixture::new();
+    let amounts = f.create_milestone_amounts(&[10_000]);
+    let id = f.client.create(&CreateParams {
+        duration: 10_000,
+        mode: PayoutMode::Stream,
+        ..f.params(&amounts)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+    f.env.ledger().with_mut(|li| li.timestamp += 4_000);
+    f.client.claim_streamed(&f.beneficiary, &id);
+
+    let result = f.client.try_cancel(&f.depositor, &id, &None);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WrongMode);
+
+    // Stopping the stream freezes claims until the arbiter splits the unclaimed rest
+    f.env.ledger().with_mut(|li| li.timestamp += 1_000);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+    assert!(f.client.try_claim_streamed(&f.beneficiary, &id).is_err());
+
+    let before = f.token.balance(&f.depositor);
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &1_000, &f.hash(1));
+    assert_eq!(f.token.balance(&f.beneficiary), 5_000);
+    assert_eq!(f.token.balance(&f.depositor), before + 5_000);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Released);
+}
+
+// ==================== VESTING TESTS ====================
+
+#[test]
+fn test_vesting_unlocks_after_cliff_in_periods() {
+    let f = TestFixture::new();
+    let amounts = f.create_milestone_amounts(&[12_000]);
+    let schedule = VestingSchedule {
+        cliff: 3_000,
+        period: 1_000,
+        amount_per_period: 3_000,
+    };
+    let id = f.client.create(&CreateParams {
+        duration: 10_000,
+        mode: PayoutMode::Vesting(schedule),
+        ..f.params(&amounts)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 2_999);
+    let result = f.client.try_claim_vested(&f.beneficiary, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::ZeroAmount);
+    let result = f.client.try_claim_streamed(&f.beneficiary, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WrongMode);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 1);
+    assert_eq!(f.client.claim_vested(&f.beneficiary, &id), 3_000);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 2_500);
+    assert_eq!(f.client.claim_vested(&f.beneficiary, &id), 6_000);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 500);
+    assert_eq!(f.client.claim_vested(&f.beneficiary, &id), 3_000);
+    assert_eq!(f.token.balance(&f.beneficiary), 12_000);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Released);
+}
+
+#[test]
+fn test_vesting_must_finish_within_duration() {
+    let f = TestFixture::new();
+    let amounts = f.create_milestone_amounts(&[12_000]);
+    let result = f.client.try_create(&CreateParams {
+        duration: 10_000,
+        mode: PayoutMode::Vesting(VestingSchedule {
+            cliff: 3_000,
+            period: 5_000,
+            amount_per_period: 3_000,
+        }),
+        ..f.params(&amounts)
+    });
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidDuration);
+}
+
+// ==================== SUBSCRIPTION TESTS ====================
+
+#[test]
+fn test_subscription_claims_elapsed_periods_and_cancel_refunds_rest() {
+    let f = TestFixture::new();
+    let periods = f.create_milestone_amounts(&[1000, 1000, 1000]);
+    let id = f.client.create(&CreateParams {
+        duration: 3600,
+        mode: PayoutMode::Subscription(1000),
+        ..f.params(&periods)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    let result = f.client.try_claim_period(&f.beneficiary, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::ZeroAmount);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 1500);
+    assert_eq!(f.client.claim_period(&f.beneficiary, &id), 1000);
+
+    // The second period has elapsed by cancellation, so only the third goes back
+    f.env.ledger().with_mut(|li| li.timestamp += 1000);
+    let before = f.token.balance(&f.depositor);
+    assert_eq!(f.client.cancel_subscription(&f.depositor, &id), 1000);
+    assert_eq!(f.token.balance(&f.depositor), before + 1000);
+
+    assert_eq!(f.client.claim_period(&f.beneficiary, &id), 1000);
+    assert_eq!(f.token.balance(&f.beneficiary), 2000);
+
+    // A cancelled subscription ends refunded, not as a completed engagement
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.status, EscrowStatus::Refunded);
+    assert_eq!(escrow.milestones.get(2).unwrap().status, MilestoneStatus::Refunded);
+    assert_eq!(f.client.get_progress(&id).approved_amount, 2000);
+    assert_eq!(f.client.get_completion_record(&f.beneficiary).completed, 0);
+    assert_eq!(f.client.verify_solvency(&f.token.address), 0);
+}
+
+#[test]
+fn test_subscription_periods_must_fit_duration() {
+    let f = TestFixture::new();
+    let periods = f.create_milestone_amounts(&[1000, 1000, 1000]);
+    let result = f.client.try_create(&CreateParams {
+        duration: 3600,
+        mode: PayoutMode::Subscription(2000),
+        ..f.params(&periods)
+    });
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidDuration);
+}
+
+// ==================== PAYROLL TESTS ====================
+
+#[test]
+fn test_payroll_claims_open_at_release_time() {
+    let f = TestFixture::new();
+    let alice = Address::generate(&f.env);
+    let bob = Address::generate(&f.env);
+    let release_at = f.env.ledger().timestamp() + 1000;
+    let id = f.client.create_payroll(
+        &f.depositor,
+        &f.token.address,
+        &vec![&f.env, (alice.clone(), 1000i128), (bob.clone(), 500i128)],
+        &release_at,
+    );
+    assert_eq!(f.client.get_total_locked(&f.token.address), 1500);
+
+    let result = f.client.try_claim_payroll(&alice, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotYetDue);
+    let result = f.client.try_claim_payroll(&f.beneficiary, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    f.env.ledger().with_mut(|li| li.timestamp = release_at);
+    assert_eq!(f.client.claim_payroll(&alice, &id), 1000);
+    assert_eq!(f.token.balance(&alice), 1000);
+    let result = f.client.try_claim_payroll(&alice, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AlreadyClaimed);
+    assert_eq!(f.client.get_total_locked(&f.token.address), 500);
+}
+
+#[test]
+fn test_payroll_release_pays_everyone_at_once() {
+    let f = TestFixture::new();
+    let alice = Address::generate(&f.env);
+    let bob = Address::generate(&f.env);
+    let id = f.client.create_payroll(
+        &f.depositor,
+        &f.token.address,
+        &vec![&f.env, (alice.clone(), 1000i128), (bob.clone(), 500i128)],
+        &(f.env.ledger().timestamp() + 30 * 24 * 3600),
+    );
+
+    let result = f.client.try_release_payroll(&alice, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    assert_eq!(f.client.release_payroll(&f.depositor, &id), 1500);
+    assert_eq!(f.token.balance(&alice), 1000);
+    assert_eq!(f.token.balance(&bob), 500);
+    assert_eq!(f.client.get_payroll(&id).unwrap().paid, vec![&f.env, true, true]);
+    assert_eq!(f.client.get_total_locked(&f.token.address), 0);
+}
+
+// ==================== TEAM SPLIT TESTS ====================
+
+#[test]
+fn test_team_splits_divide_each_payout() {
+    let f = TestFixture::new();
+    let partner = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[1000, 333]);
+    let id = f.client.create(&CreateParams {
+        splits: vec![&f.env, (f.beneficiary.clone(), 6000u32), (partner.clone(), 4000u32)],
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 600);
+    assert_eq!(f.token.balance(&partner), 400);
+
+    // Rounding dust goes to the last member so nothing stays behind
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.approve_milestone(&f.depositor, &id, &1, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 799);
+    assert_eq!(f.token.balance(&partner), 534);
+}
+
+#[test]
+fn test_team_splits_must_sum_to_whole() {
+    let f = TestFixture::new();
+    let partner = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let result = f.client.try_create(&CreateParams {
+        splits: vec![&f.env, (f.beneficiary.clone(), 6000u32), (partner.clone(), 3000u32)],
+        ..f.params(&milestones)
+    });
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidConfig);
+
+    let result = f.client.try_create(&CreateParams {
+        splits: vec![&f.env, (f.arbiter.clone(), 10_000u32)],
+        ..f.params(&milestones)
+    });
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidBeneficiary);
+}
+
+// ==================== MILESTONE RECIPIENT TESTS ====================
+
+#[test]
+fn test_milestone_recipient_overrides_beneficiary() {
+    let f = TestFixture::new();
+    let designer = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[1000, 500]);
+    let id = f.client.create(&CreateParams {
+        milestone_recipients: vec![&f.env, Some(designer.clone()), None],
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&designer), 1000);
+    assert_eq!(f.token.balance(&f.beneficiary), 0);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.approve_milestone(&f.depositor, &id, &1, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 500);
+
+    let result = f.client.try_create(&CreateParams {
+        milestone_recipients: vec![&f.env, Some(f.depositor.clone()), None],
+        ..f.params(&milestones)
+    });
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidBeneficiary);
+}
+
+// ==================== CROWDFUNDING TESTS ====================
+
+#[test]
+fn test_crowdfunded_escrow_starts_once_fully_funded() {
+    let f = TestFixture::new();
+    let backer = Address::generate(&f.env);
+    f.token_admin.mint(&backer, &1000);
+    let milestones = f.create_milestone_amounts(&[1500]);
+    let id = f.client.create(&CreateParams {
+        funding: FundingMode::Crowdfunded,
+        ..f.params(&milestones)
+    });
+    assert_eq!(f.token.balance(&f.depositor), 100_000);
+    assert_eq!(f.client.get_total_locked(&f.token.address), 0);
+
+    assert_eq!(f.client.contribute(&backer, &id, &1000), 500);
+    let result = f.client.try_start_work(&f.beneficiary, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotFunded);
+    let result = f.client.try_contribute(&f.depositor, &id, &501);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AmountOutOfBounds);
+
+    assert_eq!(f.client.contribute(&f.depositor, &id, &500), 0);
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 1500);
+}
+
+#[test]
+fn test_crowdfunded_refund_returns_each_contribution() {
+    let f = TestFixture::new();
+    let backer = Address::generate(&f.env);
+    f.token_admin.mint(&backer, &1000);
+    let milestones = f.create_milestone_amounts(&[3000]);
+    let id = f.client.create(&CreateParams {
+        funding: FundingMode::Crowdfunded,
+        ..f.params(&milestones)
+    });
+    f.client.contribute(&backer, &id, &1000);
+    f.client.contribute(&f.depositor, &id, &600);
+
+    // Only the lead depositor decides, and only what was raised goes back
+    assert!(f.client.try_refund(&backer, &id, &None).is_err());
+    f.client.refund(&f.depositor, &id, &None);
+    assert_eq!(f.token.balance(&backer), 1000);
+    assert_eq!(f.token.balance(&f.depositor), 100_000);
+    assert_eq!(f.client.get_total_locked(&f.token.address), 0);
+}
+
+#[test]
+fn test_crowdfunded_funding_status_counts_only_contributions() {
+    let f = TestFixture::new();
+    let backer = Address::generate(&f.env);
+    f.token_admin.mint(&backer, &1000);
+    let milestones = f.create_milestone_amounts(&[3000]);
+    let id = f.client.create(&CreateParams {
+        funding: FundingMode::Crowdfunded,
+        ..f.params(&milestones)
+    });
+    f.client.contribute(&backer, &id, &1000);
+
+    let status = f.client.get_funding_status(&id);
+    assert_eq!(status.funded, 1000);
+    assert_eq!(status.unfunded, 2000);
+    assert_eq!(status.held, 1000);
+    assert_eq!(status.held, f.token.balance(&f.contract_id));
+
+    f.client.refund(&f.depositor, &id, &None);
+    let status = f.client.get_funding_status(&id);
+    assert_eq!(status.funded, 1000);
+    assert_eq!(status.unfunded, 0);
+    assert_eq!(status.refunded, 1000);
+    assert_eq!(status.held, 0);
+}
+
+// ==================== PAY-AS-YOU-GO TESTS ====================
+
+#[test]
+fn test_pay_as_you_go_funds_one_milestone_at_a_time() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 2000]);
+    let id = f.client.create(&CreateParams {
+        funding: FundingMode::PerMilestone,
+        ..f.params(&milestones)
+    });
+    assert_eq!(f.token.balance(&f.depositor), 99_000);
+
+    let status = f.client.get_funding_status(&id);
+    assert_eq!(status.funded, 1000);
+    assert_eq!(status.unfunded, 2000);
+    assert_eq!(status.held, 1000);
+    assert_eq!(status.held, f.token.balance(&f.contract_id));
+
+    f.client.start_work(&f.beneficiary, &id);
+    let result = f.client.try_submit_milestone(&f.beneficiary, &id, &1);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotFunded);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+
+    assert_eq!(f.client.fund_milestone(&f.depositor, &id), 0);
+    assert_eq!(f.token.balance(&f.depositor), 97_000);
+    let result = f.client.try_fund_milestone(&f.depositor, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidMilestone);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &1);
+    f.client.approve_milestone(&f.depositor, &id, &1, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 3000);
+}
+
+#[test]
+fn test_pay_as_you_go_cancel_refunds_only_deposits() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 2000]);
+    let id = f.client.create(&CreateParams {
+        funding: FundingMode::PerMilestone,
+        kill_fee_bps: 1000,
+        ..f.params(&milestones)
+    });
+
+    f.client.start_work(&f.beneficiary, &id);
+    assert_eq!(f.client.cancel(&f.depositor, &id, &None), 300);
+    assert_eq!(f.token.balance(&f.beneficiary), 300);
+    assert_eq!(f.token.balance(&f.depositor), 99_700);
+    assert_eq!(f.token.balance(&f.contract_id), 0);
+
+    let status = f.client.get_funding_status(&id);
+    assert_eq!(status.funded, 1000);
+    assert_eq!(status.unfunded, 0);
+    assert_eq!(status.paid, 300);
+    assert_eq!(status.refunded, 700);
+}
+
+#[test]
+fn test_pay_as_you_go_expiry_refund_returns_first_deposit() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000, 2000]);
+    let id = f.client.create(&CreateParams {
+        funding: FundingMode::PerMilestone,
+        ..f.params(&milestones)
+    });
+
+    f.client.refund(&f.depositor, &id, &None);
+    assert_eq!(f.token.balance(&f.depositor), 100_000);
+    assert_eq!(f.client.get_funding_status(&id).refunded, 1000);
+}
+
+// ==================== BOUNTY TESTS ====================
+
+#[test]
+fn test_bounty_pays_the_picked_entry() {
+    let f = TestFixture::new();
+    let rival = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[2000]);
+    let id = f.client.create(&CreateParams {
+        mode: PayoutMode::Bounty,
+        ..f.params(&milestones)
+    });
+
+    f.client.submit_entry(&f.beneficiary, &id, &f.hash(1));
+    f.client.submit_entry(&rival, &id, &f.hash(2));
+    let result = f.client.try_submit_entry(&rival, &id, &f.hash(3));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::MilestoneAlreadySubmitted);
+    let result = f.client.try_submit_entry(&f.depositor, &id, &f.hash(4));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+    assert_eq!(f.client.get_entries(&id).len(), 2);
+
+    let outsider = Address::generate(&f.env);
+    let result = f.client.try_pick_winner(&f.depositor, &id, &outsider);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidBeneficiary);
+
+    f.client.pick_winner(&f.depositor, &id, &rival);
+    assert_eq!(f.token.balance(&rival), 2000);
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.beneficiary, rival);
+    assert_eq!(escrow.status, EscrowStatus::Released);
+    assert_eq!(f.client.get_escrows_by_beneficiary(&rival, &0, &10).len(), 1);
+}
+
+#[test]
+fn test_bounty_refund_waits_for_deadline_once_entered() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[2000]);
+    let id = f.client.create(&CreateParams {
+        mode: PayoutMode::Bounty,
+        ..f.params(&milestones)
+    });
+    f.client.submit_entry(&f.beneficiary, &id, &f.hash(1));
+
+    let result = f.client.try_refund(&f.depositor, &id, &None);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotYetDue);
+
+    // No winner by the deadline: entries close and the depositor takes the funds back
+    f.env.ledger().with_mut(|li| li.timestamp += 7200);
+    let result = f.client.try_submit_entry(&f.beneficiary, &id, &f.hash(2));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::SubmissionWindowClosed);
+    f.client.refund(&f.depositor, &id, &None);
+    assert_eq!(f.token.balance(&f.depositor), 100_000);
+}
+
+// ==================== HOURLY TESTS ====================
+
+#[test]
+fn test_hourly_pays_approved_hours_up_to_the_cap() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&CreateParams {
+        mode: PayoutMode::Hourly(100),
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    assert_eq!(f.client.log_time(&f.beneficiary, &id, &6, &f.hash(1)), 0);
+    // Pending time counts against the cap
+    let result = f.client.try_log_time(&f.beneficiary, &id, &5, &f.hash(2));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AmountOutOfBounds);
+    let result = f.client.try_submit_milestone(&f.beneficiary, &id, &0);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::WrongMode);
+
+    assert_eq!(f.client.approve_time(&f.depositor, &id, &0), 600);
+    assert_eq!(f.token.balance(&f.beneficiary), 600);
+    let result = f.client.try_approve_time(&f.depositor, &id, &0);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::MilestoneNotSubmitted);
+
+    f.client.log_time(&f.beneficiary, &id, &4, &f.hash(2));
+    f.client.approve_time(&f.depositor, &id, &1);
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Released);
+    assert_eq!(f.client.get_timesheets(&id).len(), 2);
+}
+
+#[test]
+fn test_disputed_timesheet_goes_to_arbitration() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&CreateParams {
+        mode: PayoutMode::Hourly(100),
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.log_time(&f.beneficiary, &id, &3, &f.hash(1));
+    f.client.approve_time(&f.depositor, &id, &0);
+    f.client.log_time(&f.beneficiary, &id, &5, &f.hash(2));
+
+    f.client.dispute_time(&f.depositor, &id, &1);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Disputed);
+
+    // The ruling splits only the disputed sheet's 500
+    let result = f.client.try_resolve_milestone_dispute(&f.arbiter, &id, &0, &600, &f.hash(3));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidMilestone);
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &200, &f.hash(3));
+    assert_eq!(f.token.balance(&f.beneficiary), 500);
+    assert_eq!(f.token.balance(&f.depositor), 99_300);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::InProgress);
+
+    // The last 200 of the cap can still be billed
+    f.client.log_time(&f.beneficiary, &id, &2, &f.hash(4));
+    f.client.approve_time(&f.depositor, &id, &2);
+    assert_eq!(f.token.balance(&f.beneficiary), 700);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Released);
+}
+
+#[test]
+fn test_ruling_on_timesheet_leaves_other_sheets_open() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&CreateParams {
+        mode: PayoutMode::Hourly(100),
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.log_time(&f.beneficiary, &id, &3, &f.hash(1));
+    f.client.log_time(&f.beneficiary, &id, &2, &f.hash(2));
+
+    f.client.dispute_time(&f.depositor, &id, &0);
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &100, &f.hash(3));
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::InProgress);
+    let sheets = f.client.get_timesheets(&id);
+    assert_eq!(sheets.get(0).unwrap().status, MilestoneStatus::Approved);
+    assert_eq!(sheets.get(1).unwrap().status, MilestoneStatus::Submitted);
+
+    f.client.approve_time(&f.depositor, &id, &1);
+    assert_eq!(f.token.balance(&f.beneficiary), 300);
+    assert_eq!(f.token.balance(&f.depositor), 99_200);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::InProgress);
+    assert_eq!(f.client.verify_solvency(&f.token.address), 0);
+}
+
+// ==================== PAYMENT REQUEST TESTS ====================
+
+#[test]
+fn test_payment_request_paid_directly() {
+    let f = TestFixture::new();
+    let id = f.client.request_payment(
+        &f.beneficiary,
+        &f.depositor,
+        &800,
+        &f.token.address,
+        &f.hash(1),
+    );
+    let result = f.client.try_pay_request(&f.beneficiary, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    f.client.pay_request(&f.depositor, &id);
+    assert_eq!(f.token.balance(&f.beneficiary), 800);
+    assert_eq!(f.client.get_request(&id).unwrap().status, RequestStatus::Paid);
+    let result = f.client.try_decline_request(&f.beneficiary, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AlreadyCompleted);
+}
+
+#[test]
+fn test_payment_request_funded_into_escrow() {
+    let f = TestFixture::new();
+    let id = f.client.request_payment(
+        &f.beneficiary,
+        &f.depositor,
+        &800,
+        &f.token.address,
+        &f.hash(1),
+    );
+    let escrow_id = f.client.escrow_request(&f.depositor, &id, &Some(f.arbiter.clone()), &7200);
+    assert_eq!(
+        f.client.get_request(&id).unwrap().status,
+        RequestStatus::Escrowed(escrow_id)
+    );
+    assert_eq!(f.client.get_escrow(&escrow_id).total_amount, 800);
+
+    f.client.start_work(&f.beneficiary, &escrow_id);
+    f.client.submit_milestone(&f.beneficiary, &escrow_id, &0);
+    f.client.approve_milestone(&f.depositor, &escrow_id, &0, &None);
+    assert_eq!(f.token.balance(&f.beneficiary), 800);
+}
+
+// ==================== DIRECT PAYMENT TESTS ====================
+
+#[test]
+fn test_pay_direct_transfers_and_tracks_stats() {
+    let f = TestFixture::new();
+    f.client.pay_direct(&f.depositor, &f.beneficiary, &f.token.address, &300, &f.hash(1));
+    assert_eq!(f.token.balance(&f.beneficiary), 300);
+    assert_eq!(f.client.get_user_stats(&f.depositor).total_spent, 300);
+    assert_eq!(f.client.get_user_stats(&f.beneficiary).total_earned, 300);
+    assert_eq!(f.client.get_volume(&f.token.address), 300);
+
+    let result =
+        f.client.try_pay_direct(&f.depositor, &f.depositor, &f.token.address, &300, &f.hash(1));
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidBeneficiary);
+}
+
+// ==================== SCHEDULED PAYMENT TESTS ====================
+
+#[test]
+fn test_scheduled_payment_executes_after_unlock() {
+    let f = TestFixture::new();
+    let unlock_at = f.env.ledger().timestamp() + 3600;
+    let id = f.client.schedule_payment(
+        &f.depositor,
+        &f.beneficiary,
+        &f.token.address,
+        &500,
+        &unlock_at,
+    );
+    assert_eq!(f.client.get_total_locked(&f.token.address), 500);
+
+    let result = f.client.try_execute_payment(&id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotYetDue);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 3600);
+    let result = f.client.try_cancel_scheduled(&f.depositor, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+    f.client.execute_payment(&id);
+    assert_eq!(f.token.balance(&f.beneficiary), 500);
+    assert_eq!(f.client.get_scheduled(&id).unwrap().state, ScheduleState::Paid);
+    assert_eq!(f.client.get_total_locked(&f.token.address), 0);
+}
+
+#[test]
+fn test_scheduled_payment_cancelled_before_unlock() {
+    let f = TestFixture::new();
+    let unlock_at = f.env.ledger().timestamp() + 3600;
+    let id = f.client.schedule_payment(
+        &f.depositor,
+        &f.beneficiary,
+        &f.token.address,
+        &500,
+        &unlock_at,
+    );
+
+    f.client.cancel_scheduled(&f.depositor, &id);
+    assert_eq!(f.token.balance(&f.depositor), 100_000);
+    f.env.ledger().with_mut(|li| li.timestamp += 3600);
+    let result = f.client.try_execute_payment(&id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AlreadyCompleted);
+}
+
+// ==================== PERCENT MILESTONE TESTS ====================
+
+#[test]
+fn test_create_percent_gives_dust_to_last_milestone() {
+    let f = TestFixture::new();
+    let shares = vec![&f.env, 3333u32, 3333, 3334];
+    let id = f.client.create_percent(&f.params(&Vec::new(&f.env)), &1000, &shares);
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.total_amount, 1000);
+    assert_eq!(escrow.milestones.get(0).unwrap().amount, 333);
+    assert_eq!(escrow.milestones.get(1).unwrap().amount, 333);
+    assert_eq!(escrow.milestones.get(2).unwrap().amount, 334);
+
+    let shares = vec![&f.env, 5000u32, 4000];
+    let result = f.client.try_create_percent(&f.params(&Vec::new(&f.env)), &1000, &shares);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidMilestone);
+}
+
+// ==================== RETAINER TESTS ====================
+
+#[test]
+fn test_retainer_releases_undisputed_periods() {
+    let f = TestFixture::new();
+    let periods = f.create_milestone_amounts(&[500, 500, 500, 500]);
+    let id = f.client.create(&CreateParams {
+        duration: 4000,
+        mode: PayoutMode::Retainer(1000),
+        ..f.params(&periods)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    // The first period is held back for arbitration
+    f.env.ledger().with_mut(|li| li.timestamp += 1500);
+    f.client.dispute_milestone(&f.depositor, &id, &0);
+    let result = f.client.try_release_retainer(&id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::ZeroAmount);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 600);
+    assert_eq!(f.client.release_retainer(&id), 500);
+    f.client.resolve_milestone_dispute(&f.arbiter, &id, &0, &200, &f.hash(1));
+    assert_eq!(f.token.balance(&f.beneficiary), 700);
+}
+
+#[test]
+fn test_retainer_termination_gives_one_period_notice() {
+    let f = TestFixture::new();
+    let periods = f.create_milestone_amounts(&[500, 500, 500, 500]);
+    let id = f.client.create(&CreateParams {
+        duration: 4000,
+        mode: PayoutMode::Retainer(1000),
+        ..f.params(&periods)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    // Notice runs to 2500: the second period still pays, the last two go back
+    f.env.ledger().with_mut(|li| li.timestamp += 1500);
+    assert_eq!(f.client.terminate_retainer(&f.beneficiary, &id), 1000);
+    assert_eq!(f.token.balance(&f.depositor), 99_000);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 500);
+    assert_eq!(f.client.release_retainer(&id), 1000);
+    let escrow = f.client.get_escrow(&id);
+    assert_eq!(escrow.status, EscrowStatus::Refunded);
+    assert_eq!(escrow.milestones.get(3).unwrap().status, MilestoneStatus::Refunded);
+    assert_eq!(f.client.get_summary(&id).refunded, 2);
+    assert_eq!(f.client.get_progress(&id).approved_amount, 1000);
+    assert_eq!(f.client.get_completion_record(&f.beneficiary).completed, 0);
+}
+
+// ==================== CLAIM TRANSFER TESTS ====================
+
+#[test]
+fn test_transferred_claim_receives_payouts() {
+    let f = TestFixture::new();
+    let factor = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&CreateParams {
+        transferable: true,
+        ..f.params(&milestones)
+    });
+
+    f.client.transfer_claim(&f.beneficiary, &id, &factor);
+    assert_eq!(f.client.get_escrow(&id).claim, ClaimState::Held(factor.clone()));
+    // Only the new holder can sell it on
+    let result = f.client.try_transfer_claim(&f.beneficiary, &id, &f.arbiter);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&factor), 1000);
+    assert_eq!(f.token.balance(&f.beneficiary), 0);
+}
+
+#[test]
+fn test_claim_transfer_needs_depositor_consent() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+    let factor = Address::generate(&f.env);
+    let result = f.client.try_transfer_claim(&f.beneficiary, &id, &factor);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+}
+
+// ==================== PAYOUT ADDRESS TESTS ====================
+
+#[test]
+fn test_changed_payout_address_receives_payouts() {
+    let f = TestFixture::new();
+    let wallet = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.change_payout_address(&f.beneficiary, &id, &wallet);
+    assert_eq!(f.client.get_payout_address(&id), wallet);
+    let result = f.client.try_change_payout_address(&f.depositor, &id, &wallet);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+
+    // The beneficiary still does the work under its own address
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+    assert_eq!(f.token.balance(&wallet), 1000);
+    assert_eq!(f.token.balance(&f.beneficiary), 0);
+}
+
+#[test]
+fn test_payout_address_change_cosigned_when_configured() {
+    let f = TestFixture::new();
+    f.set_config(&Config {
+        payout_cosign: true,
+        ..default_config(&f.admin)
+    });
+    let wallet = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+
+    f.client.change_payout_address(&f.beneficiary, &id, &wallet);
+    let signers: Vec<Address> = Vec::from_iter(&f.env, f.env.auths().into_iter().map(|(a, _)| a));
+    assert!(signers.contains(&f.beneficiary));
+    assert!(signers.contains(&f.depositor));
+}
+
+// ==================== DEPOSITOR TRANSFER TESTS ====================
+
+#[test]
+fn test_transferred_depositor_rights_move_refunds_and_control() {
+    let f = TestFixture::new();
+    let org = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&f.params(&milestones));
+
+    let result = f.client.try_transfer_depositor_rights(&f.depositor, &id, &f.depositor);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidBeneficiary);
+    f.client.transfer_depositor_rights(&f.depositor, &id, &org);
+    let signers: Vec<Address> = Vec::from_iter(&f.env, f.env.auths().into_iter().map(|(a, _)| a));
+    assert!(signers.contains(&f.beneficiary));
+    assert_eq!(f.client.get_escrow(&id).depositor, org);
+    assert_eq!(f.client.get_escrows_by_depositor(&org, &0, &10).len(), 1);
+
+    let result = f.client.try_refund(&f.depositor, &id, &None);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+    f.client.refund(&org, &id, &None);
+    assert_eq!(f.token.balance(&org), 1000);
+}
+
+// ==================== PROJECT TESTS ====================
+
+#[test]
+fn test_project_groups_escrows_and_sums_them() {
+    let f = TestFixture::new();
+    let project = f.client.create_project(&f.depositor, &f.hash(1));
+    let first = f.create_milestone_amounts(&[1000]);
+    let second = f.create_milestone_amounts(&[400, 600]);
+    let a = f.client.create(&CreateParams { project: Some(project), ..f.params(&first) });
+    let b = f.client.create(&CreateParams { project: Some(project), ..f.params(&second) });
+    f.client.create(&f.params(&first));
+
+    f.client.start_work(&f.beneficiary, &a);
+    f.client.submit_milestone(&f.beneficiary, &a, &0);
+    f.client.approve_milestone(&f.depositor, &a, &0, &None);
+
+    assert_eq!(f.client.get_project_escrows(&project, &0, &10), vec![&f.env, a, b]);
+    assert_eq!(
+        f.client.get_project_summary(&project),
+        ProjectSummary {
+            escrows: 2,
+            active: 1,
+            completed: 1,
+            refunded: 0,
+            disputed: 0,
+            total_amount: 2000,
+            paid_amount: 1000,
+        }
+    );
+
+    // Only the owner can file escrows under it
+    let result = f.client.try_create(&CreateParams {
+        depositor: f.beneficiary.clone(),
+        beneficiary: f.depositor.clone(),
+        project: Some(project),
+        ..f.params(&first)
+    });
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::NotAuthorized);
+}
+
+// ==================== PAYOUT TAIL TESTS ====================
+
+#[test]
+fn test_payout_tail_accrues_after_approval() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&CreateParams {
+        payout_tail: PayoutTail { upfront_bps: 2000, duration: 7 * 24 * 3600 },
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    f.client.approve_milestone(&f.depositor, &id, &0, &None);
+
+    assert_eq!(f.token.balance(&f.beneficiary), 200);
+    assert_eq!(f.client.get_total_locked(&f.token.address), 800);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Released);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 7 * 12 * 3600);
+    assert_eq!(f.client.claim_accrued(&f.beneficiary, &id), 400);
+    let result = f.client.try_claim_accrued(&f.beneficiary, &id);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::ZeroAmount);
+
+    f.env.ledger().with_mut(|li| li.timestamp += 7 * 12 * 3600);
+    assert_eq!(f.client.claim_accrued(&f.beneficiary, &id), 400);
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+    assert_eq!(f.client.get_total_locked(&f.token.address), 0);
+    assert!(f.client.get_accruals(&id).is_empty());
+}
+
+// ==================== CHECKLIST PROGRESS TESTS ====================
+
+#[test]
+fn test_beneficiary_marks_checklist_items_before_submitting() {
+    let f = TestFixture::new();
+    let milestones = f.create_milestone_amounts(&[1000]);
+    let id = f.client.create(&CreateParams {
+        checklists: vec![&f.env, vec![&f.env, f.hash(1), f.hash(2)]],
+        ..f.params(&milestones)
+    });
+    f.client.start_work(&f.beneficiary, &id);
+
+    f.client.mark_checklist_item(&f.beneficiary, &id, &0, &1, &true);
+    assert_eq!(f.client.get_milestone(&id, &0).checklist_done, vec![&f.env, false, true]);
+    let result = f.client.try_mark_checklist_item(&f.beneficiary, &id, &0, &2, &true);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::InvalidMilestone);
+
+    f.client.submit_milestone(&f.beneficiary, &id, &0);
+    let result = f.client.try_mark_checklist_item(&f.beneficiary, &id, &0, &0, &true);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::MilestoneAlreadySubmitted);
+}
+
+#[test]
+fn test_bounty_prize_split_among_finalists() {
+    let f = TestFixture::new();
+    let second = Address::generate(&f.env);
+    let third = Address::generate(&f.env);
+    let milestones = f.create_milestone_amounts(&[2000]);
+    let id = f.client.create(&CreateParams {
+        mode: PayoutMode::Bounty,
+        ..f.params(&milestones)
+    });
+    f.client.submit_entry(&f.beneficiary, &id, &f.hash(1));
+    f.client.submit_entry(&second, &id, &f.hash(2));
+    f.client.submit_entry(&third, &id, &f.hash(3));
+
+    let over = vec![&f.env, (f.beneficiary.clone(), 1500), (second.clone(), 600)];
+    let result = f.client.try_pick_winners(&f.depositor, &id, &over);
+    assert_eq!(result.unwrap_err().unwrap(), EscrowError::AmountOutOfBounds);
+
+    let winners = vec![&f.env, (f.beneficiary.clone(), 1000), (second.clone(), 600)];
+    f.client.pick_winners(&f.depositor, &id, &winners);
+    assert_eq!(f.token.balance(&f.beneficiary), 1000);
+    assert_eq!(f.token.balance(&second), 600);
+    assert_eq!(f.token.balance(&third), 0);
+    assert_eq!(f.token.balance(&f.depositor), 98_400);
+    assert_eq!(f.client.get_escrow(&id).status, EscrowStatus::Released);
+    assert_eq!(f.client.get_total_locked(&f.token.address), 0);
+}